@@ -1,21 +1,48 @@
 use async_stream::try_stream;
 use async_trait::async_trait;
+use forgeai_core::http::{build_http_client, env_proxy, env_timeout, extra_header_map, timed_out};
 use forgeai_core::{
-    AdapterInfo, CapabilityMatrix, ChatAdapter, ChatRequest, ChatResponse, ForgeError, Role,
-    StreamEvent, StreamResult, ToolCall, Usage,
+    http, parse_base_url, telemetry, truncate_chars, AdapterInfo, CapabilityMatrix, ChatAdapter,
+    ChatRequest, ChatResponse, ContentPart, FinishReason, ForgeError, HttpConfig, Message,
+    MessageContent, ProviderError, ResponseFormat, ResponseTiming, RetryConfig, Role, StreamEvent,
+    StreamResult, ToolCall, ToolChoice, TraceContentOptions, Usage, DEFAULT_ERROR_BODY_LIMIT,
 };
 use futures_util::StreamExt;
-use reqwest::{Client as HttpClient, StatusCode};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Client as HttpClient, NoProxy, Proxy, Response, StatusCode};
 use serde_json::{json, Map, Value};
+use std::collections::HashMap;
 use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use url::Url;
 
+const ANTHROPIC_BETA: &str = "anthropic-beta";
+const PROMPT_CACHING_BETA: &str = "prompt-caching-2024-07-31";
+
 #[derive(Clone, Debug)]
 pub struct AnthropicAdapter {
     pub api_key: String,
     pub base_url: Url,
     pub api_version: String,
+    /// `max_tokens` sent when a request leaves it unset; Anthropic requires the field.
+    pub default_max_tokens: u32,
+    /// Joins multiple system messages into the provider's single system prompt.
+    pub system_separator: String,
+    /// Attach `ResponseTiming` to responses returned by `chat`.
+    pub collect_timing: bool,
+    trace_content: TraceContentOptions,
+    timeout: Option<Duration>,
+    retry: Option<RetryConfig>,
+    http_config: Option<HttpConfig>,
+    proxy: Option<Proxy>,
+    error_body_limit: usize,
+    capabilities: CapabilityMatrix,
+    attempts: Arc<AtomicUsize>,
     client: HttpClient,
+    /// Set by `with_http_client`; the client is never rebuilt.
+    shared_client: bool,
 }
 
 impl AnthropicAdapter {
@@ -26,27 +53,183 @@ impl AnthropicAdapter {
     }
 
     pub fn with_base_url(api_key: impl Into<String>, base_url: Url) -> Result<Self, ForgeError> {
-        let client = HttpClient::builder()
-            .build()
-            .map_err(|e| ForgeError::Internal(format!("failed to build http client: {e}")))?;
-        Ok(Self {
+        let mut adapter =
+            Self::with_http_client(api_key, base_url, build_http_client(None, None, None)?);
+        adapter.shared_client = false;
+        Ok(adapter)
+    }
+
+    /// Uses `client` for every request instead of building one, so adapters
+    /// can share a connection pool and TLS/proxy settings. The client is
+    /// cloned, which shares its pool. `with_timeout` then only bounds each
+    /// request and leaves the client's connect timeout alone, and
+    /// `with_http_config` is rejected; configure the client instead.
+    pub fn with_http_client(api_key: impl Into<String>, base_url: Url, client: HttpClient) -> Self {
+        Self {
             api_key: api_key.into(),
             base_url,
             api_version: "2023-06-01".to_string(),
+            default_max_tokens: 1024,
+            system_separator: "\n\n".to_string(),
+            collect_timing: false,
+            trace_content: TraceContentOptions::default(),
+            timeout: None,
+            retry: None,
+            http_config: None,
+            proxy: None,
+            error_body_limit: DEFAULT_ERROR_BODY_LIMIT,
+            capabilities: CapabilityMatrix {
+                streaming: true,
+                tools: true,
+                structured_output: true,
+                multimodal_input: true,
+                citations: false,
+            },
+            attempts: Arc::new(AtomicUsize::new(0)),
             client,
-        })
+            shared_client: true,
+        }
+    }
+
+    /// Points an already configured adapter at `base_url`, e.g. one read from
+    /// a config file. Fails with `ForgeError::Validation` unless it is a valid
+    /// `http` or `https` URL.
+    pub fn with_base_url_str(mut self, base_url: impl AsRef<str>) -> Result<Self, ForgeError> {
+        self.base_url = parse_base_url(base_url.as_ref())?;
+        Ok(self)
+    }
+
+    /// Bounds `chat` end to end and the connect/response-header phase of
+    /// `chat_stream`; an established stream body is not cut off.
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self, ForgeError> {
+        if !self.shared_client {
+            self.client = build_http_client(
+                Some(timeout),
+                self.http_config.as_ref(),
+                self.proxy.as_ref(),
+            )?;
+        }
+        self.timeout = Some(timeout);
+        Ok(self)
+    }
+
+    pub fn with_default_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.default_max_tokens = max_tokens;
+        self
+    }
+
+    pub fn with_system_separator(mut self, separator: impl Into<String>) -> Self {
+        self.system_separator = separator.into();
+        self
+    }
+
+    /// Tunes connection pooling and keep-alive of the HTTP client; see
+    /// `HttpConfig` for the defaults it suggests.
+    pub fn with_http_config(mut self, config: HttpConfig) -> Result<Self, ForgeError> {
+        if self.shared_client {
+            return Err(ForgeError::Validation(
+                "with_http_config cannot tune a client passed to with_http_client".to_string(),
+            ));
+        }
+        self.client = build_http_client(self.timeout, Some(&config), self.proxy.as_ref())?;
+        self.http_config = Some(config);
+        Ok(self)
+    }
+
+    /// Sends every request through the proxy at `url`, e.g.
+    /// `http://proxy.corp:3128`; hosts listed in `NO_PROXY` bypass it. Fails
+    /// with `ForgeError::Validation` when `url` is not a valid proxy URL.
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, ForgeError> {
+        if self.shared_client {
+            return Err(ForgeError::Validation(
+                "with_proxy cannot reconfigure a client passed to with_http_client".to_string(),
+            ));
+        }
+        let proxy = Proxy::all(url)
+            .map_err(|e| ForgeError::Validation(format!("invalid proxy URL {url:?}: {e}")))?
+            .no_proxy(NoProxy::from_env());
+        self.client = build_http_client(self.timeout, self.http_config.as_ref(), Some(&proxy))?;
+        self.proxy = Some(proxy);
+        Ok(self)
+    }
+
+    /// Retries rate-limited and transport failures of `chat` and of the initial
+    /// `chat_stream` request with exponential backoff, honoring `Retry-After`
+    /// up to `config.max_delay`; longer provider delays are cut to it. An
+    /// established stream is never retried.
+    pub fn with_retry(mut self, config: RetryConfig) -> Self {
+        self.retry = Some(config);
+        self
+    }
+
+    /// Sets what the `tracing` feature records about prompts and completions.
+    pub fn with_trace_content(mut self, options: TraceContentOptions) -> Self {
+        self.trace_content = options;
+        self
+    }
+
+    /// Caps how many characters of a non-JSON error body (e.g. an HTML error
+    /// page) are kept as the error message; defaults to
+    /// `DEFAULT_ERROR_BODY_LIMIT`.
+    pub fn with_error_body_limit(mut self, max_chars: usize) -> Self {
+        self.error_body_limit = max_chars;
+        self
+    }
+
+    /// Replaces the `CapabilityMatrix` reported by `info()`, e.g. for a proxy
+    /// that does not support tools.
+    pub fn with_capabilities(mut self, capabilities: CapabilityMatrix) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// The JSON body `chat` would send for `request`, for debugging and
+    /// comparing providers without a server. It holds no credentials: auth
+    /// and `extra_headers` travel as headers, and the endpoint URL is not
+    /// part of it.
+    pub fn build_request_body(&self, request: &ChatRequest) -> Value {
+        build_messages_body(
+            request.clone(),
+            false,
+            self.default_max_tokens,
+            &self.system_separator,
+        )
+    }
+
+    /// Total HTTP attempts made by this adapter, including retries.
+    pub fn request_attempts(&self) -> usize {
+        self.attempts.load(Ordering::Relaxed)
     }
 
     pub fn from_env() -> Result<Self, ForgeError> {
         let api_key = env::var("ANTHROPIC_API_KEY").map_err(|_| ForgeError::Authentication)?;
-        match env::var("ANTHROPIC_BASE_URL") {
+        let mut adapter = match env::var("ANTHROPIC_BASE_URL") {
             Ok(raw) => {
                 let base_url = Url::parse(&raw).map_err(|e| {
                     ForgeError::Validation(format!("invalid ANTHROPIC_BASE_URL: {e}"))
                 })?;
-                Self::with_base_url(api_key, base_url)
+                Self::with_base_url(api_key, base_url)?
             }
-            Err(_) => Self::new(api_key),
+            Err(_) => Self::new(api_key)?,
+        };
+        if let Ok(raw) = env::var("FORGEAI_DEFAULT_MAX_TOKENS") {
+            adapter.default_max_tokens = raw
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|value| *value > 0)
+                .ok_or_else(|| {
+                    ForgeError::Validation(format!(
+                        "invalid FORGEAI_DEFAULT_MAX_TOKENS: expected a positive integer, got '{raw}'"
+                    ))
+                })?;
+        }
+        if let Some(proxy) = env_proxy() {
+            adapter = adapter.with_proxy(&proxy)?;
+        }
+        match env_timeout()? {
+            Some(timeout) => adapter.with_timeout(timeout),
+            None => Ok(adapter),
         }
     }
 
@@ -55,6 +238,43 @@ impl AnthropicAdapter {
             .join("v1/messages")
             .map_err(|e| ForgeError::Internal(format!("failed to construct endpoint url: {e}")))
     }
+
+    async fn send(
+        &self,
+        body: &Value,
+        headers: &HeaderMap,
+        stream: bool,
+    ) -> Result<Response, ForgeError> {
+        telemetry::request_body(body);
+        http::send_with_retry(self.retry, &self.attempts, || {
+            self.send_once(body, headers, stream)
+        })
+        .await
+    }
+
+    async fn send_once(
+        &self,
+        body: &Value,
+        headers: &HeaderMap,
+        stream: bool,
+    ) -> Result<Response, ForgeError> {
+        let request = self
+            .client
+            .post(self.messages_url()?)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", &self.api_version)
+            .headers(headers.clone())
+            .json(body);
+        http::send_request(
+            request,
+            self.timeout,
+            stream,
+            |status, retry_after, body| {
+                parse_http_error(status, retry_after, body, self.error_body_limit)
+            },
+        )
+        .await
+    }
 }
 
 #[async_trait]
@@ -62,186 +282,282 @@ impl ChatAdapter for AnthropicAdapter {
     fn info(&self) -> AdapterInfo {
         AdapterInfo {
             name: "anthropic".to_string(),
-            base_url: Url::parse("https://api.anthropic.com").ok(),
-            capabilities: CapabilityMatrix {
-                streaming: true,
-                tools: true,
-                structured_output: true,
-                multimodal_input: true,
-                citations: false,
-            },
+            base_url: Some(self.base_url.clone()),
+            capabilities: self.capabilities.clone(),
         }
     }
 
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
-        let response = self
-            .client
-            .post(self.messages_url()?)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", &self.api_version)
-            .json(&build_messages_body(request, false))
-            .send()
-            .await
-            .map_err(|e| ForgeError::Transport(format!("request failed: {e}")))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "failed to read error body".to_string());
-            return Err(parse_http_error(status, text));
-        }
+        let model = request.model.clone();
+        telemetry::instrument("chat", "anthropic", &model, async move {
+            telemetry::prompt(&self.trace_content, &request);
+            reject_multiple_choices(&request)?;
+            let output_tool =
+                response_format_tool(request.response_format.clone()).map(|(name, _)| name);
+            let headers = request_headers(&request)?;
+            let body = build_messages_body(
+                request,
+                false,
+                self.default_max_tokens,
+                &self.system_separator,
+            );
+            let started = Instant::now();
+            let response = self.send(&body, &headers, false).await?;
+            let first_byte = started.elapsed();
 
-        let payload = response
-            .json::<Value>()
-            .await
-            .map_err(|e| ForgeError::Provider(format!("invalid json response: {e}")))?;
-        parse_chat_response(payload)
+            let payload = response
+                .json::<Value>()
+                .await
+                .map_err(|e| match self.timeout {
+                    Some(timeout) if e.is_timeout() => timed_out(timeout),
+                    _ => ForgeError::Provider(format!("invalid json response: {e}").into()),
+                })?;
+            let mut response = parse_chat_response(payload)?;
+            if let Some(name) = output_tool {
+                unwrap_output_tool(&mut response, &name);
+            }
+            telemetry::completion(&self.trace_content, &response.output_text);
+            if let Some(usage) = &response.usage {
+                telemetry::usage(usage);
+            }
+            if self.collect_timing {
+                response.timing = Some(ResponseTiming {
+                    total: started.elapsed(),
+                    first_byte: Some(first_byte),
+                });
+            }
+            Ok(response)
+        })
+        .await
     }
 
     async fn chat_stream(
         &self,
         request: ChatRequest,
     ) -> Result<StreamResult<StreamEvent>, ForgeError> {
-        let response = self
-            .client
-            .post(self.messages_url()?)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", &self.api_version)
-            .json(&build_messages_body(request, true))
-            .send()
-            .await
-            .map_err(|e| ForgeError::Transport(format!("stream request failed: {e}")))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "failed to read error body".to_string());
-            return Err(parse_http_error(status, text));
-        }
+        let model = request.model.clone();
+        telemetry::instrument("chat_stream", "anthropic", &model, async move {
+            telemetry::prompt(&self.trace_content, &request);
+            reject_multiple_choices(&request)?;
+            let headers = request_headers(&request)?;
+            let body = build_messages_body(
+                request,
+                true,
+                self.default_max_tokens,
+                &self.system_separator,
+            );
+            let response = self.send(&body, &headers, true).await?;
 
-        let mut bytes = response.bytes_stream();
-        let stream = try_stream! {
-            let mut buffer = String::new();
-            let mut saw_done = false;
-            let mut event_name: Option<String> = None;
-            let mut data_lines: Vec<String> = Vec::new();
-
-            while let Some(chunk) = bytes.next().await {
-                let chunk = chunk.map_err(|e| ForgeError::Transport(format!("stream chunk error: {e}")))?;
-                let text = std::str::from_utf8(&chunk)
-                    .map_err(|e| ForgeError::Transport(format!("invalid utf8 stream chunk: {e}")))?;
-                buffer.push_str(text);
-
-                while let Some(line_end) = buffer.find('\n') {
-                    let mut line = buffer[..line_end].to_string();
-                    buffer.drain(..=line_end);
-                    if line.ends_with('\r') {
-                        line.pop();
-                    }
-                    if line.is_empty() {
-                        if !data_lines.is_empty() {
-                            let payload = data_lines.join("\n");
-                            let events = parse_stream_payload(&payload, event_name.as_deref())?;
-                            for event in events {
-                                if matches!(event, StreamEvent::Done) {
-                                    saw_done = true;
+            let mut bytes = response.bytes_stream();
+            let stream = try_stream! {
+                let mut buffer = String::new();
+                let mut saw_done = false;
+                let mut event_name: Option<String> = None;
+                let mut data_lines: Vec<String> = Vec::new();
+                let mut tool_blocks: HashMap<u64, String> = HashMap::new();
+
+                while let Some(chunk) = bytes.next().await {
+                    let chunk = chunk.map_err(|e| ForgeError::Transport(format!("stream chunk error: {e}")))?;
+                    let text = std::str::from_utf8(&chunk)
+                        .map_err(|e| ForgeError::Transport(format!("invalid utf8 stream chunk: {e}")))?;
+                    buffer.push_str(text);
+
+                    while let Some(line_end) = buffer.find('\n') {
+                        let mut line = buffer[..line_end].to_string();
+                        buffer.drain(..=line_end);
+                        if line.ends_with('\r') {
+                            line.pop();
+                        }
+                        if line.is_empty() {
+                            if !data_lines.is_empty() {
+                                let payload = data_lines.join("\n");
+                                let events = parse_stream_payload(&payload, event_name.as_deref(), &mut tool_blocks)?;
+                                for event in events {
+                                    if matches!(event, StreamEvent::Done) {
+                                        saw_done = true;
+                                    }
+                                    yield event;
                                 }
-                                yield event;
+                                data_lines.clear();
+                                event_name = None;
                             }
-                            data_lines.clear();
-                            event_name = None;
+                            continue;
+                        }
+                        if let Some(name) = line.strip_prefix("event:") {
+                            event_name = Some(name.trim().to_string());
+                            continue;
+                        }
+                        if let Some(data) = line.strip_prefix("data:") {
+                            data_lines.push(data.trim().to_string());
                         }
-                        continue;
-                    }
-                    if let Some(name) = line.strip_prefix("event:") {
-                        event_name = Some(name.trim().to_string());
-                        continue;
-                    }
-                    if let Some(data) = line.strip_prefix("data:") {
-                        data_lines.push(data.trim().to_string());
                     }
                 }
-            }
 
-            if !data_lines.is_empty() {
-                let payload = data_lines.join("\n");
-                let events = parse_stream_payload(&payload, event_name.as_deref())?;
-                for event in events {
-                    if matches!(event, StreamEvent::Done) {
-                        saw_done = true;
+                if !data_lines.is_empty() {
+                    let payload = data_lines.join("\n");
+                    let events = parse_stream_payload(&payload, event_name.as_deref(), &mut tool_blocks)?;
+                    for event in events {
+                        if matches!(event, StreamEvent::Done) {
+                            saw_done = true;
+                        }
+                        yield event;
                     }
-                    yield event;
                 }
-            }
 
-            if !saw_done {
-                yield StreamEvent::Done;
-            }
-        };
+                if !saw_done {
+                    yield StreamEvent::Done;
+                }
+            };
+
+            Ok(Box::pin(stream) as StreamResult<StreamEvent>)
+        })
+        .await
+    }
+}
 
-        Ok(Box::pin(stream))
+/// Anthropic has no JSON mode, so structured output is requested by forcing a
+/// call to a tool whose input schema is the requested format.
+fn response_format_tool(format: Option<ResponseFormat>) -> Option<(String, Value)> {
+    match format? {
+        ResponseFormat::Text => None,
+        ResponseFormat::JsonObject => Some(("json_output".to_string(), json!({"type": "object"}))),
+        ResponseFormat::JsonSchema { name, schema, .. } => Some((name, schema)),
     }
 }
 
-fn build_messages_body(request: ChatRequest, stream: bool) -> Value {
+/// Moves the forced output tool call into `output_text` as JSON.
+fn unwrap_output_tool(response: &mut ChatResponse, name: &str) {
+    let Some(index) = response
+        .tool_calls
+        .iter()
+        .position(|call| call.name == name)
+    else {
+        return;
+    };
+    let call = response.tool_calls.remove(index);
+    response.output_text = call.arguments.to_string();
+    if response.tool_calls.is_empty() && response.finish_reason == Some(FinishReason::ToolCalls) {
+        response.finish_reason = Some(FinishReason::Stop);
+    }
+}
+
+fn build_messages_body(
+    request: ChatRequest,
+    stream: bool,
+    default_max_tokens: u32,
+    system_separator: &str,
+) -> Value {
     let mut body = Map::new();
     body.insert("model".to_string(), Value::String(request.model));
     body.insert(
         "max_tokens".to_string(),
-        Value::Number((request.max_tokens.unwrap_or(1024)).into()),
+        Value::Number((request.max_tokens.unwrap_or(default_max_tokens)).into()),
     );
 
     if let Some(temperature) = request.temperature {
         body.insert("temperature".to_string(), json!(temperature));
     }
+    if let Some(top_p) = request.top_p {
+        body.insert("top_p".to_string(), json!(top_p));
+    }
+    // Anthropic has no frequency or presence penalties or seed; they are dropped.
+    // Anthropic documents no count limit for `stop_sequences`, so unlike the
+    // OpenAI and Gemini adapters there is no per-provider check here.
+    if !request.stop.is_empty() {
+        body.insert("stop_sequences".to_string(), json!(request.stop));
+    }
 
-    let mut system_chunks = Vec::new();
-    let mut messages = Vec::new();
+    let mut system_chunks: Vec<(String, bool)> = request
+        .system
+        .into_iter()
+        .map(|text| (text, false))
+        .collect();
+    let mut messages: Vec<Value> = Vec::new();
+    let mut previous_was_tool = false;
     for message in request.messages {
         if matches!(message.role, Role::System) {
-            system_chunks.push(message.content);
+            system_chunks.push((message.content.text(), message.cache));
             continue;
         }
+        let is_tool = matches!(message.role, Role::Tool);
+        // Anthropic expects every tool_result of a turn in a single user message.
+        if is_tool && previous_was_tool {
+            if let Some(Value::Array(blocks)) =
+                messages.last_mut().and_then(|last| last.get_mut("content"))
+            {
+                blocks.extend(message_blocks(message));
+                continue;
+            }
+        }
+        previous_was_tool = is_tool;
         let role = match message.role {
             Role::Assistant => "assistant",
             _ => "user",
         };
         messages.push(json!({
             "role": role,
-            "content": [{ "type": "text", "text": message.content }]
+            "content": message_blocks(message)
         }));
     }
     body.insert("messages".to_string(), Value::Array(messages));
 
-    if !system_chunks.is_empty() {
+    // A cached system prompt has to be sent as blocks to carry `cache_control`.
+    if system_chunks.iter().any(|(_, cache)| *cache) {
+        let blocks = system_chunks
+            .into_iter()
+            .map(|(text, cache)| {
+                let mut block = json!({ "type": "text", "text": text });
+                if cache {
+                    block["cache_control"] = json!({ "type": "ephemeral" });
+                }
+                block
+            })
+            .collect();
+        body.insert("system".to_string(), Value::Array(blocks));
+    } else if !system_chunks.is_empty() {
+        let chunks: Vec<String> = system_chunks.into_iter().map(|(text, _)| text).collect();
         body.insert(
             "system".to_string(),
-            Value::String(system_chunks.join("\n\n")),
+            Value::String(chunks.join(system_separator)),
         );
     }
 
-    if !request.tools.is_empty() {
+    let mut tools: Vec<Value> = request
+        .tools
+        .into_iter()
+        .map(|tool| match tool.raw {
+            Some(raw) => raw,
+            None => json!({
+                "name": tool.name,
+                "description": tool.description,
+                "input_schema": tool.input_schema
+            }),
+        })
+        .collect();
+    if let Some(choice) = request.tool_choice {
+        let choice = match choice {
+            ToolChoice::Auto => json!({"type": "auto"}),
+            ToolChoice::None => json!({"type": "none"}),
+            ToolChoice::Required => json!({"type": "any"}),
+            ToolChoice::Function(name) => json!({"type": "tool", "name": name}),
+        };
+        body.insert("tool_choice".to_string(), choice);
+    }
+    // A structured output tool has to be called, so it overrides `tool_choice`.
+    if let Some((name, schema)) = response_format_tool(request.response_format) {
+        tools.push(json!({
+            "name": name,
+            "description": "Respond by calling this tool with the final answer.",
+            "input_schema": schema
+        }));
         body.insert(
-            "tools".to_string(),
-            Value::Array(
-                request
-                    .tools
-                    .into_iter()
-                    .map(|tool| {
-                        json!({
-                            "name": tool.name,
-                            "description": tool.description,
-                            "input_schema": tool.input_schema
-                        })
-                    })
-                    .collect(),
-            ),
+            "tool_choice".to_string(),
+            json!({"type": "tool", "name": name}),
         );
     }
+    if !tools.is_empty() {
+        body.insert("tools".to_string(), Value::Array(tools));
+    }
 
     if stream {
         body.insert("stream".to_string(), Value::Bool(true));
@@ -250,25 +566,131 @@ fn build_messages_body(request: ChatRequest, stream: bool) -> Value {
     Value::Object(body)
 }
 
-fn parse_http_error(status: StatusCode, body: String) -> ForgeError {
-    let message = extract_provider_error(body);
+fn message_blocks(message: Message) -> Vec<Value> {
+    let cache = message.cache;
+    let mut blocks = content_blocks(message);
+    if cache {
+        if let Some(last) = blocks.last_mut() {
+            last["cache_control"] = json!({ "type": "ephemeral" });
+        }
+    }
+    blocks
+}
+
+fn content_blocks(message: Message) -> Vec<Value> {
+    if let Some(tool_use_id) = message.tool_call_id {
+        return vec![json!({
+            "type": "tool_result",
+            "tool_use_id": tool_use_id,
+            "content": message.content.text()
+        })];
+    }
+
+    let mut blocks = Vec::new();
+    match message.content {
+        // Anthropic rejects empty text blocks, so a pure tool-call turn only carries tool_use.
+        MessageContent::Text(text) => {
+            if !text.is_empty() || message.tool_calls.is_empty() {
+                blocks.push(json!({ "type": "text", "text": text }));
+            }
+        }
+        MessageContent::Parts(parts) => blocks.extend(parts.into_iter().map(part_to_block)),
+    }
+    for call in message.tool_calls {
+        blocks.push(json!({
+            "type": "tool_use",
+            "id": call.id,
+            "name": call.name,
+            "input": call.arguments
+        }));
+    }
+    blocks
+}
+
+fn part_to_block(part: ContentPart) -> Value {
+    let url = part.image_url().map(str::to_string);
+    match (part, url) {
+        (ContentPart::Text { text }, _) => json!({ "type": "text", "text": text }),
+        (ContentPart::Image { .. }, Some(url)) => {
+            json!({ "type": "image", "source": { "type": "url", "url": url } })
+        }
+        (
+            ContentPart::Image {
+                url_or_base64,
+                mime_type,
+            },
+            None,
+        ) => json!({
+            "type": "image",
+            "source": {
+                "type": "base64",
+                "media_type": mime_type,
+                "data": url_or_base64
+            }
+        }),
+    }
+}
+
+fn parse_http_error(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    body: String,
+    body_limit: usize,
+) -> ForgeError {
+    let error = extract_provider_error(status, body, body_limit);
     match status {
         StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ForgeError::Authentication,
-        StatusCode::TOO_MANY_REQUESTS => ForgeError::RateLimited,
-        _ => ForgeError::Provider(message),
+        StatusCode::TOO_MANY_REQUESTS => ForgeError::RateLimited { retry_after },
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            ForgeError::Transport(format!("{status}: {}", error.message))
+        }
+        _ => ForgeError::Provider(error),
     }
 }
 
-fn extract_provider_error(body: String) -> String {
-    serde_json::from_str::<Value>(&body)
-        .ok()
-        .and_then(|v| {
-            v.get("error")
-                .and_then(|e| e.get("message"))
-                .and_then(Value::as_str)
-                .map(ToString::to_string)
-        })
-        .unwrap_or(body)
+/// Anthropic returns a single completion per request.
+fn reject_multiple_choices(request: &ChatRequest) -> Result<(), ForgeError> {
+    if request.n.is_some_and(|n| n > 1) {
+        return Err(ForgeError::Validation(
+            "anthropic does not support n > 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// `extra_headers`, plus the prompt caching beta flag when any message is
+/// marked `cache`.
+fn request_headers(request: &ChatRequest) -> Result<HeaderMap, ForgeError> {
+    let mut headers = extra_header_map(&request.extra_headers)?;
+    if request.messages.iter().any(|message| message.cache) {
+        let beta = match headers.get(ANTHROPIC_BETA).map(HeaderValue::to_str) {
+            Some(Ok(existing)) => format!("{existing},{PROMPT_CACHING_BETA}"),
+            _ => PROMPT_CACHING_BETA.to_string(),
+        };
+        let beta = HeaderValue::from_str(&beta).map_err(|_| {
+            ForgeError::Validation(format!("invalid value for header {ANTHROPIC_BETA}"))
+        })?;
+        headers.insert(ANTHROPIC_BETA, beta);
+    }
+    Ok(headers)
+}
+
+/// Reads `{"error": {"type", "message"}}`, falling back to the raw body.
+fn extract_provider_error(status: StatusCode, body: String, body_limit: usize) -> ProviderError {
+    let parsed = serde_json::from_str::<Value>(&body).ok();
+    let error = parsed.as_ref().and_then(|v| v.get("error"));
+    let field = |name: &str| {
+        error
+            .and_then(|e| e.get(name))
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    };
+    ProviderError {
+        message: field("message").unwrap_or_else(|| truncate_chars(body, body_limit)),
+        code: None,
+        kind: field("type"),
+        http_status: status.as_u16(),
+    }
 }
 
 fn parse_chat_response(payload: Value) -> Result<ChatResponse, ForgeError> {
@@ -289,18 +711,44 @@ fn parse_chat_response(payload: Value) -> Result<ChatResponse, ForgeError> {
         .cloned()
         .unwrap_or_default();
     let output_text = extract_text_blocks(&content);
+    let reasoning: String = content
+        .iter()
+        .filter(|block| block.get("type").and_then(Value::as_str) == Some("thinking"))
+        .filter_map(|block| block.get("thinking").and_then(Value::as_str))
+        .collect();
     let tool_calls = extract_tool_calls_from_blocks(&content);
     let usage = extract_usage(payload.get("usage"));
+    let finish_reason = payload
+        .get("stop_reason")
+        .and_then(Value::as_str)
+        .map(parse_finish_reason);
 
     Ok(ChatResponse {
         id,
         model,
         output_text,
+        reasoning: (!reasoning.is_empty()).then_some(reasoning),
         tool_calls,
         usage,
+        finish_reason,
+        timing: None,
+        citations: Vec::new(),
+        system_fingerprint: None,
+        audio: None,
+        choices: Vec::new(),
     })
 }
 
+fn parse_finish_reason(raw: &str) -> FinishReason {
+    match raw {
+        "end_turn" | "stop_sequence" => FinishReason::Stop,
+        "max_tokens" => FinishReason::Length,
+        "tool_use" => FinishReason::ToolCalls,
+        "refusal" => FinishReason::ContentFilter,
+        other => FinishReason::Other(other.to_string()),
+    }
+}
+
 fn extract_text_blocks(content: &[Value]) -> String {
     content
         .iter()
@@ -335,24 +783,32 @@ fn extract_usage(raw: Option<&Value>) -> Option<Usage> {
     let input_tokens = usage
         .get("input_tokens")
         .and_then(Value::as_u64)
-        .unwrap_or(0) as u32;
+        .unwrap_or(0);
     let output_tokens = usage
         .get("output_tokens")
         .and_then(Value::as_u64)
-        .unwrap_or(0) as u32;
+        .unwrap_or(0);
+    let count = |key: &str| usage.get(key).and_then(Value::as_u64);
     Some(Usage {
         input_tokens,
         output_tokens,
         total_tokens: input_tokens.saturating_add(output_tokens),
+        cache_creation_input_tokens: count("cache_creation_input_tokens"),
+        cached_input_tokens: count("cache_read_input_tokens"),
+        reasoning_tokens: None,
     })
 }
 
+/// Parses one SSE payload. `tool_blocks` maps content-block indexes to the
+/// ids of tool calls started in them, since `input_json_delta` frames only
+/// carry the index.
 fn parse_stream_payload(
     payload: &str,
     event: Option<&str>,
+    tool_blocks: &mut HashMap<u64, String>,
 ) -> Result<Vec<StreamEvent>, ForgeError> {
     let value = serde_json::from_str::<Value>(payload)
-        .map_err(|e| ForgeError::Provider(format!("invalid stream payload: {e}")))?;
+        .map_err(|e| ForgeError::Provider(format!("invalid stream payload: {e}").into()))?;
     let event_type = event
         .map(ToString::to_string)
         .or_else(|| {
@@ -365,6 +821,22 @@ fn parse_stream_payload(
 
     let mut events = Vec::new();
 
+    if event_type == "message_start" {
+        if let Some(message) = value.get("message") {
+            let field = |name: &str| {
+                message
+                    .get(name)
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            events.push(StreamEvent::ResponseStart {
+                id: field("id"),
+                model: field("model"),
+            });
+        }
+    }
+
     if let Some(usage) = value
         .get("usage")
         .and_then(|v| extract_usage(Some(v)))
@@ -379,8 +851,8 @@ fn parse_stream_payload(
     }
 
     if event_type == "content_block_delta" {
-        if let Some(delta_text) = value
-            .get("delta")
+        let delta = value.get("delta");
+        if let Some(delta_text) = delta
             .and_then(|d| d.get("text"))
             .and_then(Value::as_str)
             .filter(|s| !s.is_empty())
@@ -389,6 +861,26 @@ fn parse_stream_payload(
                 delta: delta_text.to_string(),
             });
         }
+        if let Some(thinking) = delta
+            .filter(|d| d.get("type").and_then(Value::as_str) == Some("thinking_delta"))
+            .and_then(|d| d.get("thinking"))
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+        {
+            events.push(StreamEvent::ReasoningDelta {
+                delta: thinking.to_string(),
+            });
+        }
+        if let Some(call_id) = delta
+            .filter(|d| d.get("type").and_then(Value::as_str) == Some("input_json_delta"))
+            .and_then(|_| value.get("index").and_then(Value::as_u64))
+            .and_then(|index| tool_blocks.get(&index))
+        {
+            events.push(StreamEvent::ToolCallDelta {
+                call_id: call_id.clone(),
+                delta: delta.cloned().unwrap_or(Value::Null),
+            });
+        }
     }
 
     if event_type == "content_block_start" {
@@ -399,6 +891,9 @@ fn parse_stream_payload(
                     .and_then(Value::as_str)
                     .unwrap_or_default()
                     .to_string();
+                if let Some(index) = value.get("index").and_then(Value::as_u64) {
+                    tool_blocks.insert(index, call_id.clone());
+                }
                 events.push(StreamEvent::ToolCallDelta {
                     call_id,
                     delta: block.clone(),
@@ -407,6 +902,18 @@ fn parse_stream_payload(
         }
     }
 
+    if event_type == "message_delta" {
+        if let Some(reason) = value
+            .get("delta")
+            .and_then(|d| d.get("stop_reason"))
+            .and_then(Value::as_str)
+        {
+            events.push(StreamEvent::FinishReason {
+                reason: parse_finish_reason(reason),
+            });
+        }
+    }
+
     if event_type == "message_stop" {
         events.push(StreamEvent::Done);
     }
@@ -417,7 +924,7 @@ fn parse_stream_payload(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use forgeai_core::{ChatRequest, Message, Role};
+    use forgeai_core::ToolDefinition;
     use futures_util::StreamExt;
     use wiremock::matchers::{body_partial_json, header, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -425,17 +932,102 @@ mod tests {
     fn sample_request() -> ChatRequest {
         ChatRequest {
             model: "claude-3-5-sonnet-latest".to_string(),
-            messages: vec![Message {
-                role: Role::User,
-                content: "Say hello".to_string(),
-            }],
+            messages: vec![Message::new(Role::User, "Say hello")],
+            system: None,
             temperature: Some(0.2),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             max_tokens: Some(128),
+            n: None,
+            seed: None,
+            stop: vec![],
             tools: vec![],
+            tool_choice: None,
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::new(),
             metadata: json!({}),
         }
     }
 
+    #[test]
+    fn info_reports_configured_base_url() {
+        let adapter = AnthropicAdapter::new("test-key")
+            .unwrap()
+            .with_base_url_str("http://localhost:8080/")
+            .unwrap();
+        assert_eq!(
+            adapter.info().base_url.unwrap().as_str(),
+            "http://localhost:8080/"
+        );
+    }
+
+    #[tokio::test]
+    async fn cached_messages_send_cache_control_and_beta_header() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header("anthropic-beta", "prompt-caching-2024-07-31"))
+            .and(body_partial_json(json!({
+                "system": [
+                    {"type": "text", "text": "Long shared instructions", "cache_control": {"type": "ephemeral"}}
+                ],
+                "messages": [
+                    {"role": "user", "content": [
+                        {"type": "text", "text": "Say hello", "cache_control": {"type": "ephemeral"}}
+                    ]},
+                    {"role": "assistant", "content": [{"type": "text", "text": "Hello"}]}
+                ]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "msg_123",
+                "model": "claude-3-5-sonnet-latest",
+                "content": [{ "type": "text", "text": "Hi again" }],
+                "stop_reason": "end_turn",
+                "usage": {
+                    "input_tokens": 4,
+                    "output_tokens": 3,
+                    "cache_creation_input_tokens": 0,
+                    "cache_read_input_tokens": 1800
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let adapter =
+            AnthropicAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+                .unwrap();
+        let mut request = sample_request();
+        request.messages = vec![
+            Message::new(Role::System, "Long shared instructions").cached(),
+            Message::new(Role::User, "Say hello").cached(),
+            Message::new(Role::Assistant, "Hello"),
+        ];
+        let usage = adapter.chat(request).await.unwrap().usage.unwrap();
+
+        assert_eq!(usage.cache_creation_input_tokens, Some(0));
+        assert_eq!(usage.cached_input_tokens, Some(1800));
+        let sent: Value = server.received_requests().await.unwrap()[0]
+            .body_json()
+            .unwrap();
+        assert!(sent["messages"][1]["content"][0]
+            .get("cache_control")
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn multiple_choices_are_rejected() {
+        let adapter =
+            AnthropicAdapter::with_base_url("test-key", Url::parse("http://127.0.0.1:9").unwrap())
+                .unwrap();
+        let mut request = sample_request();
+        request.n = Some(2);
+        let err = adapter.chat(request).await.unwrap_err();
+        assert!(matches!(err, ForgeError::Validation(ref m) if m.contains("n > 1")));
+    }
+
     #[tokio::test]
     async fn chat_contract_parses_response_and_usage() {
         let server = MockServer::start().await;
@@ -450,6 +1042,7 @@ mod tests {
                 "id": "msg_123",
                 "model": "claude-3-5-sonnet-latest",
                 "content": [{ "type": "text", "text": "Hello from Anthropic" }],
+                "stop_reason": "tool_use",
                 "usage": {"input_tokens": 12, "output_tokens": 5}
             })))
             .mount(&server)
@@ -464,6 +1057,7 @@ mod tests {
         assert_eq!(response.model, "claude-3-5-sonnet-latest");
         assert_eq!(response.output_text, "Hello from Anthropic");
         assert_eq!(response.usage.unwrap().total_tokens, 17);
+        assert_eq!(response.finish_reason, Some(FinishReason::ToolCalls));
     }
 
     #[tokio::test]
@@ -477,7 +1071,7 @@ mod tests {
             "event: content_block_delta\n",
             "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\" world\"}}\n\n",
             "event: message_delta\n",
-            "data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":2}}\n\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":2}}\n\n",
             "event: message_stop\n",
             "data: {\"type\":\"message_stop\"}\n\n"
         );
@@ -517,6 +1111,434 @@ mod tests {
         assert!(events
             .iter()
             .any(|e| matches!(e, StreamEvent::Usage { usage } if usage.output_tokens == 2)));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::FinishReason {
+                reason: FinishReason::Stop
+            }
+        )));
         assert!(events.iter().any(|e| matches!(e, StreamEvent::Done)));
     }
+
+    #[test]
+    fn thinking_deltas_stream_as_reasoning() {
+        let frames = [
+            (
+                "content_block_start",
+                r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            ),
+            (
+                "content_block_delta",
+                r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"2 + 2 "}}"#,
+            ),
+            (
+                "content_block_delta",
+                r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"is 4."}}"#,
+            ),
+            (
+                "content_block_delta",
+                r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"EqQB"}}"#,
+            ),
+            (
+                "content_block_stop",
+                r#"{"type":"content_block_stop","index":0}"#,
+            ),
+            (
+                "content_block_start",
+                r#"{"type":"content_block_start","index":1,"content_block":{"type":"text","text":""}}"#,
+            ),
+            (
+                "content_block_delta",
+                r#"{"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"4"}}"#,
+            ),
+        ];
+        let mut tool_blocks = HashMap::new();
+        let events: Vec<StreamEvent> = frames
+            .iter()
+            .flat_map(|(event, payload)| {
+                parse_stream_payload(payload, Some(event), &mut tool_blocks).unwrap()
+            })
+            .collect();
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], StreamEvent::ReasoningDelta { delta } if delta == "2 + 2 "));
+        assert!(matches!(&events[1], StreamEvent::ReasoningDelta { delta } if delta == "is 4."));
+        assert!(matches!(&events[2], StreamEvent::TextDelta { delta } if delta == "4"));
+    }
+
+    #[test]
+    fn input_json_deltas_stream_under_their_block_id() {
+        let frames = [
+            (
+                "content_block_start",
+                r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"time.now","input":{}}}"#,
+            ),
+            (
+                "content_block_delta",
+                r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"timezone\":"}}"#,
+            ),
+            (
+                "content_block_delta",
+                r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"UTC\"}"}}"#,
+            ),
+        ];
+        let mut tool_blocks = HashMap::new();
+        let events: Vec<StreamEvent> = frames
+            .iter()
+            .flat_map(|(event, payload)| {
+                parse_stream_payload(payload, Some(event), &mut tool_blocks).unwrap()
+            })
+            .collect();
+
+        assert_eq!(events.len(), 3);
+        let fragments: Vec<_> = events[1..]
+            .iter()
+            .map(|event| match event {
+                StreamEvent::ToolCallDelta { call_id, delta } => {
+                    (call_id.as_str(), delta["partial_json"].as_str().unwrap())
+                }
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            fragments,
+            vec![("toolu_1", "{\"timezone\":"), ("toolu_1", "\"UTC\"}")]
+        );
+    }
+
+    #[test]
+    fn empty_content_keeps_stop_reason() {
+        let response = parse_chat_response(json!({
+            "id": "msg_empty",
+            "model": "claude-3-5-sonnet-latest",
+            "content": [],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 8, "output_tokens": 0}
+        }))
+        .unwrap();
+
+        assert_eq!(response.output_text, "");
+        assert!(response.tool_calls.is_empty());
+        assert_eq!(response.finish_reason, Some(FinishReason::Stop));
+        assert_eq!(response.usage.unwrap().output_tokens, 0);
+    }
+
+    #[test]
+    fn pure_tool_call_turn_omits_empty_text_block() {
+        let mut request = sample_request();
+        let mut assistant = Message::new(Role::Assistant, "");
+        assistant.tool_calls = vec![ToolCall {
+            id: "toolu_1".to_string(),
+            name: "time.now".to_string(),
+            arguments: json!({"timezone": "UTC"}),
+        }];
+        let mut tool = Message::new(Role::Tool, "12:00");
+        tool.tool_call_id = Some("toolu_1".to_string());
+        request.messages.extend([assistant, tool]);
+
+        let body = build_messages_body(request, false, 1024, "\n\n");
+        let messages = body["messages"].as_array().unwrap();
+
+        assert_eq!(
+            messages[1]["content"],
+            json!([{
+                "type": "tool_use",
+                "id": "toolu_1",
+                "name": "time.now",
+                "input": {"timezone": "UTC"}
+            }])
+        );
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+    }
+
+    #[test]
+    fn consecutive_tool_results_share_one_user_message() {
+        let mut request = sample_request();
+        let mut assistant = Message::new(Role::Assistant, "");
+        assistant.tool_calls = vec![
+            ToolCall {
+                id: "toolu_1".to_string(),
+                name: "time.now".to_string(),
+                arguments: json!({}),
+            },
+            ToolCall {
+                id: "toolu_2".to_string(),
+                name: "weather".to_string(),
+                arguments: json!({"city": "Paris"}),
+            },
+        ];
+        let mut first = Message::new(Role::Tool, "12:00");
+        first.tool_call_id = Some("toolu_1".to_string());
+        let mut second = Message::new(Role::Tool, "sunny");
+        second.tool_call_id = Some("toolu_2".to_string());
+        request.messages.extend([assistant, first, second]);
+
+        let body = build_messages_body(request, false, 1024, "\n\n");
+        let messages = body["messages"].as_array().unwrap();
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(
+            messages[2]["content"],
+            json!([
+                {"type": "tool_result", "tool_use_id": "toolu_1", "content": "12:00"},
+                {"type": "tool_result", "tool_use_id": "toolu_2", "content": "sunny"}
+            ])
+        );
+    }
+
+    #[test]
+    fn provider_error_keeps_error_type() {
+        let err = parse_http_error(
+            StatusCode::BAD_REQUEST,
+            None,
+            json!({
+                "type": "error",
+                "error": {"type": "invalid_request_error", "message": "max_tokens too large"}
+            })
+            .to_string(),
+            DEFAULT_ERROR_BODY_LIMIT,
+        );
+
+        let ForgeError::Provider(error) = err else {
+            panic!("expected provider error, got {err:?}");
+        };
+        assert_eq!(error.message, "max_tokens too large");
+        assert_eq!(error.kind.as_deref(), Some("invalid_request_error"));
+        assert_eq!(error.code, None);
+        assert_eq!(error.http_status, 400);
+    }
+
+    #[test]
+    fn top_p_serializes_and_penalties_are_skipped() {
+        let mut request = sample_request();
+        request.top_p = Some(0.5);
+        request.frequency_penalty = Some(0.25);
+        request.presence_penalty = Some(0.25);
+
+        let body = build_messages_body(request, false, 1024, "\n\n");
+
+        assert_eq!(body["top_p"], json!(0.5));
+        assert!(body.get("frequency_penalty").is_none());
+        assert!(body.get("presence_penalty").is_none());
+    }
+
+    #[test]
+    fn system_field_precedes_system_messages() {
+        let mut request = sample_request();
+        request.system = Some("Be brief.".to_string());
+        request
+            .messages
+            .insert(0, Message::new(Role::System, "Answer in French."));
+
+        let body = build_messages_body(request, false, 1024, "\n\n");
+
+        assert_eq!(body["system"], "Be brief.\n\nAnswer in French.");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn stop_sequences_serialize_only_when_present() {
+        let mut request = sample_request();
+        let body = build_messages_body(request.clone(), false, 1024, "\n\n");
+        assert!(body.get("stop_sequences").is_none());
+
+        request.stop = vec!["END".to_string(), "###".to_string()];
+        let body = build_messages_body(request, false, 1024, "\n\n");
+        assert_eq!(body["stop_sequences"], json!(["END", "###"]));
+    }
+
+    #[test]
+    fn server_tool_declaration_passes_through_verbatim() {
+        let declaration = json!({
+            "type": "code_execution_20250522",
+            "name": "code_execution"
+        });
+        let mut request = sample_request();
+        request.tools = vec![
+            ToolDefinition::server_tool(declaration.clone()),
+            ToolDefinition {
+                name: "weather".to_string(),
+                description: None,
+                input_schema: json!({"type": "object"}),
+                raw: None,
+            },
+        ];
+
+        let body = build_messages_body(request, false, 1024, "\n\n");
+
+        assert_eq!(body["tools"][0], declaration);
+        assert_eq!(body["tools"][1]["name"], "weather");
+        assert_eq!(body["tools"][1]["input_schema"], json!({"type": "object"}));
+    }
+
+    #[test]
+    fn tool_choice_function_is_sent() {
+        let mut request = sample_request();
+        assert!(build_messages_body(request.clone(), false, 1024, "\n\n")
+            .get("tool_choice")
+            .is_none());
+        request.tools = vec![ToolDefinition {
+            name: "weather".to_string(),
+            description: None,
+            input_schema: json!({"type": "object"}),
+            raw: None,
+        }];
+        request.tool_choice = Some(ToolChoice::Function("weather".to_string()));
+        let body = build_messages_body(request, false, 1024, "\n\n");
+        assert_eq!(
+            body["tool_choice"],
+            json!({"type": "tool", "name": "weather"})
+        );
+    }
+
+    #[test]
+    fn json_schema_response_format_forces_output_tool() {
+        let mut request = sample_request();
+        request.response_format = Some(ResponseFormat::JsonSchema {
+            name: "place".to_string(),
+            schema: json!({"type": "object"}),
+            strict: true,
+        });
+
+        let body = build_messages_body(request, false, 1024, "\n\n");
+        assert_eq!(
+            body["tool_choice"],
+            json!({"type": "tool", "name": "place"})
+        );
+        assert_eq!(body["tools"][0]["name"], "place");
+
+        let mut response = ChatResponse {
+            tool_calls: vec![ToolCall {
+                id: "toolu_1".to_string(),
+                name: "place".to_string(),
+                arguments: json!({"city": "Paris"}),
+            }],
+            finish_reason: Some(FinishReason::ToolCalls),
+            ..Default::default()
+        };
+        unwrap_output_tool(&mut response, "place");
+        assert_eq!(response.output_text, r#"{"city":"Paris"}"#);
+        assert!(response.tool_calls.is_empty());
+        assert_eq!(response.finish_reason, Some(FinishReason::Stop));
+    }
+
+    #[test]
+    fn from_env_reads_default_max_tokens() {
+        env::set_var("ANTHROPIC_API_KEY", "test-key");
+        env::set_var("FORGEAI_DEFAULT_MAX_TOKENS", "4096");
+        let adapter = AnthropicAdapter::from_env().unwrap();
+        env::set_var("FORGEAI_DEFAULT_MAX_TOKENS", "0");
+        let invalid = AnthropicAdapter::from_env();
+        env::remove_var("FORGEAI_DEFAULT_MAX_TOKENS");
+
+        assert_eq!(adapter.default_max_tokens, 4096);
+        assert!(matches!(invalid, Err(ForgeError::Validation(_))));
+
+        let mut request = sample_request();
+        request.max_tokens = None;
+        let body = build_messages_body(request, false, adapter.default_max_tokens, "\n\n");
+        assert_eq!(body["max_tokens"], 4096);
+    }
+
+    #[test]
+    fn image_parts_serialize_as_image_blocks() {
+        let mut request = sample_request();
+        request.messages = vec![Message::new(
+            Role::User,
+            vec![
+                ContentPart::Text {
+                    text: "Compare these".to_string(),
+                },
+                ContentPart::Image {
+                    url_or_base64: "https://example.com/cat.png".to_string(),
+                    mime_type: "image/png".to_string(),
+                },
+                ContentPart::Image {
+                    url_or_base64: "aGVsbG8=".to_string(),
+                    mime_type: "image/jpeg".to_string(),
+                },
+            ],
+        )];
+
+        let body = build_messages_body(request, false, 1024, "\n\n");
+        let content = &body["messages"][0]["content"];
+
+        assert_eq!(content[0], json!({"type": "text", "text": "Compare these"}));
+        assert_eq!(
+            content[1]["source"],
+            json!({"type": "url", "url": "https://example.com/cat.png"})
+        );
+        assert_eq!(
+            content[2]["source"],
+            json!({"type": "base64", "media_type": "image/jpeg", "data": "aGVsbG8="})
+        );
+    }
+
+    #[test]
+    fn system_messages_join_with_configured_separator() {
+        let mut request = sample_request();
+        request
+            .messages
+            .insert(0, Message::new(Role::System, "Be terse."));
+        request
+            .messages
+            .insert(1, Message::new(Role::System, "Answer in French."));
+
+        let body = build_messages_body(request, false, 1024, "\n---\n");
+
+        assert_eq!(body["system"], "Be terse.\n---\nAnswer in French.");
+    }
+
+    #[tokio::test]
+    async fn chat_times_out_with_transport_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"content": []}))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let adapter =
+            AnthropicAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+                .unwrap()
+                .with_timeout(Duration::from_millis(50))
+                .unwrap();
+
+        let err = adapter.chat(sample_request()).await.unwrap_err();
+        assert!(matches!(err, ForgeError::Transport(ref m) if m == "request timed out after 50ms"));
+    }
+
+    #[tokio::test]
+    async fn chat_retries_rate_limits_with_backoff() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"content": []})))
+            .mount(&server)
+            .await;
+
+        let adapter =
+            AnthropicAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+                .unwrap()
+                .with_retry(RetryConfig {
+                    base_delay: Duration::from_millis(1),
+                    ..RetryConfig::default()
+                });
+
+        adapter.chat(sample_request()).await.unwrap();
+        assert_eq!(adapter.request_attempts(), 3);
+    }
 }
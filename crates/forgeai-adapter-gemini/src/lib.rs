@@ -1,13 +1,22 @@
 use async_stream::try_stream;
 use async_trait::async_trait;
+use forgeai_core::http::{build_http_client, env_proxy, env_timeout, extra_header_map, timed_out};
 use forgeai_core::{
-    AdapterInfo, CapabilityMatrix, ChatAdapter, ChatRequest, ChatResponse, ForgeError, Role,
-    StreamEvent, StreamResult, ToolCall, Usage,
+    http, parse_base_url, telemetry, truncate_chars, AdapterInfo, CapabilityMatrix, ChatAdapter,
+    ChatRequest, ChatResponse, Citation, ContentPart, FinishReason, ForgeError, HttpConfig,
+    Message, MessageContent, ProviderError, ResponseFormat, ResponseTiming, RetryConfig, Role,
+    StreamEvent, StreamResult, ToolCall, ToolChoice, TraceContentOptions, Usage,
+    DEFAULT_ERROR_BODY_LIMIT,
 };
 use futures_util::StreamExt;
-use reqwest::{Client as HttpClient, StatusCode};
+use reqwest::header::HeaderMap;
+use reqwest::{Client as HttpClient, NoProxy, Proxy, Response, StatusCode};
 use serde_json::{json, Map, Value};
+use std::collections::HashMap;
 use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use url::Url;
 
 #[derive(Clone, Debug)]
@@ -15,7 +24,23 @@ pub struct GeminiAdapter {
     pub api_key: String,
     pub base_url: Url,
     pub api_version: String,
+    /// Joins multiple system messages into the provider's single system prompt.
+    pub system_separator: String,
+    /// Attach `ResponseTiming` to responses returned by `chat`.
+    pub collect_timing: bool,
+    /// Send the key as `x-goog-api-key` instead of the `key` query parameter.
+    key_in_header: bool,
+    trace_content: TraceContentOptions,
+    timeout: Option<Duration>,
+    retry: Option<RetryConfig>,
+    http_config: Option<HttpConfig>,
+    proxy: Option<Proxy>,
+    error_body_limit: usize,
+    capabilities: CapabilityMatrix,
+    attempts: Arc<AtomicUsize>,
     client: HttpClient,
+    /// Set by `with_http_client`; the client is never rebuilt.
+    shared_client: bool,
 }
 
 impl GeminiAdapter {
@@ -26,26 +51,168 @@ impl GeminiAdapter {
     }
 
     pub fn with_base_url(api_key: impl Into<String>, base_url: Url) -> Result<Self, ForgeError> {
-        let client = HttpClient::builder()
-            .build()
-            .map_err(|e| ForgeError::Internal(format!("failed to build http client: {e}")))?;
-        Ok(Self {
+        let mut adapter =
+            Self::with_http_client(api_key, base_url, build_http_client(None, None, None)?);
+        adapter.shared_client = false;
+        Ok(adapter)
+    }
+
+    /// Uses `client` for every request instead of building one, so adapters
+    /// can share a connection pool and TLS/proxy settings. The client is
+    /// cloned, which shares its pool. `with_timeout` then only bounds each
+    /// request and leaves the client's connect timeout alone, and
+    /// `with_http_config` is rejected; configure the client instead.
+    pub fn with_http_client(api_key: impl Into<String>, base_url: Url, client: HttpClient) -> Self {
+        Self {
             api_key: api_key.into(),
             base_url,
             api_version: "v1beta".to_string(),
+            system_separator: "\n\n".to_string(),
+            collect_timing: false,
+            key_in_header: false,
+            trace_content: TraceContentOptions::default(),
+            timeout: None,
+            retry: None,
+            http_config: None,
+            proxy: None,
+            error_body_limit: DEFAULT_ERROR_BODY_LIMIT,
+            capabilities: CapabilityMatrix {
+                streaming: true,
+                tools: true,
+                structured_output: true,
+                multimodal_input: true,
+                citations: true,
+            },
+            attempts: Arc::new(AtomicUsize::new(0)),
             client,
-        })
+            shared_client: true,
+        }
+    }
+
+    /// Points an already configured adapter at `base_url`, e.g. one read from
+    /// a config file. Fails with `ForgeError::Validation` unless it is a valid
+    /// `http` or `https` URL.
+    pub fn with_base_url_str(mut self, base_url: impl AsRef<str>) -> Result<Self, ForgeError> {
+        self.base_url = parse_base_url(base_url.as_ref())?;
+        Ok(self)
+    }
+
+    /// Bounds `chat` end to end and the connect/response-header phase of
+    /// `chat_stream`; an established stream body is not cut off.
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self, ForgeError> {
+        if !self.shared_client {
+            self.client = build_http_client(
+                Some(timeout),
+                self.http_config.as_ref(),
+                self.proxy.as_ref(),
+            )?;
+        }
+        self.timeout = Some(timeout);
+        Ok(self)
+    }
+
+    pub fn with_system_separator(mut self, separator: impl Into<String>) -> Self {
+        self.system_separator = separator.into();
+        self
+    }
+
+    /// Tunes connection pooling and keep-alive of the HTTP client; see
+    /// `HttpConfig` for the defaults it suggests.
+    pub fn with_http_config(mut self, config: HttpConfig) -> Result<Self, ForgeError> {
+        if self.shared_client {
+            return Err(ForgeError::Validation(
+                "with_http_config cannot tune a client passed to with_http_client".to_string(),
+            ));
+        }
+        self.client = build_http_client(self.timeout, Some(&config), self.proxy.as_ref())?;
+        self.http_config = Some(config);
+        Ok(self)
+    }
+
+    /// Sends the API key in the `x-goog-api-key` header rather than the `key`
+    /// query parameter, which keeps it out of URLs and access logs. Some
+    /// Gemini-compatible gateways only accept the header.
+    pub fn with_api_key_in_header(mut self, enabled: bool) -> Self {
+        self.key_in_header = enabled;
+        self
+    }
+
+    /// Sends every request through the proxy at `url`, e.g.
+    /// `http://proxy.corp:3128`; hosts listed in `NO_PROXY` bypass it. Fails
+    /// with `ForgeError::Validation` when `url` is not a valid proxy URL.
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, ForgeError> {
+        if self.shared_client {
+            return Err(ForgeError::Validation(
+                "with_proxy cannot reconfigure a client passed to with_http_client".to_string(),
+            ));
+        }
+        let proxy = Proxy::all(url)
+            .map_err(|e| ForgeError::Validation(format!("invalid proxy URL {url:?}: {e}")))?
+            .no_proxy(NoProxy::from_env());
+        self.client = build_http_client(self.timeout, self.http_config.as_ref(), Some(&proxy))?;
+        self.proxy = Some(proxy);
+        Ok(self)
+    }
+
+    /// Retries rate-limited and transport failures of `chat` and of the initial
+    /// `chat_stream` request with exponential backoff, honoring `Retry-After`
+    /// up to `config.max_delay`; longer provider delays are cut to it. An
+    /// established stream is never retried.
+    pub fn with_retry(mut self, config: RetryConfig) -> Self {
+        self.retry = Some(config);
+        self
+    }
+
+    /// Sets what the `tracing` feature records about prompts and completions.
+    pub fn with_trace_content(mut self, options: TraceContentOptions) -> Self {
+        self.trace_content = options;
+        self
+    }
+
+    /// Caps how many characters of a non-JSON error body (e.g. an HTML error
+    /// page) are kept as the error message; defaults to
+    /// `DEFAULT_ERROR_BODY_LIMIT`.
+    pub fn with_error_body_limit(mut self, max_chars: usize) -> Self {
+        self.error_body_limit = max_chars;
+        self
+    }
+
+    /// Replaces the `CapabilityMatrix` reported by `info()`, e.g. for a proxy
+    /// that does not support tools.
+    pub fn with_capabilities(mut self, capabilities: CapabilityMatrix) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// The JSON body `chat` would send for `request`, for debugging and
+    /// comparing providers without a server. It holds no credentials: auth
+    /// and `extra_headers` travel as headers, and the endpoint URL is not
+    /// part of it.
+    pub fn build_request_body(&self, request: &ChatRequest) -> Value {
+        build_generate_body(request.clone(), &self.system_separator)
+    }
+
+    /// Total HTTP attempts made by this adapter, including retries.
+    pub fn request_attempts(&self) -> usize {
+        self.attempts.load(Ordering::Relaxed)
     }
 
     pub fn from_env() -> Result<Self, ForgeError> {
         let api_key = env::var("GEMINI_API_KEY").map_err(|_| ForgeError::Authentication)?;
-        match env::var("GEMINI_BASE_URL") {
+        let mut adapter = match env::var("GEMINI_BASE_URL") {
             Ok(raw) => {
                 let base_url = Url::parse(&raw)
                     .map_err(|e| ForgeError::Validation(format!("invalid GEMINI_BASE_URL: {e}")))?;
-                Self::with_base_url(api_key, base_url)
+                Self::with_base_url(api_key, base_url)?
             }
-            Err(_) => Self::new(api_key),
+            Err(_) => Self::new(api_key)?,
+        };
+        if let Some(proxy) = env_proxy() {
+            adapter = adapter.with_proxy(&proxy)?;
+        }
+        match env_timeout()? {
+            Some(timeout) => adapter.with_timeout(timeout),
+            None => Ok(adapter),
         }
     }
 
@@ -66,13 +233,55 @@ impl GeminiAdapter {
             .map_err(|e| ForgeError::Internal(format!("failed to construct endpoint url: {e}")))?;
         {
             let mut qp = url.query_pairs_mut();
-            qp.append_pair("key", &self.api_key);
+            if !self.key_in_header {
+                qp.append_pair("key", &self.api_key);
+            }
             if stream {
                 qp.append_pair("alt", "sse");
             }
         }
         Ok(url)
     }
+
+    async fn send(
+        &self,
+        url: &Url,
+        body: &Value,
+        headers: &HeaderMap,
+        stream: bool,
+    ) -> Result<Response, ForgeError> {
+        telemetry::request_body(body);
+        http::send_with_retry(self.retry, &self.attempts, || {
+            self.send_once(url, body, headers, stream)
+        })
+        .await
+    }
+
+    async fn send_once(
+        &self,
+        url: &Url,
+        body: &Value,
+        headers: &HeaderMap,
+        stream: bool,
+    ) -> Result<Response, ForgeError> {
+        let mut request = self
+            .client
+            .post(url.clone())
+            .json(body)
+            .headers(headers.clone());
+        if self.key_in_header {
+            request = request.header("x-goog-api-key", &self.api_key);
+        }
+        http::send_request(
+            request,
+            self.timeout,
+            stream,
+            |status, retry_after, body| {
+                parse_http_error(status, retry_after, body, self.error_body_limit)
+            },
+        )
+        .await
+    }
 }
 
 #[async_trait]
@@ -80,154 +289,159 @@ impl ChatAdapter for GeminiAdapter {
     fn info(&self) -> AdapterInfo {
         AdapterInfo {
             name: "gemini".to_string(),
-            base_url: Url::parse("https://generativelanguage.googleapis.com").ok(),
-            capabilities: CapabilityMatrix {
-                streaming: true,
-                tools: true,
-                structured_output: true,
-                multimodal_input: true,
-                citations: true,
-            },
+            base_url: Some(self.base_url.clone()),
+            capabilities: self.capabilities.clone(),
         }
     }
 
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
-        let url = self.endpoint_url(&request.model, false)?;
         let model = request.model.clone();
-        let response = self
-            .client
-            .post(url)
-            .json(&build_generate_body(request))
-            .send()
-            .await
-            .map_err(|e| ForgeError::Transport(format!("request failed: {e}")))?;
+        telemetry::instrument("chat", "gemini", &model, async move {
+            telemetry::prompt(&self.trace_content, &request);
+            reject_multiple_choices(&request)?;
+            check_stop_sequences(&request)?;
+            let url = self.endpoint_url(&request.model, false)?;
+            let model = request.model.clone();
+            let headers = extra_header_map(&request.extra_headers)?;
+            let body = build_generate_body(request, &self.system_separator);
+            let started = Instant::now();
+            let response = self.send(&url, &body, &headers, false).await?;
+            let first_byte = started.elapsed();
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
+            let payload = response
+                .json::<Value>()
                 .await
-                .unwrap_or_else(|_| "failed to read error body".to_string());
-            return Err(parse_http_error(status, text));
-        }
-
-        let payload = response
-            .json::<Value>()
-            .await
-            .map_err(|e| ForgeError::Provider(format!("invalid json response: {e}")))?;
-        parse_chat_response(model, payload)
+                .map_err(|e| match self.timeout {
+                    Some(timeout) if e.is_timeout() => timed_out(timeout),
+                    _ => ForgeError::Provider(format!("invalid json response: {e}").into()),
+                })?;
+            let mut response = parse_chat_response(model, payload)?;
+            telemetry::completion(&self.trace_content, &response.output_text);
+            if let Some(usage) = &response.usage {
+                telemetry::usage(usage);
+            }
+            if self.collect_timing {
+                response.timing = Some(ResponseTiming {
+                    total: started.elapsed(),
+                    first_byte: Some(first_byte),
+                });
+            }
+            Ok(response)
+        })
+        .await
     }
 
     async fn chat_stream(
         &self,
         request: ChatRequest,
     ) -> Result<StreamResult<StreamEvent>, ForgeError> {
-        let url = self.endpoint_url(&request.model, true)?;
-        let response = self
-            .client
-            .post(url)
-            .json(&build_generate_body(request))
-            .send()
-            .await
-            .map_err(|e| ForgeError::Transport(format!("stream request failed: {e}")))?;
+        let model = request.model.clone();
+        telemetry::instrument("chat_stream", "gemini", &model, async move {
+            telemetry::prompt(&self.trace_content, &request);
+            reject_multiple_choices(&request)?;
+            check_stop_sequences(&request)?;
+            let url = self.endpoint_url(&request.model, true)?;
+            let headers = extra_header_map(&request.extra_headers)?;
+            let body = build_generate_body(request, &self.system_separator);
+            let response = self.send(&url, &body, &headers, true).await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "failed to read error body".to_string());
-            return Err(parse_http_error(status, text));
-        }
+            let mut bytes = response.bytes_stream();
+            let stream = try_stream! {
+                let mut buffer = String::new();
+                // Each candidate may report its own finishReason, possibly in
+                // different chunks, so the terminal events are emitted once the
+                // body ends rather than per payload.
+                let mut finish_reason: Option<FinishReason> = None;
+                let mut started = false;
 
-        let mut bytes = response.bytes_stream();
-        let stream = try_stream! {
-            let mut buffer = String::new();
-            let mut saw_done = false;
-
-            while let Some(chunk) = bytes.next().await {
-                let chunk = chunk.map_err(|e| ForgeError::Transport(format!("stream chunk error: {e}")))?;
-                let chunk_text = std::str::from_utf8(&chunk)
-                    .map_err(|e| ForgeError::Transport(format!("invalid utf8 stream chunk: {e}")))?;
-                buffer.push_str(chunk_text);
-
-                while let Some(line_end) = buffer.find('\n') {
-                    let mut line = buffer[..line_end].to_string();
-                    buffer.drain(..=line_end);
-                    if line.ends_with('\r') {
-                        line.pop();
-                    }
-                    if line.trim().is_empty() {
-                        continue;
-                    }
-                    if let Some(data) = line.strip_prefix("data:") {
-                        let payload = data.trim();
-                        if payload == "[DONE]" {
-                            saw_done = true;
-                            yield StreamEvent::Done;
-                            continue;
+                while let Some(chunk) = bytes.next().await {
+                    let chunk = chunk.map_err(|e| ForgeError::Transport(format!("stream chunk error: {e}")))?;
+                    let chunk_text = std::str::from_utf8(&chunk)
+                        .map_err(|e| ForgeError::Transport(format!("invalid utf8 stream chunk: {e}")))?;
+                    buffer.push_str(chunk_text);
+
+                    while let Some(line_end) = buffer.find('\n') {
+                        let mut line = buffer[..line_end].to_string();
+                        buffer.drain(..=line_end);
+                        if line.ends_with('\r') {
+                            line.pop();
                         }
-                        for event in parse_stream_payload(payload)? {
-                            if matches!(event, StreamEvent::Done) {
-                                saw_done = true;
-                            }
+                        for event in parse_stream_line(&line, &mut started, &mut finish_reason)? {
                             yield event;
                         }
                     }
                 }
-            }
 
-            if !buffer.trim().is_empty() {
-                let line = buffer.trim();
-                if let Some(data) = line.strip_prefix("data:") {
-                    let payload = data.trim();
-                    if payload == "[DONE]" {
-                        saw_done = true;
-                        yield StreamEvent::Done;
-                    } else {
-                        for event in parse_stream_payload(payload)? {
-                            if matches!(event, StreamEvent::Done) {
-                                saw_done = true;
-                            }
-                            yield event;
-                        }
-                    }
+                for event in parse_stream_line(buffer.trim(), &mut started, &mut finish_reason)? {
+                    yield event;
                 }
-            }
 
-            if !saw_done {
+                if let Some(reason) = finish_reason {
+                    yield StreamEvent::FinishReason { reason };
+                }
                 yield StreamEvent::Done;
-            }
-        };
+            };
 
-        Ok(Box::pin(stream))
+            Ok(Box::pin(stream) as StreamResult<StreamEvent>)
+        })
+        .await
     }
 }
 
-fn build_generate_body(request: ChatRequest) -> Value {
+fn build_generate_body(request: ChatRequest, system_separator: &str) -> Value {
     let mut body = Map::new();
+    let mut generation_config = Map::new();
     if let Some(temperature) = request.temperature {
+        generation_config.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(top_p) = request.top_p {
+        generation_config.insert("topP".to_string(), json!(top_p));
+    }
+    if let Some(penalty) = request.frequency_penalty {
+        generation_config.insert("frequencyPenalty".to_string(), json!(penalty));
+    }
+    if let Some(penalty) = request.presence_penalty {
+        generation_config.insert("presencePenalty".to_string(), json!(penalty));
+    }
+    if let Some(max_tokens) = request.max_tokens {
+        generation_config.insert("maxOutputTokens".to_string(), json!(max_tokens));
+    }
+    if let Some(seed) = request.seed {
+        generation_config.insert("seed".to_string(), json!(seed));
+    }
+    if !request.stop.is_empty() {
+        generation_config.insert("stopSequences".to_string(), json!(request.stop));
+    }
+    // Gemini has no strict mode; the schema always constrains decoding.
+    match request.response_format {
+        Some(ResponseFormat::Text) => {
+            generation_config.insert("responseMimeType".to_string(), json!("text/plain"));
+        }
+        Some(ResponseFormat::JsonObject) => {
+            generation_config.insert("responseMimeType".to_string(), json!("application/json"));
+        }
+        Some(ResponseFormat::JsonSchema { schema, .. }) => {
+            generation_config.insert("responseMimeType".to_string(), json!("application/json"));
+            generation_config.insert("responseSchema".to_string(), schema);
+        }
+        None => {}
+    }
+    if !generation_config.is_empty() {
         body.insert(
             "generationConfig".to_string(),
-            json!({
-                "temperature": temperature,
-                "maxOutputTokens": request.max_tokens
-            }),
-        );
-    } else if let Some(max_tokens) = request.max_tokens {
-        body.insert(
-            "generationConfig".to_string(),
-            json!({
-                "maxOutputTokens": max_tokens
-            }),
+            Value::Object(generation_config),
         );
     }
 
     let mut contents = Vec::new();
-    let mut system_chunks = Vec::new();
+    let mut system_chunks: Vec<String> = request.system.into_iter().collect();
+    let mut call_names: HashMap<String, String> = HashMap::new();
+    // Calls of the latest model turn not answered yet. Gemini usually omits
+    // call ids, so results whose id is empty are matched to calls in order.
+    let mut pending_calls: Vec<(String, String)> = Vec::new();
     for message in request.messages {
         if matches!(message.role, Role::System) {
-            system_chunks.push(message.content);
+            system_chunks.push(message.content.text());
             continue;
         }
         let role = if matches!(message.role, Role::Assistant) {
@@ -235,9 +449,22 @@ fn build_generate_body(request: ChatRequest) -> Value {
         } else {
             "user"
         };
+        if !message.tool_calls.is_empty() {
+            pending_calls.clear();
+        }
+        for call in &message.tool_calls {
+            call_names.insert(call.id.clone(), call.name.clone());
+            pending_calls.push((call.id.clone(), call.name.clone()));
+        }
+        let result_name = message.tool_call_id.as_ref().and_then(|id| {
+            match pending_calls.iter().position(|(call_id, _)| call_id == id) {
+                Some(position) => Some(pending_calls.remove(position).1),
+                None => call_names.get(id).cloned(),
+            }
+        });
         contents.push(json!({
             "role": role,
-            "parts": [{ "text": message.content }]
+            "parts": message_parts(message, result_name)
         }));
     }
     body.insert("contents".to_string(), Value::Array(contents));
@@ -247,7 +474,7 @@ fn build_generate_body(request: ChatRequest) -> Value {
             "systemInstruction".to_string(),
             json!({
                 "parts": [{
-                    "text": system_chunks.join("\n\n")
+                    "text": system_chunks.join(system_separator)
                 }]
             }),
         );
@@ -260,48 +487,156 @@ fn build_generate_body(request: ChatRequest) -> Value {
                 request
                     .tools
                     .into_iter()
-                    .map(|tool| {
-                        json!({
+                    .map(|tool| match tool.raw {
+                        Some(raw) => raw,
+                        None => json!({
                             "functionDeclarations": [{
                                 "name": tool.name,
                                 "description": tool.description,
                                 "parameters": tool.input_schema
                             }]
-                        })
+                        }),
                     })
                     .collect(),
             ),
         );
     }
+    if let Some(choice) = request.tool_choice {
+        let config = match choice {
+            ToolChoice::Auto => json!({"mode": "AUTO"}),
+            ToolChoice::None => json!({"mode": "NONE"}),
+            ToolChoice::Required => json!({"mode": "ANY"}),
+            ToolChoice::Function(name) => json!({"mode": "ANY", "allowedFunctionNames": [name]}),
+        };
+        body.insert(
+            "toolConfig".to_string(),
+            json!({ "functionCallingConfig": config }),
+        );
+    }
 
     Value::Object(body)
 }
 
-fn parse_http_error(status: StatusCode, body: String) -> ForgeError {
-    let message = extract_provider_error(body);
+/// `result_name` is the name of the call a tool result answers; Gemini keys
+/// function responses by name rather than id.
+fn message_parts(message: Message, result_name: Option<String>) -> Vec<Value> {
+    if let Some(name) = result_name {
+        let output = message.content.text();
+        let response = match serde_json::from_str::<Value>(&output) {
+            Ok(value @ Value::Object(_)) => value,
+            _ => json!({ "output": output }),
+        };
+        return vec![json!({
+            "functionResponse": {
+                "name": name,
+                "response": response
+            }
+        })];
+    }
+
+    let mut parts = Vec::new();
+    match message.content {
+        // Gemini rejects empty text parts, so a pure tool-call turn only carries functionCall.
+        MessageContent::Text(text) => {
+            if !text.is_empty() || message.tool_calls.is_empty() {
+                parts.push(json!({ "text": text }));
+            }
+        }
+        MessageContent::Parts(content) => parts.extend(content.into_iter().map(part_to_gemini)),
+    }
+    for call in message.tool_calls {
+        parts.push(json!({
+            "functionCall": {
+                "name": call.name,
+                "args": call.arguments
+            }
+        }));
+    }
+    parts
+}
+
+fn part_to_gemini(part: ContentPart) -> Value {
+    let url = part.image_url().map(str::to_string);
+    match (part, url) {
+        (ContentPart::Text { text }, _) => json!({ "text": text }),
+        (ContentPart::Image { mime_type, .. }, Some(url)) => {
+            json!({ "fileData": { "mimeType": mime_type, "fileUri": url } })
+        }
+        (
+            ContentPart::Image {
+                url_or_base64,
+                mime_type,
+            },
+            None,
+        ) => json!({ "inlineData": { "mimeType": mime_type, "data": url_or_base64 } }),
+    }
+}
+
+fn parse_http_error(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    body: String,
+    body_limit: usize,
+) -> ForgeError {
+    let error = extract_provider_error(status, body, body_limit);
     match status {
         StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ForgeError::Authentication,
-        StatusCode::TOO_MANY_REQUESTS => ForgeError::RateLimited,
-        _ => ForgeError::Provider(message),
+        StatusCode::TOO_MANY_REQUESTS => ForgeError::RateLimited { retry_after },
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            ForgeError::Transport(format!("{status}: {}", error.message))
+        }
+        _ => ForgeError::Provider(error),
     }
 }
 
-fn extract_provider_error(body: String) -> String {
-    serde_json::from_str::<Value>(&body)
-        .ok()
-        .and_then(|v| {
-            v.get("error")
-                .and_then(|e| e.get("message"))
-                .and_then(Value::as_str)
-                .map(ToString::to_string)
-        })
-        .unwrap_or(body)
+/// Gemini returns a single completion per request.
+fn reject_multiple_choices(request: &ChatRequest) -> Result<(), ForgeError> {
+    if request.n.is_some_and(|n| n > 1) {
+        return Err(ForgeError::Validation(
+            "gemini does not support n > 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Stop sequences `generationConfig.stopSequences` accepts.
+const MAX_STOP_SEQUENCES: usize = 5;
+
+/// Rejects more stop sequences than Gemini accepts, before it answers 400.
+fn check_stop_sequences(request: &ChatRequest) -> Result<(), ForgeError> {
+    if request.stop.len() > MAX_STOP_SEQUENCES {
+        return Err(ForgeError::Validation(format!(
+            "gemini accepts at most {MAX_STOP_SEQUENCES} stop sequences, got {}",
+            request.stop.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Reads `{"error": {"message", "status"}}`, falling back to the raw body.
+fn extract_provider_error(status: StatusCode, body: String, body_limit: usize) -> ProviderError {
+    let parsed = serde_json::from_str::<Value>(&body).ok();
+    let error = parsed.as_ref().and_then(|v| v.get("error"));
+    let field = |name: &str| {
+        error
+            .and_then(|e| e.get(name))
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    };
+    ProviderError {
+        message: field("message").unwrap_or_else(|| truncate_chars(body, body_limit)),
+        code: field("status"),
+        kind: None,
+        http_status: status.as_u16(),
+    }
 }
 
 fn parse_chat_response(model: String, payload: Value) -> Result<ChatResponse, ForgeError> {
     let output_text = extract_text_from_payload(&payload);
     let tool_calls = extract_tool_calls_from_payload(&payload);
     let usage = extract_usage(payload.get("usageMetadata"));
+    let finish_reason = extract_finish_reason(&payload, !tool_calls.is_empty());
+    let citations = extract_citations(&payload);
 
     Ok(ChatResponse {
         id: payload
@@ -311,8 +646,110 @@ fn parse_chat_response(model: String, payload: Value) -> Result<ChatResponse, Fo
             .to_string(),
         model,
         output_text,
+        reasoning: None,
         tool_calls,
         usage,
+        finish_reason,
+        timing: None,
+        citations,
+        system_fingerprint: None,
+        audio: None,
+        choices: Vec::new(),
+    })
+}
+
+/// One citation per chunk a `groundingSupports` entry points at, spanning
+/// that support's segment; chunks no support refers to are listed without a
+/// span.
+fn extract_citations(payload: &Value) -> Vec<Citation> {
+    let mut citations = Vec::new();
+    let candidates = payload
+        .get("candidates")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    for metadata in candidates.filter_map(|c| c.get("groundingMetadata")) {
+        let sources: Vec<(String, Option<String>)> = metadata
+            .get("groundingChunks")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|chunk| {
+                let source = chunk.get("web").or_else(|| chunk.get("retrievedContext"))?;
+                let uri = source.get("uri")?.as_str()?.to_string();
+                let title = source
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                Some((uri, title))
+            })
+            .collect();
+        let mut cited = vec![false; sources.len()];
+        for support in metadata
+            .get("groundingSupports")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let segment = support.get("segment");
+            let index = |key: &str| {
+                segment
+                    .and_then(|s| s.get(key))
+                    .and_then(Value::as_u64)
+                    .map(|n| n as usize)
+            };
+            let end_index = index("endIndex");
+            // Zero offsets are omitted from the JSON.
+            let start_index = index("startIndex").or(end_index.map(|_| 0));
+            for chunk in support
+                .get("groundingChunkIndices")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_u64)
+            {
+                let Some((uri, title)) = sources.get(chunk as usize) else {
+                    continue;
+                };
+                cited[chunk as usize] = true;
+                citations.push(Citation {
+                    uri: uri.clone(),
+                    title: title.clone(),
+                    start_index,
+                    end_index,
+                });
+            }
+        }
+        for ((uri, title), cited) in sources.into_iter().zip(cited) {
+            if !cited {
+                citations.push(Citation {
+                    uri,
+                    title,
+                    start_index: None,
+                    end_index: None,
+                });
+            }
+        }
+    }
+    citations
+}
+
+/// Gemini reports `STOP` for function-call turns too, so `has_tool_calls`
+/// distinguishes them.
+fn extract_finish_reason(payload: &Value, has_tool_calls: bool) -> Option<FinishReason> {
+    let raw = payload
+        .get("candidates")
+        .and_then(Value::as_array)?
+        .iter()
+        .find_map(|c| c.get("finishReason").and_then(Value::as_str))?;
+    Some(match raw {
+        "STOP" if has_tool_calls => FinishReason::ToolCalls,
+        "STOP" => FinishReason::Stop,
+        "MAX_TOKENS" => FinishReason::Length,
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" => {
+            FinishReason::ContentFilter
+        }
+        other => FinishReason::Other(other.to_string()),
     })
 }
 
@@ -383,34 +820,77 @@ fn extract_usage(raw: Option<&Value>) -> Option<Usage> {
     let input_tokens = usage
         .get("promptTokenCount")
         .and_then(Value::as_u64)
-        .unwrap_or(0) as u32;
+        .unwrap_or(0);
     let output_tokens = usage
         .get("candidatesTokenCount")
         .and_then(Value::as_u64)
-        .unwrap_or(0) as u32;
+        .unwrap_or(0);
     let total_tokens = usage
         .get("totalTokenCount")
         .and_then(Value::as_u64)
-        .map(|v| v as u32)
         .unwrap_or_else(|| input_tokens.saturating_add(output_tokens));
+    let count = |key: &str| usage.get(key).and_then(Value::as_u64);
     Some(Usage {
         input_tokens,
         output_tokens,
         total_tokens,
+        cache_creation_input_tokens: None,
+        cached_input_tokens: count("cachedContentTokenCount"),
+        reasoning_tokens: count("thoughtsTokenCount"),
     })
 }
 
-fn parse_stream_payload(payload: &str) -> Result<Vec<StreamEvent>, ForgeError> {
+/// Parses one SSE line. The first finish reason seen is stored in
+/// `finish_reason` instead of being emitted; `started` records whether
+/// `ResponseStart` was already emitted, since every chunk repeats the id.
+fn parse_stream_line(
+    line: &str,
+    started: &mut bool,
+    finish_reason: &mut Option<FinishReason>,
+) -> Result<Vec<StreamEvent>, ForgeError> {
+    let Some(data) = line.strip_prefix("data:") else {
+        return Ok(Vec::new());
+    };
+    let payload = data.trim();
+    if payload.is_empty() || payload == "[DONE]" {
+        return Ok(Vec::new());
+    }
+    let (events, reason) = parse_stream_payload(payload, started)?;
+    if finish_reason.is_none() {
+        *finish_reason = reason;
+    }
+    Ok(events)
+}
+
+fn parse_stream_payload(
+    payload: &str,
+    started: &mut bool,
+) -> Result<(Vec<StreamEvent>, Option<FinishReason>), ForgeError> {
     let value = serde_json::from_str::<Value>(payload)
-        .map_err(|e| ForgeError::Provider(format!("invalid stream payload: {e}")))?;
+        .map_err(|e| ForgeError::Provider(format!("invalid stream payload: {e}").into()))?;
 
     let mut events = Vec::new();
+    if !*started {
+        if let Some(id) = value.get("responseId").and_then(Value::as_str) {
+            *started = true;
+            events.push(StreamEvent::ResponseStart {
+                id: id.to_string(),
+                model: value
+                    .get("modelVersion")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            });
+        }
+    }
     let text = extract_text_from_payload(&value);
     if !text.is_empty() {
         events.push(StreamEvent::TextDelta { delta: text });
     }
 
-    for tool_call in extract_tool_calls_from_payload(&value) {
+    let tool_calls = extract_tool_calls_from_payload(&value);
+    let has_tool_calls = !tool_calls.is_empty();
+    for tool_call in tool_calls {
         events.push(StreamEvent::ToolCallDelta {
             call_id: tool_call.id,
             delta: json!({
@@ -424,41 +904,75 @@ fn parse_stream_payload(payload: &str) -> Result<Vec<StreamEvent>, ForgeError> {
         events.push(StreamEvent::Usage { usage });
     }
 
-    if value
-        .get("candidates")
-        .and_then(Value::as_array)
-        .and_then(|items| items.first())
-        .and_then(|c| c.get("finishReason"))
-        .is_some()
-    {
-        events.push(StreamEvent::Done);
-    }
-
-    Ok(events)
+    Ok((events, extract_finish_reason(&value, has_tool_calls)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use forgeai_core::{ChatRequest, Message, Role};
+    use forgeai_core::ToolDefinition;
     use futures_util::StreamExt;
-    use wiremock::matchers::{body_partial_json, method, path, query_param};
+    use wiremock::matchers::{
+        body_partial_json, header, method, path, query_param, query_param_is_missing,
+    };
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn sample_request() -> ChatRequest {
         ChatRequest {
             model: "gemini-1.5-flash".to_string(),
-            messages: vec![Message {
-                role: Role::User,
-                content: "Say hello".to_string(),
-            }],
+            messages: vec![Message::new(Role::User, "Say hello")],
+            system: None,
             temperature: Some(0.2),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             max_tokens: Some(64),
+            n: None,
+            seed: None,
+            stop: vec![],
             tools: vec![],
+            tool_choice: None,
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::new(),
             metadata: json!({}),
         }
     }
 
+    #[test]
+    fn info_reports_configured_base_url() {
+        let adapter = GeminiAdapter::new("test-key")
+            .unwrap()
+            .with_base_url_str("http://localhost:8080/")
+            .unwrap();
+        assert_eq!(
+            adapter.info().base_url.unwrap().as_str(),
+            "http://localhost:8080/"
+        );
+    }
+
+    #[tokio::test]
+    async fn api_key_can_be_sent_in_header() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1beta/models/gemini-1.5-flash:generateContent"))
+            .and(query_param_is_missing("key"))
+            .and(header("x-goog-api-key", "test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "candidates": [{"content": {"parts": [{"text": "Hi"}]}}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let adapter = GeminiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+            .unwrap()
+            .with_api_key_in_header(true);
+        let response = adapter.chat(sample_request()).await.unwrap();
+        assert_eq!(response.output_text, "Hi");
+    }
+
     #[tokio::test]
     async fn chat_contract_parses_response_and_usage() {
         let server = MockServer::start().await;
@@ -471,12 +985,15 @@ mod tests {
                 "candidates": [{
                     "content": {
                         "parts": [{"text":"Hello from Gemini"}]
-                    }
+                    },
+                    "finishReason": "MAX_TOKENS"
                 }],
                 "usageMetadata": {
                     "promptTokenCount": 9,
                     "candidatesTokenCount": 4,
-                    "totalTokenCount": 13
+                    "totalTokenCount": 13,
+                    "cachedContentTokenCount": 6,
+                    "thoughtsTokenCount": 2
                 }
             })))
             .mount(&server)
@@ -489,7 +1006,11 @@ mod tests {
         assert_eq!(response.id, "resp_123");
         assert_eq!(response.model, "gemini-1.5-flash");
         assert_eq!(response.output_text, "Hello from Gemini");
-        assert_eq!(response.usage.unwrap().total_tokens, 13);
+        let usage = response.usage.unwrap();
+        assert_eq!(usage.total_tokens, 13);
+        assert_eq!(usage.cached_input_tokens, Some(6));
+        assert_eq!(usage.reasoning_tokens, Some(2));
+        assert_eq!(response.finish_reason, Some(FinishReason::Length));
     }
 
     #[tokio::test]
@@ -533,6 +1054,341 @@ mod tests {
         assert!(events
             .iter()
             .any(|e| matches!(e, StreamEvent::Usage { usage } if usage.total_tokens == 11)));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::FinishReason {
+                reason: FinishReason::Stop
+            }
+        )));
         assert!(events.iter().any(|e| matches!(e, StreamEvent::Done)));
     }
+
+    #[tokio::test]
+    async fn multi_candidate_stream_emits_single_done() {
+        let server = MockServer::start().await;
+        let sse_body = concat!(
+            "data: {\"candidates\":[{\"index\":0,\"content\":{\"parts\":[{\"text\":\"A\"}]},\"finishReason\":\"STOP\"},{\"index\":1,\"content\":{\"parts\":[{\"text\":\"B\"}]}}]}\n\n",
+            "data: {\"candidates\":[{\"index\":1,\"content\":{\"parts\":[{\"text\":\"C\"}]},\"finishReason\":\"STOP\"}]}\n\n"
+        );
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(sse_body, "text/event-stream"))
+            .mount(&server)
+            .await;
+
+        let adapter =
+            GeminiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap()).unwrap();
+        let events: Vec<_> = adapter
+            .chat_stream(sample_request())
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        let text: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::TextDelta { delta } => Some(delta.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "ABC");
+        let finishes = events
+            .iter()
+            .filter(|e| matches!(e, StreamEvent::FinishReason { .. }))
+            .count();
+        let dones = events
+            .iter()
+            .filter(|e| matches!(e, StreamEvent::Done))
+            .count();
+        assert_eq!((finishes, dones), (1, 1));
+        assert!(matches!(events.last(), Some(StreamEvent::Done)));
+    }
+
+    #[test]
+    fn parses_citations_from_grounding_metadata() {
+        let payload = json!({
+            "candidates": [{
+                "content": {"parts": [{"text": "Rust 1.0 shipped in 2015. It is popular."}]},
+                "finishReason": "STOP",
+                "groundingMetadata": {
+                    "groundingChunks": [
+                        {"web": {"uri": "https://blog.rust-lang.org/2015/05/15/Rust-1.0.html", "title": "rust-lang.org"}},
+                        {"web": {"uri": "https://survey.stackoverflow.co/2024", "title": "stackoverflow.co"}},
+                        {"web": {"uri": "https://example.com/unused"}}
+                    ],
+                    "groundingSupports": [
+                        {"segment": {"endIndex": 25, "text": "Rust 1.0 shipped in 2015."}, "groundingChunkIndices": [0]},
+                        {"segment": {"startIndex": 26, "endIndex": 41, "text": "It is popular."}, "groundingChunkIndices": [1, 0]}
+                    ]
+                }
+            }]
+        });
+
+        let response = parse_chat_response("gemini-2.0-flash".to_string(), payload).unwrap();
+
+        let spans: Vec<_> = response
+            .citations
+            .iter()
+            .map(|c| (c.uri.as_str(), c.start_index, c.end_index))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (
+                    "https://blog.rust-lang.org/2015/05/15/Rust-1.0.html",
+                    Some(0),
+                    Some(25)
+                ),
+                ("https://survey.stackoverflow.co/2024", Some(26), Some(41)),
+                (
+                    "https://blog.rust-lang.org/2015/05/15/Rust-1.0.html",
+                    Some(26),
+                    Some(41)
+                ),
+                ("https://example.com/unused", None, None),
+            ]
+        );
+        assert_eq!(
+            response.citations[0].title.as_deref(),
+            Some("rust-lang.org")
+        );
+        assert_eq!(response.citations[3].title, None);
+    }
+
+    #[test]
+    fn provider_error_keeps_status_code() {
+        let err = parse_http_error(
+            StatusCode::BAD_REQUEST,
+            None,
+            json!({"error": {
+                "code": 400,
+                "message": "API key not valid.",
+                "status": "INVALID_ARGUMENT"
+            }})
+            .to_string(),
+            DEFAULT_ERROR_BODY_LIMIT,
+        );
+
+        let ForgeError::Provider(error) = err else {
+            panic!("expected provider error, got {err:?}");
+        };
+        assert_eq!(error.message, "API key not valid.");
+        assert_eq!(error.code.as_deref(), Some("INVALID_ARGUMENT"));
+        assert_eq!(error.http_status, 400);
+    }
+
+    #[test]
+    fn id_less_tool_results_match_calls_in_order() {
+        let mut request = sample_request();
+        let mut assistant = Message::new(Role::Assistant, "");
+        assistant.tool_calls = ["weather", "time"]
+            .into_iter()
+            .map(|name| ToolCall {
+                id: String::new(),
+                name: name.to_string(),
+                arguments: json!({}),
+            })
+            .collect();
+        request.messages.push(assistant);
+        for output in ["sunny", "12:00"] {
+            let mut tool = Message::new(Role::Tool, output);
+            tool.tool_call_id = Some(String::new());
+            request.messages.push(tool);
+        }
+
+        let body = build_generate_body(request, "\n\n");
+        let names: Vec<_> = body["contents"].as_array().unwrap()[2..]
+            .iter()
+            .map(|content| content["parts"][0]["functionResponse"]["name"].clone())
+            .collect();
+        assert_eq!(names, vec![json!("weather"), json!("time")]);
+    }
+
+    #[test]
+    fn tool_choice_function_is_sent() {
+        let mut request = sample_request();
+        assert!(build_generate_body(request.clone(), "\n\n")
+            .get("toolConfig")
+            .is_none());
+        request.tools = vec![ToolDefinition {
+            name: "weather".to_string(),
+            description: None,
+            input_schema: json!({"type": "object"}),
+            raw: None,
+        }];
+        request.tool_choice = Some(ToolChoice::Function("weather".to_string()));
+        let body = build_generate_body(request, "\n\n");
+        assert_eq!(
+            body["toolConfig"],
+            json!({"functionCallingConfig": {"mode": "ANY", "allowedFunctionNames": ["weather"]}})
+        );
+    }
+
+    #[test]
+    fn sampling_parameters_serialize_into_generation_config() {
+        let mut request = sample_request();
+        let body = build_generate_body(request.clone(), "\n\n");
+        assert!(body["generationConfig"].get("topP").is_none());
+
+        request.top_p = Some(0.5);
+        request.frequency_penalty = Some(0.25);
+        request.presence_penalty = Some(0.75);
+        let body = build_generate_body(request, "\n\n");
+        assert_eq!(body["generationConfig"]["topP"], json!(0.5));
+        assert_eq!(body["generationConfig"]["frequencyPenalty"], json!(0.25));
+        assert_eq!(body["generationConfig"]["presencePenalty"], json!(0.75));
+    }
+
+    #[test]
+    fn system_field_precedes_system_messages() {
+        let mut request = sample_request();
+        request.system = Some("Be brief.".to_string());
+        request
+            .messages
+            .insert(0, Message::new(Role::System, "Answer in French."));
+
+        let body = build_generate_body(request, "\n\n");
+
+        assert_eq!(
+            body["systemInstruction"]["parts"][0]["text"],
+            "Be brief.\n\nAnswer in French."
+        );
+        assert_eq!(body["contents"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn json_schema_response_format_sets_response_schema() {
+        let mut request = sample_request();
+        let body = build_generate_body(request.clone(), "\n\n");
+        assert!(body["generationConfig"].get("responseMimeType").is_none());
+
+        let schema = json!({"type": "object", "properties": {"city": {"type": "string"}}});
+        request.response_format = Some(ResponseFormat::JsonSchema {
+            name: "place".to_string(),
+            schema: schema.clone(),
+            strict: true,
+        });
+        let body = build_generate_body(request, "\n\n");
+
+        assert_eq!(
+            body["generationConfig"]["responseMimeType"],
+            "application/json"
+        );
+        assert_eq!(body["generationConfig"]["responseSchema"], schema);
+    }
+
+    #[test]
+    fn stop_sequences_serialize_into_generation_config() {
+        let mut request = sample_request();
+        let body = build_generate_body(request.clone(), "\n\n");
+        assert!(body["generationConfig"].get("stopSequences").is_none());
+
+        request.stop = vec!["END".to_string(), "###".to_string()];
+        let body = build_generate_body(request, "\n\n");
+        assert_eq!(
+            body["generationConfig"]["stopSequences"],
+            json!(["END", "###"])
+        );
+    }
+
+    #[test]
+    fn image_parts_serialize_as_inline_and_file_data() {
+        let mut request = sample_request();
+        request.messages = vec![Message::new(
+            Role::User,
+            vec![
+                ContentPart::Text {
+                    text: "Compare these".to_string(),
+                },
+                ContentPart::Image {
+                    url_or_base64: "https://example.com/cat.png".to_string(),
+                    mime_type: "image/png".to_string(),
+                },
+                ContentPart::Image {
+                    url_or_base64: "aGVsbG8=".to_string(),
+                    mime_type: "image/jpeg".to_string(),
+                },
+            ],
+        )];
+
+        let body = build_generate_body(request, "\n\n");
+        let parts = &body["contents"][0]["parts"];
+
+        assert_eq!(parts[0], json!({"text": "Compare these"}));
+        assert_eq!(
+            parts[1]["fileData"],
+            json!({"mimeType": "image/png", "fileUri": "https://example.com/cat.png"})
+        );
+        assert_eq!(
+            parts[2]["inlineData"],
+            json!({"mimeType": "image/jpeg", "data": "aGVsbG8="})
+        );
+    }
+
+    #[test]
+    fn system_messages_join_with_configured_separator() {
+        let mut request = sample_request();
+        request
+            .messages
+            .insert(0, Message::new(Role::System, "Be terse."));
+        request
+            .messages
+            .insert(1, Message::new(Role::System, "Answer in French."));
+
+        let body = build_generate_body(request, "\n---\n");
+
+        assert_eq!(
+            body["systemInstruction"]["parts"][0]["text"],
+            "Be terse.\n---\nAnswer in French."
+        );
+    }
+
+    #[tokio::test]
+    async fn chat_times_out_with_transport_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1beta/models/gemini-1.5-flash:generateContent"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"candidates": []}))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let adapter = GeminiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+            .unwrap()
+            .with_timeout(Duration::from_millis(50))
+            .unwrap();
+
+        let err = adapter.chat(sample_request()).await.unwrap_err();
+        assert!(matches!(err, ForgeError::Transport(ref m) if m == "request timed out after 50ms"));
+    }
+
+    #[tokio::test]
+    async fn chat_retries_rate_limits_with_backoff() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1beta/models/gemini-1.5-flash:generateContent"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1beta/models/gemini-1.5-flash:generateContent"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"candidates": []})))
+            .mount(&server)
+            .await;
+
+        let adapter = GeminiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+            .unwrap()
+            .with_retry(RetryConfig {
+                base_delay: Duration::from_millis(1),
+                ..RetryConfig::default()
+            });
+
+        adapter.chat(sample_request()).await.unwrap();
+        assert_eq!(adapter.request_attempts(), 3);
+    }
 }
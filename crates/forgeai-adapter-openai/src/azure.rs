@@ -0,0 +1,176 @@
+//! Adapter for Azure OpenAI deployments.
+
+use crate::{AuthHeader, OpenAiAdapter};
+use async_trait::async_trait;
+use forgeai_core::http::{build_http_client, env_proxy, env_timeout};
+use forgeai_core::{
+    parse_base_url, AdapterInfo, CapabilityMatrix, ChatAdapter, ChatRequest, ChatResponse,
+    ForgeError, HttpConfig, RetryConfig, StreamEvent, StreamResult, TraceContentOptions,
+};
+use reqwest::Client as HttpClient;
+use std::env;
+use std::time::Duration;
+use url::Url;
+
+/// `ChatAdapter` for an Azure OpenAI deployment. Requests go to
+/// `{endpoint}/openai/deployments/{deployment}/chat/completions?api-version={api_version}`
+/// with `api-key` authentication; bodies and responses use the
+/// chat-completions schema, exactly as `OpenAiAdapter` does.
+///
+/// Azure selects the model by deployment, so `ChatRequest::model` is only
+/// echoed in the body.
+#[derive(Clone, Debug)]
+pub struct AzureOpenAiAdapter {
+    inner: OpenAiAdapter,
+    deployment: String,
+}
+
+impl AzureOpenAiAdapter {
+    /// `endpoint` is the resource URL, e.g. `https://my-resource.openai.azure.com`.
+    pub fn new(
+        endpoint: Url,
+        deployment: impl AsRef<str>,
+        api_version: impl Into<String>,
+        api_key: impl Into<String>,
+    ) -> Result<Self, ForgeError> {
+        let mut adapter = Self::with_http_client(
+            endpoint,
+            deployment,
+            api_version,
+            api_key,
+            build_http_client(None, None, None)?,
+        )?;
+        adapter.inner.shared_client = false;
+        Ok(adapter)
+    }
+
+    /// Like `new`, but sends every request with `client`; see
+    /// `OpenAiAdapter::with_http_client`.
+    pub fn with_http_client(
+        endpoint: Url,
+        deployment: impl AsRef<str>,
+        api_version: impl Into<String>,
+        api_key: impl Into<String>,
+        client: HttpClient,
+    ) -> Result<Self, ForgeError> {
+        let deployment = deployment.as_ref().to_string();
+        let mut inner = OpenAiAdapter::with_http_client(
+            api_key,
+            deployment_url(endpoint, &deployment)?,
+            client,
+        );
+        inner.name = "azure-openai".to_string();
+        inner.auth = AuthHeader::ApiKey;
+        inner.chat_path = "chat/completions";
+        inner.api_version = Some(api_version.into());
+        Ok(Self { inner, deployment })
+    }
+
+    /// Points the adapter at another resource `endpoint`, keeping the
+    /// deployment. See `OpenAiAdapter::with_base_url_str`.
+    pub fn with_base_url_str(mut self, endpoint: impl AsRef<str>) -> Result<Self, ForgeError> {
+        self.inner.base_url = deployment_url(parse_base_url(endpoint.as_ref())?, &self.deployment)?;
+        Ok(self)
+    }
+
+    /// Reads `AZURE_OPENAI_ENDPOINT`, `AZURE_OPENAI_API_KEY`,
+    /// `AZURE_OPENAI_API_VERSION` and the optional `FORGEAI_TIMEOUT_MS` and
+    /// `HTTPS_PROXY`/`ALL_PROXY`.
+    pub fn from_env(deployment: impl AsRef<str>) -> Result<Self, ForgeError> {
+        let api_key = env::var("AZURE_OPENAI_API_KEY").map_err(|_| ForgeError::Authentication)?;
+        let endpoint = env::var("AZURE_OPENAI_ENDPOINT")
+            .map_err(|_| ForgeError::Validation("AZURE_OPENAI_ENDPOINT is not set".to_string()))?;
+        let endpoint = Url::parse(&endpoint)
+            .map_err(|e| ForgeError::Validation(format!("invalid AZURE_OPENAI_ENDPOINT: {e}")))?;
+        let api_version = env::var("AZURE_OPENAI_API_VERSION").map_err(|_| {
+            ForgeError::Validation("AZURE_OPENAI_API_VERSION is not set".to_string())
+        })?;
+        let mut adapter = Self::new(endpoint, deployment, api_version, api_key)?;
+        if let Some(proxy) = env_proxy() {
+            adapter = adapter.with_proxy(&proxy)?;
+        }
+        match env_timeout()? {
+            Some(timeout) => adapter.with_timeout(timeout),
+            None => Ok(adapter),
+        }
+    }
+
+    /// See `OpenAiAdapter::with_timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self, ForgeError> {
+        self.inner = self.inner.with_timeout(timeout)?;
+        Ok(self)
+    }
+
+    /// See `OpenAiAdapter::with_http_config`.
+    pub fn with_http_config(mut self, config: HttpConfig) -> Result<Self, ForgeError> {
+        self.inner = self.inner.with_http_config(config)?;
+        Ok(self)
+    }
+
+    /// See `OpenAiAdapter::with_proxy`.
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, ForgeError> {
+        self.inner = self.inner.with_proxy(url)?;
+        Ok(self)
+    }
+
+    /// See `OpenAiAdapter::with_retry`.
+    pub fn with_retry(mut self, config: RetryConfig) -> Self {
+        self.inner = self.inner.with_retry(config);
+        self
+    }
+
+    /// See `OpenAiAdapter::with_trace_content`.
+    pub fn with_trace_content(mut self, options: TraceContentOptions) -> Self {
+        self.inner = self.inner.with_trace_content(options);
+        self
+    }
+
+    /// See `OpenAiAdapter::with_error_body_limit`.
+    pub fn with_error_body_limit(mut self, max_chars: usize) -> Self {
+        self.inner = self.inner.with_error_body_limit(max_chars);
+        self
+    }
+
+    /// See `OpenAiAdapter::with_capabilities`.
+    pub fn with_capabilities(mut self, capabilities: CapabilityMatrix) -> Self {
+        self.inner = self.inner.with_capabilities(capabilities);
+        self
+    }
+
+    /// See `OpenAiAdapter::build_request_body`.
+    pub fn build_request_body(&self, request: &ChatRequest) -> serde_json::Value {
+        self.inner.build_request_body(request)
+    }
+
+    /// Total HTTP attempts made by this adapter, including retries.
+    pub fn request_attempts(&self) -> usize {
+        self.inner.request_attempts()
+    }
+}
+
+fn deployment_url(mut endpoint: Url, deployment: &str) -> Result<Url, ForgeError> {
+    endpoint
+        .path_segments_mut()
+        .map_err(|_| ForgeError::Validation("invalid Azure OpenAI endpoint".to_string()))?
+        .pop_if_empty()
+        .extend(["openai", "deployments", deployment, ""]);
+    Ok(endpoint)
+}
+
+#[async_trait]
+impl ChatAdapter for AzureOpenAiAdapter {
+    fn info(&self) -> AdapterInfo {
+        self.inner.info()
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        self.inner.chat(request).await
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+        self.inner.chat_stream(request).await
+    }
+}
@@ -0,0 +1,158 @@
+//! Adapter for third-party providers that speak OpenAI's chat-completions schema.
+
+use crate::OpenAiAdapter;
+use async_trait::async_trait;
+use forgeai_core::http::build_http_client;
+use forgeai_core::{
+    parse_base_url, AdapterInfo, CapabilityMatrix, ChatAdapter, ChatRequest, ChatResponse,
+    ForgeError, HttpConfig, RetryConfig, StreamEvent, StreamResult, TraceContentOptions,
+};
+use reqwest::Client as HttpClient;
+use std::time::Duration;
+use url::Url;
+
+/// Header used to send the API key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthHeader {
+    /// `Authorization: Bearer <key>`
+    Bearer,
+    /// `x-api-key: <key>`
+    XApiKey,
+    /// `api-key: <key>`, as used by Azure OpenAI.
+    ApiKey,
+}
+
+/// `ChatAdapter` for OpenAI-compatible `chat/completions` endpoints such as
+/// DeepSeek, Together, Groq or Ollama. Request bodies and responses use the
+/// chat-completions schema, exactly as `OpenAiAdapter` does.
+///
+/// `base_url` includes any version prefix (`https://api.groq.com/openai/v1`);
+/// requests are sent to `{base_url}/chat/completions`.
+#[derive(Clone, Debug)]
+pub struct CompatAdapter {
+    inner: OpenAiAdapter,
+}
+
+impl CompatAdapter {
+    /// `name` is reported in `AdapterInfo`, e.g. for router provider hints.
+    pub fn new(
+        name: impl Into<String>,
+        base_url: Url,
+        api_key: impl Into<String>,
+    ) -> Result<Self, ForgeError> {
+        let mut adapter = Self::with_http_client(
+            name,
+            base_url,
+            api_key,
+            build_http_client(None, None, None)?,
+        );
+        adapter.inner.shared_client = false;
+        Ok(adapter)
+    }
+
+    /// Like `new`, but sends every request with `client`; see
+    /// `OpenAiAdapter::with_http_client`.
+    pub fn with_http_client(
+        name: impl Into<String>,
+        base_url: Url,
+        api_key: impl Into<String>,
+        client: HttpClient,
+    ) -> Self {
+        let mut inner =
+            OpenAiAdapter::with_http_client(api_key, with_trailing_slash(base_url), client);
+        inner.name = name.into();
+        inner.chat_path = "chat/completions";
+        Self { inner }
+    }
+
+    /// See `OpenAiAdapter::with_base_url_str`.
+    pub fn with_base_url_str(mut self, base_url: impl AsRef<str>) -> Result<Self, ForgeError> {
+        self.inner.base_url = with_trailing_slash(parse_base_url(base_url.as_ref())?);
+        Ok(self)
+    }
+
+    /// Defaults to `AuthHeader::Bearer`.
+    pub fn with_auth_header(mut self, auth: AuthHeader) -> Self {
+        self.inner.auth = auth;
+        self
+    }
+
+    /// See `OpenAiAdapter::with_timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self, ForgeError> {
+        self.inner = self.inner.with_timeout(timeout)?;
+        Ok(self)
+    }
+
+    /// See `OpenAiAdapter::with_http_config`.
+    pub fn with_http_config(mut self, config: HttpConfig) -> Result<Self, ForgeError> {
+        self.inner = self.inner.with_http_config(config)?;
+        Ok(self)
+    }
+
+    /// See `OpenAiAdapter::with_proxy`.
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, ForgeError> {
+        self.inner = self.inner.with_proxy(url)?;
+        Ok(self)
+    }
+
+    /// See `OpenAiAdapter::with_retry`.
+    pub fn with_retry(mut self, config: RetryConfig) -> Self {
+        self.inner = self.inner.with_retry(config);
+        self
+    }
+
+    /// See `OpenAiAdapter::with_trace_content`.
+    pub fn with_trace_content(mut self, options: TraceContentOptions) -> Self {
+        self.inner = self.inner.with_trace_content(options);
+        self
+    }
+
+    /// See `OpenAiAdapter::with_error_body_limit`.
+    pub fn with_error_body_limit(mut self, max_chars: usize) -> Self {
+        self.inner = self.inner.with_error_body_limit(max_chars);
+        self
+    }
+
+    /// See `OpenAiAdapter::with_capabilities`.
+    pub fn with_capabilities(mut self, capabilities: CapabilityMatrix) -> Self {
+        self.inner = self.inner.with_capabilities(capabilities);
+        self
+    }
+
+    /// See `OpenAiAdapter::build_request_body`.
+    pub fn build_request_body(&self, request: &ChatRequest) -> serde_json::Value {
+        self.inner.build_request_body(request)
+    }
+
+    /// Total HTTP attempts made by this adapter, including retries.
+    pub fn request_attempts(&self) -> usize {
+        self.inner.request_attempts()
+    }
+}
+
+/// Keeps the last path segment when `chat/completions` is joined on.
+fn with_trailing_slash(mut base_url: Url) -> Url {
+    if !base_url.path().ends_with('/') {
+        let path = format!("{}/", base_url.path());
+        base_url.set_path(&path);
+    }
+    base_url
+}
+
+#[async_trait]
+impl ChatAdapter for CompatAdapter {
+    fn info(&self) -> AdapterInfo {
+        self.inner.info()
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        self.inner.chat(request).await
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+        self.inner.chat_stream(request).await
+    }
+}
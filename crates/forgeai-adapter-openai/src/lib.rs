@@ -1,20 +1,55 @@
+mod azure;
+mod compat;
+
+pub use azure::AzureOpenAiAdapter;
+pub use compat::{AuthHeader, CompatAdapter};
+
 use async_stream::try_stream;
 use async_trait::async_trait;
+use forgeai_core::http::{build_http_client, env_proxy, env_timeout, extra_header_map, timed_out};
+use forgeai_core::openai::message_to_openai;
 use forgeai_core::{
-    AdapterInfo, CapabilityMatrix, ChatAdapter, ChatRequest, ChatResponse, ForgeError, Role,
-    StreamEvent, StreamResult, ToolCall, Usage,
+    http, parse_base_url, telemetry, truncate_chars, AdapterInfo, AudioOutput, CapabilityMatrix,
+    ChatAdapter, ChatRequest, ChatResponse, Choice, FinishReason, ForgeError, HttpConfig,
+    ProviderError, ResponseFormat, ResponseTiming, RetryConfig, StreamEvent, StreamResult,
+    ToolCall, ToolChoice, TraceContentOptions, Usage, DEFAULT_ERROR_BODY_LIMIT,
 };
 use futures_util::StreamExt;
-use reqwest::{Client as HttpClient, StatusCode};
+use reqwest::header::HeaderMap;
+use reqwest::{Client as HttpClient, NoProxy, Proxy, Response, StatusCode};
 use serde_json::{json, Map, Value};
 use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use url::Url;
 
 #[derive(Clone, Debug)]
 pub struct OpenAiAdapter {
     pub api_key: String,
     pub base_url: Url,
+    /// Sent as `OpenAI-Organization` when set.
+    pub organization: Option<String>,
+    /// Sent as `OpenAI-Project` when set.
+    pub project: Option<String>,
+    /// Attach `ResponseTiming` to responses returned by `chat`.
+    pub collect_timing: bool,
+    trace_content: TraceContentOptions,
+    timeout: Option<Duration>,
+    retry: Option<RetryConfig>,
+    http_config: Option<HttpConfig>,
+    proxy: Option<Proxy>,
+    error_body_limit: usize,
+    capabilities: CapabilityMatrix,
+    attempts: Arc<AtomicUsize>,
+    name: String,
+    auth: AuthHeader,
+    chat_path: &'static str,
+    /// Sent as the `api-version` query parameter when set (Azure).
+    api_version: Option<String>,
     client: HttpClient,
+    /// Set by `with_http_client`; the client is never rebuilt.
+    shared_client: bool,
 }
 
 impl OpenAiAdapter {
@@ -25,32 +60,225 @@ impl OpenAiAdapter {
     }
 
     pub fn with_base_url(api_key: impl Into<String>, base_url: Url) -> Result<Self, ForgeError> {
-        let client = HttpClient::builder()
-            .build()
-            .map_err(|e| ForgeError::Internal(format!("failed to build http client: {e}")))?;
-        Ok(Self {
+        let mut adapter =
+            Self::with_http_client(api_key, base_url, build_http_client(None, None, None)?);
+        adapter.shared_client = false;
+        Ok(adapter)
+    }
+
+    /// Uses `client` for every request instead of building one, so adapters
+    /// can share a connection pool and TLS/proxy settings. The client is
+    /// cloned, which shares its pool. `with_timeout` then only bounds each
+    /// request and leaves the client's connect timeout alone, and
+    /// `with_http_config` is rejected; configure the client instead.
+    pub fn with_http_client(api_key: impl Into<String>, base_url: Url, client: HttpClient) -> Self {
+        Self {
             api_key: api_key.into(),
             base_url,
+            organization: None,
+            project: None,
+            collect_timing: false,
+            trace_content: TraceContentOptions::default(),
+            timeout: None,
+            retry: None,
+            http_config: None,
+            proxy: None,
+            error_body_limit: DEFAULT_ERROR_BODY_LIMIT,
+            capabilities: CapabilityMatrix {
+                streaming: true,
+                tools: true,
+                structured_output: true,
+                multimodal_input: true,
+                citations: false,
+            },
+            attempts: Arc::new(AtomicUsize::new(0)),
+            name: "openai".to_string(),
+            auth: AuthHeader::Bearer,
+            chat_path: "v1/chat/completions",
+            api_version: None,
             client,
-        })
+            shared_client: true,
+        }
+    }
+
+    /// Points an already configured adapter at `base_url`, e.g. one read from
+    /// a config file. Fails with `ForgeError::Validation` unless it is a valid
+    /// `http` or `https` URL.
+    pub fn with_base_url_str(mut self, base_url: impl AsRef<str>) -> Result<Self, ForgeError> {
+        self.base_url = parse_base_url(base_url.as_ref())?;
+        Ok(self)
+    }
+
+    pub fn with_organization(mut self, organization: impl Into<String>) -> Self {
+        self.organization = Some(organization.into());
+        self
+    }
+
+    pub fn with_project(mut self, project: impl Into<String>) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    /// Bounds `chat` end to end and the connect/response-header phase of
+    /// `chat_stream`; an established stream body is not cut off.
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self, ForgeError> {
+        if !self.shared_client {
+            self.client = build_http_client(
+                Some(timeout),
+                self.http_config.as_ref(),
+                self.proxy.as_ref(),
+            )?;
+        }
+        self.timeout = Some(timeout);
+        Ok(self)
+    }
+
+    /// Tunes connection pooling and keep-alive of the HTTP client; see
+    /// `HttpConfig` for the defaults it suggests.
+    pub fn with_http_config(mut self, config: HttpConfig) -> Result<Self, ForgeError> {
+        if self.shared_client {
+            return Err(ForgeError::Validation(
+                "with_http_config cannot tune a client passed to with_http_client".to_string(),
+            ));
+        }
+        self.client = build_http_client(self.timeout, Some(&config), self.proxy.as_ref())?;
+        self.http_config = Some(config);
+        Ok(self)
+    }
+
+    /// Sends every request through the proxy at `url`, e.g.
+    /// `http://proxy.corp:3128`; hosts listed in `NO_PROXY` bypass it. Fails
+    /// with `ForgeError::Validation` when `url` is not a valid proxy URL.
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, ForgeError> {
+        if self.shared_client {
+            return Err(ForgeError::Validation(
+                "with_proxy cannot reconfigure a client passed to with_http_client".to_string(),
+            ));
+        }
+        let proxy = Proxy::all(url)
+            .map_err(|e| ForgeError::Validation(format!("invalid proxy URL {url:?}: {e}")))?
+            .no_proxy(NoProxy::from_env());
+        self.client = build_http_client(self.timeout, self.http_config.as_ref(), Some(&proxy))?;
+        self.proxy = Some(proxy);
+        Ok(self)
+    }
+
+    /// Retries rate-limited and transport failures of `chat` and of the initial
+    /// `chat_stream` request with exponential backoff, honoring `Retry-After`
+    /// up to `config.max_delay`; longer provider delays are cut to it. An
+    /// established stream is never retried.
+    pub fn with_retry(mut self, config: RetryConfig) -> Self {
+        self.retry = Some(config);
+        self
+    }
+
+    /// Sets what the `tracing` feature records about prompts and completions.
+    pub fn with_trace_content(mut self, options: TraceContentOptions) -> Self {
+        self.trace_content = options;
+        self
+    }
+
+    /// Caps how many characters of a non-JSON error body (e.g. an HTML error
+    /// page) are kept as the error message; defaults to
+    /// `DEFAULT_ERROR_BODY_LIMIT`.
+    pub fn with_error_body_limit(mut self, max_chars: usize) -> Self {
+        self.error_body_limit = max_chars;
+        self
+    }
+
+    /// Replaces the `CapabilityMatrix` reported by `info()`, e.g. for a proxy
+    /// that does not support tools.
+    pub fn with_capabilities(mut self, capabilities: CapabilityMatrix) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// The JSON body `chat` would send for `request`, for debugging and
+    /// comparing providers without a server. It holds no credentials: auth
+    /// and `extra_headers` travel as headers, and the endpoint URL is not
+    /// part of it.
+    pub fn build_request_body(&self, request: &ChatRequest) -> Value {
+        build_chat_body(request.clone(), false)
+    }
+
+    /// Total HTTP attempts made by this adapter, including retries.
+    pub fn request_attempts(&self) -> usize {
+        self.attempts.load(Ordering::Relaxed)
     }
 
     pub fn from_env() -> Result<Self, ForgeError> {
         let api_key = env::var("OPENAI_API_KEY").map_err(|_| ForgeError::Authentication)?;
-        match env::var("OPENAI_BASE_URL") {
+        let mut adapter = match env::var("OPENAI_BASE_URL") {
             Ok(raw) => {
                 let base_url = Url::parse(&raw)
                     .map_err(|e| ForgeError::Validation(format!("invalid OPENAI_BASE_URL: {e}")))?;
-                Self::with_base_url(api_key, base_url)
+                Self::with_base_url(api_key, base_url)?
             }
-            Err(_) => Self::new(api_key),
+            Err(_) => Self::new(api_key)?,
+        };
+        adapter.organization = env::var("OPENAI_ORG_ID").ok();
+        adapter.project = env::var("OPENAI_PROJECT_ID").ok();
+        if let Some(proxy) = env_proxy() {
+            adapter = adapter.with_proxy(&proxy)?;
+        }
+        match env_timeout()? {
+            Some(timeout) => adapter.with_timeout(timeout),
+            None => Ok(adapter),
         }
     }
 
     fn chat_completions_url(&self) -> Result<Url, ForgeError> {
-        self.base_url
-            .join("v1/chat/completions")
-            .map_err(|e| ForgeError::Internal(format!("failed to construct endpoint url: {e}")))
+        let mut url = self
+            .base_url
+            .join(self.chat_path)
+            .map_err(|e| ForgeError::Internal(format!("failed to construct endpoint url: {e}")))?;
+        if let Some(version) = &self.api_version {
+            url.query_pairs_mut().append_pair("api-version", version);
+        }
+        Ok(url)
+    }
+
+    async fn send(
+        &self,
+        body: &Value,
+        headers: &HeaderMap,
+        stream: bool,
+    ) -> Result<Response, ForgeError> {
+        telemetry::request_body(body);
+        http::send_with_retry(self.retry, &self.attempts, || {
+            self.send_once(body, headers, stream)
+        })
+        .await
+    }
+
+    async fn send_once(
+        &self,
+        body: &Value,
+        headers: &HeaderMap,
+        stream: bool,
+    ) -> Result<Response, ForgeError> {
+        let mut request = self.client.post(self.chat_completions_url()?).json(body);
+        request = match self.auth {
+            AuthHeader::Bearer => request.bearer_auth(&self.api_key),
+            AuthHeader::XApiKey => request.header("x-api-key", &self.api_key),
+            AuthHeader::ApiKey => request.header("api-key", &self.api_key),
+        };
+        if let Some(organization) = &self.organization {
+            request = request.header("OpenAI-Organization", organization);
+        }
+        if let Some(project) = &self.project {
+            request = request.header("OpenAI-Project", project);
+        }
+        request = request.headers(headers.clone());
+        http::send_request(
+            request,
+            self.timeout,
+            stream,
+            |status, retry_after, body| {
+                parse_http_error(status, retry_after, body, self.error_body_limit)
+            },
+        )
+        .await
     }
 }
 
@@ -58,122 +286,137 @@ impl OpenAiAdapter {
 impl ChatAdapter for OpenAiAdapter {
     fn info(&self) -> AdapterInfo {
         AdapterInfo {
-            name: "openai".to_string(),
+            name: self.name.clone(),
             base_url: Some(self.base_url.clone()),
-            capabilities: CapabilityMatrix {
-                streaming: true,
-                tools: true,
-                structured_output: true,
-                multimodal_input: true,
-                citations: false,
-            },
+            capabilities: self.capabilities.clone(),
         }
     }
 
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
-        let response = self
-            .client
-            .post(self.chat_completions_url()?)
-            .bearer_auth(&self.api_key)
-            .json(&build_chat_body(request, false))
-            .send()
-            .await
-            .map_err(|e| ForgeError::Transport(format!("request failed: {e}")))?;
+        let model = request.model.clone();
+        telemetry::instrument("chat", &self.name, &model, async move {
+            telemetry::prompt(&self.trace_content, &request);
+            check_stop_sequences(&request)?;
+            let headers = extra_header_map(&request.extra_headers)?;
+            let body = build_chat_body(request, false);
+            let started = Instant::now();
+            let response = self.send(&body, &headers, false).await?;
+            let first_byte = started.elapsed();
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
+            let payload = response
+                .json::<Value>()
                 .await
-                .unwrap_or_else(|_| "failed to read error body".to_string());
-            return Err(parse_http_error(status, text));
-        }
-
-        let payload = response
-            .json::<Value>()
-            .await
-            .map_err(|e| ForgeError::Provider(format!("invalid json response: {e}")))?;
-        parse_chat_response(payload)
+                .map_err(|e| match self.timeout {
+                    Some(timeout) if e.is_timeout() => timed_out(timeout),
+                    _ => ForgeError::Provider(format!("invalid json response: {e}").into()),
+                })?;
+            let mut response = parse_chat_response(payload)?;
+            telemetry::completion(&self.trace_content, &response.output_text);
+            if let Some(usage) = &response.usage {
+                telemetry::usage(usage);
+            }
+            if self.collect_timing {
+                response.timing = Some(ResponseTiming {
+                    total: started.elapsed(),
+                    first_byte: Some(first_byte),
+                });
+            }
+            Ok(response)
+        })
+        .await
     }
 
     async fn chat_stream(
         &self,
         request: ChatRequest,
     ) -> Result<StreamResult<StreamEvent>, ForgeError> {
-        let response = self
-            .client
-            .post(self.chat_completions_url()?)
-            .bearer_auth(&self.api_key)
-            .json(&build_chat_body(request, true))
-            .send()
-            .await
-            .map_err(|e| ForgeError::Transport(format!("stream request failed: {e}")))?;
+        let model = request.model.clone();
+        telemetry::instrument("chat_stream", &self.name, &model, async move {
+            telemetry::prompt(&self.trace_content, &request);
+            // Deltas carry a choice index that `StreamEvent` has no room for.
+            if request.n.is_some_and(|n| n > 1) {
+                return Err(ForgeError::Validation(
+                    "n > 1 is not supported when streaming".to_string(),
+                ));
+            }
+            check_stop_sequences(&request)?;
+            let headers = extra_header_map(&request.extra_headers)?;
+            let body = build_chat_body(request, true);
+            let response = self.send(&body, &headers, true).await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "failed to read error body".to_string());
-            return Err(parse_http_error(status, text));
-        }
+            let mut bytes = response.bytes_stream();
+            let stream = try_stream! {
+                let mut buffer = String::new();
+                let mut saw_done = false;
+                let mut started = false;
 
-        let mut bytes = response.bytes_stream();
-        let stream = try_stream! {
-            let mut buffer = String::new();
-            let mut saw_done = false;
-
-            while let Some(chunk) = bytes.next().await {
-                let chunk = chunk.map_err(|e| ForgeError::Transport(format!("stream chunk error: {e}")))?;
-                let chunk_text = std::str::from_utf8(&chunk)
-                    .map_err(|e| ForgeError::Transport(format!("invalid utf8 stream chunk: {e}")))?;
-                buffer.push_str(chunk_text);
-
-                while let Some(line_end) = buffer.find('\n') {
-                    let mut line = buffer[..line_end].to_string();
-                    buffer.drain(..=line_end);
-                    if line.ends_with('\r') {
-                        line.pop();
-                    }
-                    if line.trim().is_empty() {
-                        continue;
+                while let Some(chunk) = bytes.next().await {
+                    let chunk = chunk.map_err(|e| ForgeError::Transport(format!("stream chunk error: {e}")))?;
+                    let chunk_text = std::str::from_utf8(&chunk)
+                        .map_err(|e| ForgeError::Transport(format!("invalid utf8 stream chunk: {e}")))?;
+                    buffer.push_str(chunk_text);
+
+                    while let Some(line_end) = buffer.find('\n') {
+                        let mut line = buffer[..line_end].to_string();
+                        buffer.drain(..=line_end);
+                        if line.ends_with('\r') {
+                            line.pop();
+                        }
+                        if line.trim().is_empty() {
+                            continue;
+                        }
+                        if let Some(data) = line.strip_prefix("data:") {
+                            let payload = data.trim();
+                            if payload == "[DONE]" {
+                                saw_done = true;
+                                yield StreamEvent::Done;
+                                continue;
+                            }
+                            for event in parse_stream_payload(payload, &mut started)? {
+                                yield event;
+                            }
+                        }
                     }
+                }
+
+                if !buffer.trim().is_empty() {
+                    let line = buffer.trim();
                     if let Some(data) = line.strip_prefix("data:") {
                         let payload = data.trim();
                         if payload == "[DONE]" {
                             saw_done = true;
                             yield StreamEvent::Done;
-                            continue;
-                        }
-                        for event in parse_stream_payload(payload)? {
-                            yield event;
+                        } else {
+                            for event in parse_stream_payload(payload, &mut started)? {
+                                yield event;
+                            }
                         }
                     }
                 }
-            }
 
-            if !buffer.trim().is_empty() {
-                let line = buffer.trim();
-                if let Some(data) = line.strip_prefix("data:") {
-                    let payload = data.trim();
-                    if payload == "[DONE]" {
-                        saw_done = true;
-                        yield StreamEvent::Done;
-                    } else {
-                        for event in parse_stream_payload(payload)? {
-                            yield event;
-                        }
-                    }
+                if !saw_done {
+                    yield StreamEvent::Done;
                 }
-            }
+            };
 
-            if !saw_done {
-                yield StreamEvent::Done;
-            }
-        };
+            Ok(Box::pin(stream) as StreamResult<StreamEvent>)
+        })
+        .await
+    }
+}
+
+/// Stop sequences the chat-completions API accepts per request.
+const MAX_STOP_SEQUENCES: usize = 4;
 
-        Ok(Box::pin(stream))
+/// Rejects more stop sequences than OpenAI accepts, before it answers 400.
+fn check_stop_sequences(request: &ChatRequest) -> Result<(), ForgeError> {
+    if request.stop.len() > MAX_STOP_SEQUENCES {
+        return Err(ForgeError::Validation(format!(
+            "openai accepts at most {MAX_STOP_SEQUENCES} stop sequences, got {}",
+            request.stop.len()
+        )));
     }
+    Ok(())
 }
 
 fn build_chat_body(request: ChatRequest, stream: bool) -> Value {
@@ -183,23 +426,37 @@ fn build_chat_body(request: ChatRequest, stream: bool) -> Value {
         "messages".to_string(),
         Value::Array(
             request
-                .messages
+                .system
+                .map(|system| json!({"role": "system", "content": system}))
                 .into_iter()
-                .map(|m| {
-                    json!({
-                        "role": role_to_openai(&m.role),
-                        "content": m.content
-                    })
-                })
+                .chain(request.messages.iter().map(message_to_openai))
                 .collect(),
         ),
     );
     if let Some(temperature) = request.temperature {
         body.insert("temperature".to_string(), json!(temperature));
     }
+    if let Some(top_p) = request.top_p {
+        body.insert("top_p".to_string(), json!(top_p));
+    }
+    if let Some(penalty) = request.frequency_penalty {
+        body.insert("frequency_penalty".to_string(), json!(penalty));
+    }
+    if let Some(penalty) = request.presence_penalty {
+        body.insert("presence_penalty".to_string(), json!(penalty));
+    }
     if let Some(max_tokens) = request.max_tokens {
         body.insert("max_tokens".to_string(), json!(max_tokens));
     }
+    if let Some(n) = request.n {
+        body.insert("n".to_string(), json!(n));
+    }
+    if let Some(seed) = request.seed {
+        body.insert("seed".to_string(), json!(seed));
+    }
+    if !request.stop.is_empty() {
+        body.insert("stop".to_string(), json!(request.stop));
+    }
     if !request.tools.is_empty() {
         body.insert(
             "tools".to_string(),
@@ -207,20 +464,42 @@ fn build_chat_body(request: ChatRequest, stream: bool) -> Value {
                 request
                     .tools
                     .into_iter()
-                    .map(|tool| {
-                        json!({
+                    .map(|tool| match tool.raw {
+                        Some(raw) => raw,
+                        None => json!({
                             "type": "function",
                             "function": {
                                 "name": tool.name,
                                 "description": tool.description,
                                 "parameters": tool.input_schema,
                             }
-                        })
+                        }),
                     })
                     .collect(),
             ),
         );
     }
+    if let Some(choice) = request.tool_choice {
+        let choice = match choice {
+            ToolChoice::Auto => json!("auto"),
+            ToolChoice::None => json!("none"),
+            ToolChoice::Required => json!("required"),
+            ToolChoice::Function(name) => json!({"type": "function", "function": {"name": name}}),
+        };
+        body.insert("tool_choice".to_string(), choice);
+    }
+    if let Some(format) = request.response_format {
+        body.insert(
+            "response_format".to_string(),
+            response_format_to_openai(format),
+        );
+    }
+    if !request.modalities.is_empty() {
+        body.insert("modalities".to_string(), json!(request.modalities));
+    }
+    if let Some(audio) = request.audio {
+        body.insert("audio".to_string(), json!(audio));
+    }
     if stream {
         body.insert("stream".to_string(), Value::Bool(true));
         body.insert("stream_options".to_string(), json!({"include_usage": true}));
@@ -228,34 +507,54 @@ fn build_chat_body(request: ChatRequest, stream: bool) -> Value {
     Value::Object(body)
 }
 
-fn role_to_openai(role: &Role) -> &'static str {
-    match role {
-        Role::System => "system",
-        Role::User => "user",
-        Role::Assistant => "assistant",
-        Role::Tool => "tool",
+fn response_format_to_openai(format: ResponseFormat) -> Value {
+    match format {
+        ResponseFormat::Text => json!({"type": "text"}),
+        ResponseFormat::JsonObject => json!({"type": "json_object"}),
+        ResponseFormat::JsonSchema {
+            name,
+            schema,
+            strict,
+        } => json!({
+            "type": "json_schema",
+            "json_schema": {"name": name, "schema": schema, "strict": strict}
+        }),
     }
 }
 
-fn parse_http_error(status: StatusCode, body: String) -> ForgeError {
-    let message = extract_provider_error(body);
+fn parse_http_error(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    body: String,
+    body_limit: usize,
+) -> ForgeError {
+    let error = extract_provider_error(status, body, body_limit);
     match status {
         StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ForgeError::Authentication,
-        StatusCode::TOO_MANY_REQUESTS => ForgeError::RateLimited,
-        _ => ForgeError::Provider(message),
+        StatusCode::TOO_MANY_REQUESTS => ForgeError::RateLimited { retry_after },
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            ForgeError::Transport(format!("{status}: {}", error.message))
+        }
+        _ => ForgeError::Provider(error),
     }
 }
 
-fn extract_provider_error(body: String) -> String {
-    serde_json::from_str::<Value>(&body)
-        .ok()
-        .and_then(|v| {
-            v.get("error")
-                .and_then(|e| e.get("message"))
-                .and_then(Value::as_str)
-                .map(ToString::to_string)
-        })
-        .unwrap_or(body)
+/// Reads `{"error": {"message", "code", "type"}}`, falling back to the raw body.
+fn extract_provider_error(status: StatusCode, body: String, body_limit: usize) -> ProviderError {
+    let parsed = serde_json::from_str::<Value>(&body).ok();
+    let error = parsed.as_ref().and_then(|v| v.get("error"));
+    let field = |name: &str| {
+        error
+            .and_then(|e| e.get(name))
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    };
+    ProviderError {
+        message: field("message").unwrap_or_else(|| truncate_chars(body, body_limit)),
+        code: field("code"),
+        kind: field("type"),
+        http_status: status.as_u16(),
+    }
 }
 
 fn parse_chat_response(payload: Value) -> Result<ChatResponse, ForgeError> {
@@ -270,27 +569,70 @@ fn parse_chat_response(payload: Value) -> Result<ChatResponse, ForgeError> {
         .unwrap_or_default()
         .to_string();
 
-    let choice = payload
+    let raw_choices = payload
         .get("choices")
         .and_then(Value::as_array)
-        .and_then(|choices| choices.first());
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let mut choices: Vec<Choice> = raw_choices.iter().map(parse_choice).collect();
 
-    let message = choice
+    let message = raw_choices
+        .first()
         .and_then(|c| c.get("message"))
         .unwrap_or(&Value::Null);
-    let output_text = extract_text_content(message.get("content"));
-    let tool_calls = extract_tool_calls(message.get("tool_calls"));
+    let reasoning = reasoning_text(message).map(str::to_string);
     let usage = extract_usage(payload.get("usage"));
+    let first = if choices.len() == 1 {
+        choices.remove(0)
+    } else {
+        choices.first().cloned().unwrap_or_default()
+    };
 
     Ok(ChatResponse {
         id,
         model,
-        output_text,
-        tool_calls,
+        output_text: first.output_text,
+        reasoning,
+        tool_calls: first.tool_calls,
         usage,
+        finish_reason: first.finish_reason,
+        timing: None,
+        citations: Vec::new(),
+        system_fingerprint: payload
+            .get("system_fingerprint")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        audio: message.get("audio").and_then(parse_audio),
+        choices,
+    })
+}
+
+fn parse_audio(audio: &Value) -> Option<AudioOutput> {
+    let text = |key: &str| {
+        audio
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    Some(AudioOutput {
+        id: audio.get("id")?.as_str()?.to_string(),
+        data_base64: text("data"),
+        transcript: text("transcript"),
+        expires_at: audio.get("expires_at").and_then(Value::as_u64),
     })
 }
 
+fn parse_finish_reason(raw: &str) -> FinishReason {
+    match raw {
+        "stop" => FinishReason::Stop,
+        "length" => FinishReason::Length,
+        "tool_calls" | "function_call" => FinishReason::ToolCalls,
+        "content_filter" => FinishReason::ContentFilter,
+        other => FinishReason::Other(other.to_string()),
+    }
+}
+
 fn extract_text_content(content: Option<&Value>) -> String {
     match content {
         Some(Value::String(text)) => text.clone(),
@@ -340,21 +682,68 @@ fn extract_tool_calls(raw: Option<&Value>) -> Vec<ToolCall> {
 
 fn extract_usage(raw: Option<&Value>) -> Option<Usage> {
     let usage = raw?;
-    let input_tokens = usage.get("prompt_tokens")?.as_u64()? as u32;
-    let output_tokens = usage.get("completion_tokens")?.as_u64()? as u32;
-    let total_tokens = usage.get("total_tokens")?.as_u64()? as u32;
+    let input_tokens = usage.get("prompt_tokens")?.as_u64()?;
+    let output_tokens = usage.get("completion_tokens")?.as_u64()?;
+    // Some compatible backends omit the total; derive it rather than drop the counts.
+    let total_tokens = usage
+        .get("total_tokens")
+        .and_then(Value::as_u64)
+        .unwrap_or_else(|| input_tokens.saturating_add(output_tokens));
+    let detail = |section: &str, key: &str| usage.get(section)?.get(key)?.as_u64();
     Some(Usage {
         input_tokens,
         output_tokens,
         total_tokens,
+        cache_creation_input_tokens: None,
+        cached_input_tokens: detail("prompt_tokens_details", "cached_tokens"),
+        reasoning_tokens: detail("completion_tokens_details", "reasoning_tokens"),
     })
 }
 
-fn parse_stream_payload(payload: &str) -> Result<Vec<StreamEvent>, ForgeError> {
+/// Converts one entry of a response's `choices` array.
+fn parse_choice(choice: &Value) -> Choice {
+    let message = choice.get("message").unwrap_or(&Value::Null);
+    Choice {
+        output_text: extract_text_content(message.get("content")),
+        tool_calls: extract_tool_calls(message.get("tool_calls")),
+        finish_reason: choice
+            .get("finish_reason")
+            .and_then(Value::as_str)
+            .map(parse_finish_reason),
+    }
+}
+
+/// Reasoning text of a message or delta. OpenAI-compatible reasoning
+/// backends use `reasoning_content` (DeepSeek, vLLM) or `reasoning`
+/// (OpenRouter).
+fn reasoning_text(message: &Value) -> Option<&str> {
+    message
+        .get("reasoning_content")
+        .or_else(|| message.get("reasoning"))
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+}
+
+/// Parses one chunk payload. `started` records whether `ResponseStart` was
+/// already emitted, since every chunk repeats the response id.
+fn parse_stream_payload(payload: &str, started: &mut bool) -> Result<Vec<StreamEvent>, ForgeError> {
     let value = serde_json::from_str::<Value>(payload)
-        .map_err(|e| ForgeError::Provider(format!("invalid stream payload: {e}")))?;
+        .map_err(|e| ForgeError::Provider(format!("invalid stream payload: {e}").into()))?;
 
     let mut events = Vec::new();
+    if !*started {
+        if let Some(id) = value.get("id").and_then(Value::as_str) {
+            *started = true;
+            events.push(StreamEvent::ResponseStart {
+                id: id.to_string(),
+                model: value
+                    .get("model")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            });
+        }
+    }
     if let Some(usage) = extract_usage(value.get("usage")) {
         events.push(StreamEvent::Usage { usage });
     }
@@ -371,6 +760,11 @@ fn parse_stream_payload(payload: &str) -> Result<Vec<StreamEvent>, ForgeError> {
                     delta: content.to_string(),
                 });
             }
+            if let Some(reasoning) = choice.get("delta").and_then(reasoning_text) {
+                events.push(StreamEvent::ReasoningDelta {
+                    delta: reasoning.to_string(),
+                });
+            }
 
             if let Some(tool_calls) = choice
                 .get("delta")
@@ -389,6 +783,12 @@ fn parse_stream_payload(payload: &str) -> Result<Vec<StreamEvent>, ForgeError> {
                     });
                 }
             }
+
+            if let Some(reason) = choice.get("finish_reason").and_then(Value::as_str) {
+                events.push(StreamEvent::FinishReason {
+                    reason: parse_finish_reason(reason),
+                });
+            }
         }
     }
 
@@ -398,21 +798,31 @@ fn parse_stream_payload(payload: &str) -> Result<Vec<StreamEvent>, ForgeError> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use forgeai_core::{ChatRequest, Message, Role};
+    use forgeai_core::{AudioConfig, ContentPart, Message, Modality, Role, ToolDefinition};
     use futures_util::StreamExt;
-    use wiremock::matchers::{body_partial_json, header, method, path};
+    use std::collections::HashMap;
+    use wiremock::matchers::{body_partial_json, header, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn sample_request() -> ChatRequest {
         ChatRequest {
             model: "gpt-4o-mini".to_string(),
-            messages: vec![Message {
-                role: Role::User,
-                content: "Say hello".to_string(),
-            }],
+            messages: vec![Message::new(Role::User, "Say hello")],
+            system: None,
             temperature: Some(0.2),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             max_tokens: Some(32),
+            n: None,
+            seed: None,
+            stop: vec![],
             tools: vec![],
+            tool_choice: None,
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::new(),
             metadata: json!({}),
         }
     }
@@ -429,7 +839,8 @@ mod tests {
                 "model": "gpt-4o-mini",
                 "choices": [{
                     "index": 0,
-                    "message": {"role": "assistant", "content": "Hello from OpenAI"}
+                    "message": {"role": "assistant", "content": "Hello from OpenAI"},
+                    "finish_reason": "length"
                 }],
                 "usage": {"prompt_tokens": 10, "completion_tokens": 4, "total_tokens": 14}
             })))
@@ -444,6 +855,255 @@ mod tests {
         assert_eq!(response.model, "gpt-4o-mini");
         assert_eq!(response.output_text, "Hello from OpenAI");
         assert_eq!(response.usage.unwrap().total_tokens, 14);
+        assert_eq!(response.finish_reason, Some(FinishReason::Length));
+    }
+
+    #[tokio::test]
+    async fn http_config_builds_client() {
+        let configs = [
+            HttpConfig::default(),
+            HttpConfig {
+                pool_max_idle_per_host: 0,
+                pool_idle_timeout: None,
+                tcp_keepalive: None,
+                http2_prior_knowledge: true,
+            },
+        ];
+        for config in configs {
+            OpenAiAdapter::new("test-key")
+                .unwrap()
+                .with_timeout(Duration::from_secs(5))
+                .unwrap()
+                .with_http_config(config)
+                .unwrap();
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "chatcmpl-1",
+                "choices": [{"message": {"role": "assistant", "content": "Hi"}}]
+            })))
+            .mount(&server)
+            .await;
+        let adapter = OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+            .unwrap()
+            .with_http_config(HttpConfig::default())
+            .unwrap();
+        for _ in 0..2 {
+            let response = adapter.chat(sample_request()).await.unwrap();
+            assert_eq!(response.output_text, "Hi");
+        }
+    }
+
+    #[test]
+    fn with_proxy_rejects_invalid_url() {
+        let adapter = OpenAiAdapter::new("test-key")
+            .unwrap()
+            .with_proxy("http://proxy.corp:3128")
+            .unwrap();
+        assert!(adapter.proxy.is_some());
+
+        let err = OpenAiAdapter::new("test-key")
+            .unwrap()
+            .with_proxy("http://bad host:3128")
+            .unwrap_err();
+        assert!(matches!(err, ForgeError::Validation(ref m) if m.starts_with("invalid proxy URL")));
+    }
+
+    #[tokio::test]
+    async fn adapters_share_one_http_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "chatcmpl-1",
+                "choices": [{"message": {"role": "assistant", "content": "Hi"}}]
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = HttpClient::new();
+        let base_url = Url::parse(&server.uri()).unwrap();
+        let first = OpenAiAdapter::with_http_client("key-1", base_url.clone(), client.clone());
+        let second = OpenAiAdapter::with_http_client("key-2", base_url, client)
+            .with_timeout(Duration::from_secs(5))
+            .unwrap();
+        for adapter in [first, second] {
+            let response = adapter.chat(sample_request()).await.unwrap();
+            assert_eq!(response.output_text, "Hi");
+        }
+
+        let err = OpenAiAdapter::with_http_client(
+            "key",
+            Url::parse(&server.uri()).unwrap(),
+            HttpClient::new(),
+        )
+        .with_http_config(HttpConfig::default())
+        .unwrap_err();
+        assert!(matches!(err, ForgeError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn base_url_can_be_set_by_builder() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "chatcmpl-1",
+                "choices": [{"message": {"role": "assistant", "content": "Hi"}}]
+            })))
+            .mount(&server)
+            .await;
+
+        let adapter = OpenAiAdapter::new("test-key")
+            .unwrap()
+            .with_organization("org-1")
+            .with_base_url_str(server.uri())
+            .unwrap();
+        assert_eq!(adapter.organization.as_deref(), Some("org-1"));
+        let response = adapter.chat(sample_request()).await.unwrap();
+        assert_eq!(response.output_text, "Hi");
+
+        for invalid in ["not a url", "ftp://example.com"] {
+            let err = OpenAiAdapter::new("test-key")
+                .unwrap()
+                .with_base_url_str(invalid)
+                .unwrap_err();
+            assert!(
+                matches!(err, ForgeError::Validation(ref m) if m.starts_with("invalid base URL"))
+            );
+        }
+    }
+
+    /// In-memory subscriber capturing `(span name, provider)` for each span and
+    /// `(message, content)` for each event.
+    #[cfg(feature = "tracing")]
+    #[derive(Clone, Default)]
+    struct TraceRecorder {
+        spans: Arc<std::sync::Mutex<Vec<(String, String)>>>,
+        events: Arc<std::sync::Mutex<Vec<(String, String)>>>,
+    }
+
+    #[cfg(feature = "tracing")]
+    #[derive(Default)]
+    struct Fields {
+        provider: String,
+        message: String,
+        content: String,
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::field::Visit for Fields {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            if field.name() == "provider" {
+                self.provider = value.to_string();
+            }
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            match field.name() {
+                "message" => self.message = format!("{value:?}"),
+                "content" => self.content = format!("{value:?}"),
+                _ => {}
+            }
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for TraceRecorder {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut fields = Fields::default();
+            span.record(&mut fields);
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((span.metadata().name().to_string(), fields.provider));
+            tracing::span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.events
+                .lock()
+                .unwrap()
+                .push((fields.message, fields.content));
+        }
+
+        fn enter(&self, _span: &tracing::span::Id) {}
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    async fn mount_hello(server: &MockServer) {
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "chatcmpl-123",
+                "model": "gpt-4o-mini",
+                "choices": [{"message": {"role": "assistant", "content": "Hello there, friend"}}]
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn chat_records_adapter_span_with_provider() {
+        let server = MockServer::start().await;
+        mount_hello(&server).await;
+
+        let recorder = TraceRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+        let adapter =
+            OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap()).unwrap();
+        adapter.chat(sample_request()).await.unwrap();
+
+        let spans = recorder.spans.lock().unwrap();
+        assert!(spans
+            .iter()
+            .any(|(name, provider)| name == "forgeai.adapter" && provider == "openai"));
+        // Content is only recorded when asked for.
+        let events = recorder.events.lock().unwrap();
+        assert!(!events
+            .iter()
+            .any(|(message, _)| message == "prompt" || message == "completion"));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn chat_records_redacted_truncated_content_when_enabled() {
+        let server = MockServer::start().await;
+        mount_hello(&server).await;
+
+        let recorder = TraceRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+        let adapter = OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+            .unwrap()
+            .with_trace_content(TraceContentOptions {
+                record_content: true,
+                max_chars: 11,
+                redact: Some(|text| text.replace("hello", "[redacted]")),
+            });
+        adapter.chat(sample_request()).await.unwrap();
+
+        let events = recorder.events.lock().unwrap();
+        let content = |name: &str| {
+            events
+                .iter()
+                .find(|(message, _)| message == name)
+                .map(|(_, content)| content.clone())
+        };
+        assert_eq!(content("prompt").as_deref(), Some("user: Say […"));
+        assert_eq!(content("completion").as_deref(), Some("Hello there…"));
     }
 
     #[tokio::test]
@@ -452,6 +1112,7 @@ mod tests {
         let sse_body = concat!(
             "data: {\"id\":\"chatcmpl-1\",\"model\":\"gpt-4o-mini\",\"choices\":[{\"delta\":{\"content\":\"Hello\"},\"index\":0}]}\n\n",
             "data: {\"id\":\"chatcmpl-1\",\"model\":\"gpt-4o-mini\",\"choices\":[{\"delta\":{\"content\":\" world\"},\"index\":0}]}\n\n",
+            "data: {\"id\":\"chatcmpl-1\",\"model\":\"gpt-4o-mini\",\"choices\":[{\"delta\":{},\"index\":0,\"finish_reason\":\"stop\"}]}\n\n",
             "data: {\"usage\":{\"prompt_tokens\":10,\"completion_tokens\":2,\"total_tokens\":12},\"choices\":[]}\n\n",
             "data: [DONE]\n\n"
         );
@@ -477,6 +1138,17 @@ mod tests {
             }
         }
 
+        assert!(matches!(
+            &events[0],
+            StreamEvent::ResponseStart { id, model } if id == "chatcmpl-1" && model == "gpt-4o-mini"
+        ));
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, StreamEvent::ResponseStart { .. }))
+                .count(),
+            1
+        );
         assert!(events
             .iter()
             .any(|e| matches!(e, StreamEvent::TextDelta { delta } if delta == "Hello")));
@@ -487,6 +1159,726 @@ mod tests {
             e,
             StreamEvent::Usage { usage } if usage.total_tokens == 12
         )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            StreamEvent::FinishReason {
+                reason: FinishReason::Stop
+            }
+        )));
         assert!(events.iter().any(|e| matches!(e, StreamEvent::Done)));
     }
+
+    #[test]
+    fn pure_tool_call_turn_serializes_null_content_and_tool_calls() {
+        let mut request = sample_request();
+        let mut assistant = Message::new(Role::Assistant, "");
+        assistant.tool_calls = vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "time.now".to_string(),
+            arguments: json!({"timezone": "UTC"}),
+        }];
+        let mut tool = Message::new(Role::Tool, "{\"time\":\"12:00\"}");
+        tool.tool_call_id = Some("call_1".to_string());
+        request.messages.extend([assistant, tool]);
+
+        let body = build_chat_body(request, false);
+        let messages = body["messages"].as_array().unwrap();
+
+        assert_eq!(messages[1]["role"], "assistant");
+        assert!(messages[1]["content"].is_null());
+        assert_eq!(messages[1]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(
+            messages[1]["tool_calls"][0]["function"]["arguments"],
+            "{\"timezone\":\"UTC\"}"
+        );
+        assert_eq!(messages[2]["role"], "tool");
+        assert_eq!(messages[2]["tool_call_id"], "call_1");
+    }
+
+    #[tokio::test]
+    async fn chat_with_n_returns_every_choice() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({"n": 2})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "chatcmpl-2",
+                "model": "gpt-4o-mini",
+                "choices": [
+                    {"index": 0, "message": {"role": "assistant", "content": "Hi!"}, "finish_reason": "stop"},
+                    {"index": 1, "message": {"role": "assistant", "content": "Hello there, how can"}, "finish_reason": "length"}
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let adapter =
+            OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap()).unwrap();
+        let mut request = sample_request();
+        request.n = Some(2);
+        let response = adapter.chat(request.clone()).await.unwrap();
+
+        assert_eq!(response.output_text, "Hi!");
+        assert_eq!(response.finish_reason, Some(FinishReason::Stop));
+        assert_eq!(response.choices.len(), 2);
+        assert_eq!(response.choices[1].output_text, "Hello there, how can");
+        assert_eq!(
+            response.choices[1].finish_reason,
+            Some(FinishReason::Length)
+        );
+
+        let err = adapter.chat_stream(request).await.err().unwrap();
+        assert!(matches!(err, ForgeError::Validation(_)));
+    }
+
+    #[test]
+    fn single_choice_leaves_choices_empty() {
+        let response = parse_chat_response(json!({
+            "id": "chatcmpl-1",
+            "choices": [{"message": {"content": "Hi"}, "finish_reason": "stop"}]
+        }))
+        .unwrap();
+        assert_eq!(response.output_text, "Hi");
+        assert!(response.choices.is_empty());
+    }
+
+    #[test]
+    fn build_request_body_returns_provider_json() {
+        let mut request = sample_request();
+        request.temperature = None;
+        request.tools = vec![ToolDefinition {
+            name: "weather".to_string(),
+            description: Some("Current weather for a city".to_string()),
+            input_schema: json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+            raw: None,
+        }];
+        request
+            .extra_headers
+            .insert("x-gateway-key".to_string(), "secret".to_string());
+        let adapter = OpenAiAdapter::new("test-key").unwrap();
+
+        assert_eq!(
+            adapter.build_request_body(&request),
+            json!({
+                "model": "gpt-4o-mini",
+                "messages": [{"role": "user", "content": "Say hello"}],
+                "max_tokens": 32,
+                "tools": [{
+                    "type": "function",
+                    "function": {
+                        "name": "weather",
+                        "description": "Current weather for a city",
+                        "parameters": {"type": "object", "properties": {"city": {"type": "string"}}}
+                    }
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn tool_choice_function_is_sent() {
+        let mut request = sample_request();
+        assert!(build_chat_body(request.clone(), false)
+            .get("tool_choice")
+            .is_none());
+        request.tools = vec![ToolDefinition {
+            name: "weather".to_string(),
+            description: None,
+            input_schema: json!({"type": "object"}),
+            raw: None,
+        }];
+        request.tool_choice = Some(ToolChoice::Function("weather".to_string()));
+        let body = build_chat_body(request, false);
+        assert_eq!(
+            body["tool_choice"],
+            json!({"type": "function", "function": {"name": "weather"}})
+        );
+    }
+
+    #[test]
+    fn modalities_and_audio_config_are_sent() {
+        let mut request = sample_request();
+        let body = build_chat_body(request.clone(), false);
+        assert!(body.get("modalities").is_none() && body.get("audio").is_none());
+
+        request.modalities = vec![Modality::Text, Modality::Audio];
+        request.audio = Some(AudioConfig {
+            voice: "alloy".to_string(),
+            format: "wav".to_string(),
+        });
+        let body = build_chat_body(request, false);
+        assert_eq!(body["modalities"], json!(["text", "audio"]));
+        assert_eq!(body["audio"], json!({"voice": "alloy", "format": "wav"}));
+    }
+
+    #[test]
+    fn parses_audio_output() {
+        let response = parse_chat_response(json!({
+            "id": "chatcmpl-audio",
+            "model": "gpt-4o-audio-preview",
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "audio": {
+                        "id": "audio_abc123",
+                        "data": "UklGRiQAAABXQVZF",
+                        "expires_at": 1729018505,
+                        "transcript": "Hello there!"
+                    }
+                },
+                "finish_reason": "stop"
+            }]
+        }))
+        .unwrap();
+        assert_eq!(response.output_text, "");
+        assert_eq!(
+            response.audio,
+            Some(AudioOutput {
+                id: "audio_abc123".to_string(),
+                data_base64: "UklGRiQAAABXQVZF".to_string(),
+                transcript: "Hello there!".to_string(),
+                expires_at: Some(1729018505),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn non_json_error_body_is_truncated() {
+        let server = MockServer::start().await;
+        let page = format!("<html><body>{}</body></html>", "x".repeat(10 * 1024));
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(500).set_body_raw(page, "text/html"))
+            .mount(&server)
+            .await;
+
+        let adapter =
+            OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap()).unwrap();
+        let Err(ForgeError::Provider(error)) = adapter.chat(sample_request()).await else {
+            panic!("expected a provider error");
+        };
+        assert_eq!(error.http_status, 500);
+        assert_eq!(error.message.chars().count(), DEFAULT_ERROR_BODY_LIMIT + 1);
+        assert!(error.message.starts_with("<html><body>xxx"));
+        assert!(error.message.ends_with('…'));
+
+        let Err(ForgeError::Provider(error)) = adapter
+            .with_error_body_limit(16)
+            .chat(sample_request())
+            .await
+        else {
+            panic!("expected a provider error");
+        };
+        assert_eq!(error.message, "<html><body>xxxx…");
+    }
+
+    #[test]
+    fn usage_without_total_tokens_sums_input_and_output() {
+        let usage = extract_usage(Some(&json!({"prompt_tokens": 7, "completion_tokens": 5})));
+        assert_eq!(
+            usage.map(|u| (u.input_tokens, u.output_tokens, u.total_tokens)),
+            Some((7, 5, 12))
+        );
+    }
+
+    #[test]
+    fn usage_keeps_counts_above_u32_max() {
+        let large = u64::from(u32::MAX) + 10;
+        let usage = extract_usage(Some(&json!({
+            "prompt_tokens": large,
+            "completion_tokens": 5,
+            "completion_tokens_details": {"reasoning_tokens": large}
+        })))
+        .unwrap();
+        assert_eq!(usage.input_tokens, large);
+        assert_eq!(usage.total_tokens, large + 5);
+        assert_eq!(usage.reasoning_tokens, Some(large));
+    }
+
+    #[test]
+    fn usage_reads_cached_and_reasoning_token_details() {
+        let usage = extract_usage(Some(&json!({
+            "prompt_tokens": 1200,
+            "completion_tokens": 300,
+            "total_tokens": 1500,
+            "prompt_tokens_details": {"cached_tokens": 1024},
+            "completion_tokens_details": {"reasoning_tokens": 256}
+        })))
+        .unwrap();
+        assert_eq!(usage.cached_input_tokens, Some(1024));
+        assert_eq!(usage.reasoning_tokens, Some(256));
+
+        let usage = extract_usage(Some(&json!({"prompt_tokens": 7, "completion_tokens": 5})));
+        assert!(
+            usage.is_some_and(|u| u.cached_input_tokens.is_none() && u.reasoning_tokens.is_none())
+        );
+    }
+
+    #[test]
+    fn provider_error_keeps_code_and_type() {
+        let err = parse_http_error(
+            StatusCode::BAD_REQUEST,
+            None,
+            json!({"error": {
+                "message": "This model's maximum context length is 128000 tokens.",
+                "type": "invalid_request_error",
+                "code": "context_length_exceeded"
+            }})
+            .to_string(),
+            DEFAULT_ERROR_BODY_LIMIT,
+        );
+
+        let ForgeError::Provider(error) = err else {
+            panic!("expected provider error, got {err:?}");
+        };
+        assert_eq!(error.code.as_deref(), Some("context_length_exceeded"));
+        assert_eq!(error.kind.as_deref(), Some("invalid_request_error"));
+        assert_eq!(error.http_status, 400);
+        assert_eq!(
+            error.to_string(),
+            "This model's maximum context length is 128000 tokens. (status 400, code context_length_exceeded, type invalid_request_error)"
+        );
+    }
+
+    #[test]
+    fn sampling_parameters_serialize_only_when_present() {
+        let mut request = sample_request();
+        let body = build_chat_body(request.clone(), false);
+        assert!(body.get("top_p").is_none());
+        assert!(body.get("frequency_penalty").is_none());
+        assert!(body.get("presence_penalty").is_none());
+
+        request.top_p = Some(0.5);
+        request.frequency_penalty = Some(0.25);
+        request.presence_penalty = Some(-0.5);
+        let body = build_chat_body(request, false);
+        assert_eq!(body["top_p"], json!(0.5));
+        assert_eq!(body["frequency_penalty"], json!(0.25));
+        assert_eq!(body["presence_penalty"], json!(-0.5));
+    }
+
+    #[test]
+    fn seed_is_sent_and_fingerprint_parsed() {
+        let mut request = sample_request();
+        assert!(build_chat_body(request.clone(), false)
+            .get("seed")
+            .is_none());
+        request.seed = Some(42);
+        assert_eq!(build_chat_body(request, false)["seed"], json!(42));
+
+        let response = parse_chat_response(json!({
+            "id": "chatcmpl-1",
+            "system_fingerprint": "fp_44709d6fcb",
+            "choices": [{"message": {"content": "Hi"}, "finish_reason": "stop"}]
+        }))
+        .unwrap();
+        assert_eq!(
+            response.system_fingerprint.as_deref(),
+            Some("fp_44709d6fcb")
+        );
+    }
+
+    #[test]
+    fn system_field_is_sent_before_system_messages() {
+        let mut request = sample_request();
+        request.system = Some("Be brief.".to_string());
+        request
+            .messages
+            .insert(0, Message::new(Role::System, "Answer in French."));
+
+        let body = build_chat_body(request, false);
+        let messages = body["messages"].as_array().unwrap();
+
+        assert_eq!(
+            messages[0],
+            json!({"role": "system", "content": "Be brief."})
+        );
+        assert_eq!(messages[1]["role"], "system");
+        assert_eq!(messages[1]["content"], "Answer in French.");
+        assert_eq!(messages[2]["role"], "user");
+    }
+
+    #[test]
+    fn stop_sequences_serialize_only_when_present() {
+        let mut request = sample_request();
+        let body = build_chat_body(request.clone(), false);
+        assert!(body.get("stop").is_none());
+
+        request.stop = vec!["END".to_string()];
+        let body = build_chat_body(request, false);
+        assert_eq!(body["stop"], json!(["END"]));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_fifth_stop_sequence_before_sending() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"choices": []})))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let adapter =
+            OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap()).unwrap();
+        let mut request = sample_request();
+        request.stop = (1..=5).map(|i| format!("END{i}")).collect();
+
+        let err = adapter.chat(request.clone()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ForgeError::Validation(ref m) if m == "openai accepts at most 4 stop sequences, got 5"
+        ));
+        let err = adapter.chat_stream(request).await.err().unwrap();
+        assert!(matches!(err, ForgeError::Validation(ref m) if m.contains("at most 4")));
+    }
+
+    #[test]
+    fn json_schema_response_format_serializes_nested_schema() {
+        let mut request = sample_request();
+        let body = build_chat_body(request.clone(), false);
+        assert!(body.get("response_format").is_none());
+
+        let schema = json!({"type": "object", "properties": {"city": {"type": "string"}}});
+        request.response_format = Some(ResponseFormat::JsonSchema {
+            name: "place".to_string(),
+            schema: schema.clone(),
+            strict: true,
+        });
+        let body = build_chat_body(request, false);
+
+        assert_eq!(
+            body["response_format"],
+            json!({
+                "type": "json_schema",
+                "json_schema": {"name": "place", "schema": schema, "strict": true}
+            })
+        );
+    }
+
+    #[test]
+    fn image_parts_serialize_as_image_url_content() {
+        let mut request = sample_request();
+        request.messages = vec![Message::new(
+            Role::User,
+            vec![
+                ContentPart::Text {
+                    text: "Compare these".to_string(),
+                },
+                ContentPart::Image {
+                    url_or_base64: "https://example.com/cat.png".to_string(),
+                    mime_type: "image/png".to_string(),
+                },
+                ContentPart::Image {
+                    url_or_base64: "aGVsbG8=".to_string(),
+                    mime_type: "image/jpeg".to_string(),
+                },
+            ],
+        )];
+
+        let body = build_chat_body(request, false);
+        let content = &body["messages"][0]["content"];
+
+        assert_eq!(content[0], json!({"type": "text", "text": "Compare these"}));
+        assert_eq!(
+            content[1]["image_url"]["url"],
+            "https://example.com/cat.png"
+        );
+        assert_eq!(
+            content[2]["image_url"]["url"],
+            "data:image/jpeg;base64,aGVsbG8="
+        );
+    }
+
+    #[tokio::test]
+    async fn chat_times_out_with_transport_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"choices": []}))
+                    .set_delay(Duration::from_millis(500)),
+            )
+            .mount(&server)
+            .await;
+
+        let adapter = OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+            .unwrap()
+            .with_timeout(Duration::from_millis(50))
+            .unwrap();
+
+        let err = adapter.chat(sample_request()).await.unwrap_err();
+        assert!(matches!(err, ForgeError::Transport(ref m) if m == "request timed out after 50ms"));
+        let err = adapter.chat_stream(sample_request()).await.err().unwrap();
+        assert!(matches!(err, ForgeError::Transport(ref m) if m.contains("timed out")));
+    }
+
+    #[tokio::test]
+    async fn chat_collects_timing_when_enabled() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({
+                        "id": "chatcmpl-1",
+                        "choices": [{"message": {"role": "assistant", "content": "hi"}}]
+                    }))
+                    .set_delay(Duration::from_millis(20)),
+            )
+            .mount(&server)
+            .await;
+
+        let mut adapter =
+            OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap()).unwrap();
+        let response = adapter.chat(sample_request()).await.unwrap();
+        assert!(response.timing.is_none());
+
+        adapter.collect_timing = true;
+        let timing = adapter
+            .chat(sample_request())
+            .await
+            .unwrap()
+            .timing
+            .unwrap();
+        assert!(timing.total > Duration::ZERO);
+        assert!(timing.first_byte.unwrap() <= timing.total);
+    }
+
+    #[tokio::test]
+    async fn chat_retries_rate_limits_with_backoff() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"choices": []})))
+            .mount(&server)
+            .await;
+
+        let adapter = OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+            .unwrap()
+            .with_retry(RetryConfig {
+                base_delay: Duration::from_millis(1),
+                ..RetryConfig::default()
+            });
+
+        adapter.chat(sample_request()).await.unwrap();
+        assert_eq!(adapter.request_attempts(), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_after_is_capped_at_max_delay() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "86400"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"choices": []})))
+            .mount(&server)
+            .await;
+
+        let adapter = OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap())
+            .unwrap()
+            .with_retry(RetryConfig {
+                max_delay: Duration::from_millis(10),
+                ..RetryConfig::default()
+            });
+
+        tokio::time::timeout(Duration::from_secs(5), adapter.chat(sample_request()))
+            .await
+            .expect("Retry-After should be capped at max_delay")
+            .unwrap();
+        assert_eq!(adapter.request_attempts(), 2);
+    }
+
+    #[tokio::test]
+    async fn sends_organization_and_project_headers_only_when_configured() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"choices": []})))
+            .mount(&server)
+            .await;
+
+        let adapter =
+            OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap()).unwrap();
+        adapter.chat(sample_request()).await.unwrap();
+        adapter
+            .clone()
+            .with_organization("org-123")
+            .with_project("proj-456")
+            .chat(sample_request())
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests[0].headers.get("openai-organization").is_none());
+        assert!(requests[0].headers.get("openai-project").is_none());
+        assert_eq!(
+            requests[1].headers.get("openai-organization").unwrap(),
+            "org-123"
+        );
+        assert_eq!(
+            requests[1].headers.get("openai-project").unwrap(),
+            "proj-456"
+        );
+    }
+
+    #[tokio::test]
+    async fn forwards_extra_headers_and_rejects_invalid_names() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("api-key", "gateway-key"))
+            .and(header("x-trace-id", "trace-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"choices": []})))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let adapter =
+            OpenAiAdapter::with_base_url("test-key", Url::parse(&server.uri()).unwrap()).unwrap();
+        let mut request = sample_request();
+        request.extra_headers = HashMap::from([
+            ("api-key".to_string(), "gateway-key".to_string()),
+            ("x-trace-id".to_string(), "trace-1".to_string()),
+        ]);
+        adapter.chat(request.clone()).await.unwrap();
+        let _stream = adapter.chat_stream(request.clone()).await.unwrap();
+
+        request
+            .extra_headers
+            .insert("bad header".to_string(), "x".to_string());
+        let err = adapter.chat(request).await.unwrap_err();
+        assert!(matches!(err, ForgeError::Validation(ref m) if m.contains("bad header")));
+    }
+
+    #[tokio::test]
+    async fn compat_adapter_targets_prefixed_chat_completions() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/openai/v1/chat/completions"))
+            .and(header("x-api-key", "groq-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "cmpl-1",
+                "choices": [{"message": {"role": "assistant", "content": "Hi from Groq"}}]
+            })))
+            .mount(&server)
+            .await;
+
+        let base_url = Url::parse(&format!("{}/openai/v1", server.uri())).unwrap();
+        let adapter = CompatAdapter::new("groq", base_url, "groq-key")
+            .unwrap()
+            .with_auth_header(AuthHeader::XApiKey);
+
+        assert_eq!(adapter.info().name, "groq");
+        let response = adapter.chat(sample_request()).await.unwrap();
+        assert_eq!(response.output_text, "Hi from Groq");
+    }
+
+    #[test]
+    fn with_capabilities_overrides_info() {
+        let no_tools = CapabilityMatrix {
+            streaming: true,
+            tools: false,
+            structured_output: false,
+            multimodal_input: false,
+            citations: false,
+        };
+        let adapter = OpenAiAdapter::new("test-key").unwrap();
+        assert!(adapter.info().capabilities.tools);
+        let adapter = adapter.with_capabilities(no_tools.clone());
+        let capabilities = adapter.info().capabilities;
+        assert!(capabilities.streaming && !capabilities.tools && !capabilities.structured_output);
+
+        let base_url = Url::parse("http://localhost:11434/v1").unwrap();
+        let compat = CompatAdapter::new("ollama", base_url, "")
+            .unwrap()
+            .with_capabilities(no_tools);
+        assert!(!compat.info().capabilities.tools);
+    }
+
+    #[tokio::test]
+    async fn azure_adapter_targets_deployment_with_api_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/openai/deployments/gpt-4o-prod/chat/completions"))
+            .and(query_param("api-version", "2024-06-01"))
+            .and(header("api-key", "azure-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "cmpl-1",
+                "choices": [{"message": {"role": "assistant", "content": "Hi from Azure"}}]
+            })))
+            .mount(&server)
+            .await;
+
+        let adapter = AzureOpenAiAdapter::new(
+            Url::parse(&server.uri()).unwrap(),
+            "gpt-4o-prod",
+            "2024-06-01",
+            "azure-key",
+        )
+        .unwrap();
+
+        assert_eq!(adapter.info().name, "azure-openai");
+        let response = adapter.chat(sample_request()).await.unwrap();
+        assert_eq!(response.output_text, "Hi from Azure");
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests[0].headers.get("authorization").is_none());
+    }
+
+    #[tokio::test]
+    async fn wrappers_accept_shared_client_and_base_url_str() {
+        let server = MockServer::start().await;
+        for route in [
+            "/openai/v1/chat/completions",
+            "/openai/deployments/gpt-4o-prod/chat/completions",
+        ] {
+            Mock::given(method("POST"))
+                .and(path(route))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "id": "cmpl-1",
+                    "choices": [{"message": {"role": "assistant", "content": "Hi"}}]
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let client = HttpClient::new();
+        let placeholder = Url::parse("https://example.invalid").unwrap();
+
+        let compat =
+            CompatAdapter::with_http_client("groq", placeholder.clone(), "key", client.clone())
+                .with_base_url_str(format!("{}/openai/v1", server.uri()))
+                .unwrap();
+        assert_eq!(
+            compat.chat(sample_request()).await.unwrap().output_text,
+            "Hi"
+        );
+
+        let azure = AzureOpenAiAdapter::with_http_client(
+            placeholder,
+            "gpt-4o-prod",
+            "2024-06-01",
+            "key",
+            client,
+        )
+        .unwrap()
+        .with_base_url_str(server.uri())
+        .unwrap();
+        assert_eq!(
+            azure.chat(sample_request()).await.unwrap().output_text,
+            "Hi"
+        );
+    }
 }
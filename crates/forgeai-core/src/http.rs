@@ -0,0 +1,181 @@
+//! HTTP plumbing shared by the provider adapters, enabled by the `http`
+//! feature: client construction, environment configuration, and sending
+//! requests with timeouts and retries.
+
+use crate::{telemetry, ForgeError, HttpConfig, RetryConfig};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use reqwest::{Client, Proxy, RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Builds the client an adapter sends with. `timeout` bounds connecting;
+/// whole requests are bounded per call by `send_request`.
+pub fn build_http_client(
+    timeout: Option<Duration>,
+    http_config: Option<&HttpConfig>,
+    proxy: Option<&Proxy>,
+) -> Result<Client, ForgeError> {
+    let mut builder = Client::builder();
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy.clone());
+    }
+    if let Some(timeout) = timeout {
+        builder = builder.connect_timeout(timeout);
+    }
+    if let Some(config) = http_config {
+        builder = builder
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .tcp_keepalive(config.tcp_keepalive);
+        if config.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+    }
+    builder
+        .build()
+        .map_err(|e| ForgeError::Internal(format!("failed to build http client: {e}")))
+}
+
+/// Proxy URL from `HTTPS_PROXY` or `ALL_PROXY` (either case), if set.
+pub fn env_proxy() -> Option<String> {
+    ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+}
+
+/// Request timeout from `FORGEAI_TIMEOUT_MS`, if set.
+pub fn env_timeout() -> Result<Option<Duration>, ForgeError> {
+    let Ok(raw) = env::var("FORGEAI_TIMEOUT_MS") else {
+        return Ok(None);
+    };
+    raw.trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|millis| *millis > 0)
+        .map(|millis| Some(Duration::from_millis(millis)))
+        .ok_or_else(|| {
+            ForgeError::Validation(format!(
+                "invalid FORGEAI_TIMEOUT_MS: expected a positive integer, got '{raw}'"
+            ))
+        })
+}
+
+pub fn timed_out(timeout: Duration) -> ForgeError {
+    ForgeError::Transport(format!("request timed out after {timeout:?}"))
+}
+
+pub fn transport_error(
+    context: &str,
+    error: reqwest::Error,
+    timeout: Option<Duration>,
+) -> ForgeError {
+    match timeout {
+        Some(timeout) if error.is_timeout() => timed_out(timeout),
+        _ => ForgeError::Transport(format!("{context}: {error}")),
+    }
+}
+
+/// Converts `ChatRequest::extra_headers`, rejecting names or values that are
+/// not valid in HTTP.
+pub fn extra_header_map(headers: &HashMap<String, String>) -> Result<HeaderMap, ForgeError> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| ForgeError::Validation(format!("invalid header name: {name:?}")))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| ForgeError::Validation(format!("invalid value for header {name}")))?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+/// Parses a delay-seconds `Retry-After` header; HTTP-date values are ignored.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Sends `request`, bounding it by `timeout`. For streams only the wait for
+/// the response head is bounded, so long streams are not cut off. Error
+/// statuses are turned into an error by `parse_error`, which receives the
+/// status, the `Retry-After` delay and the body.
+pub async fn send_request(
+    request: RequestBuilder,
+    timeout: Option<Duration>,
+    stream: bool,
+    parse_error: impl FnOnce(StatusCode, Option<Duration>, String) -> ForgeError,
+) -> Result<Response, ForgeError> {
+    let context = if stream {
+        "stream request failed"
+    } else {
+        "request failed"
+    };
+    let response = match timeout {
+        Some(timeout) if stream => tokio::time::timeout(timeout, request.send())
+            .await
+            .map_err(|_| timed_out(timeout))?,
+        Some(timeout) => request.timeout(timeout).send().await,
+        None => request.send().await,
+    }
+    .map_err(|e| transport_error(context, e, timeout))?;
+
+    telemetry::http_status(response.status().as_u16());
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let retry_after = retry_after(response.headers());
+        let text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "failed to read error body".to_string());
+        return Err(parse_error(status, retry_after, text));
+    }
+    Ok(response)
+}
+
+/// Runs `attempt` until it succeeds, fails with an error `RetryConfig`
+/// does not retry, or `retry` allows no more retries. A `Retry-After` delay
+/// from the provider takes precedence over the backoff but is capped at
+/// `max_delay`. Every attempt is counted in `attempts`.
+pub async fn send_with_retry<T, F, Fut>(
+    retry: Option<RetryConfig>,
+    attempts: &AtomicUsize,
+    mut attempt: F,
+) -> Result<T, ForgeError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ForgeError>>,
+{
+    let mut retries = 0;
+    loop {
+        attempts.fetch_add(1, Ordering::Relaxed);
+        match attempt().await {
+            Err(error)
+                if retry.is_some_and(|retry| retries < retry.max_retries)
+                    && RetryConfig::should_retry(&error) =>
+            {
+                let retry = retry.unwrap_or_default();
+                let delay = match error {
+                    ForgeError::RateLimited {
+                        retry_after: Some(retry_after),
+                    } => retry_after.min(retry.max_delay),
+                    _ => retry.backoff(retries),
+                };
+                telemetry::retry(retries + 1, delay);
+                tokio::time::sleep(delay).await;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
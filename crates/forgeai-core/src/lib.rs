@@ -1,12 +1,23 @@
 //! Core domain types and adapter traits for forgeai-rs.
 
 use async_trait::async_trait;
+use base64::prelude::{Engine as _, BASE64_STANDARD};
 use futures_core::Stream;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
 use std::pin::Pin;
+use std::time::Duration;
 use url::Url;
 
+#[cfg(feature = "http")]
+pub mod http;
+pub mod openai;
+pub mod telemetry;
+#[cfg(feature = "testing")]
+pub mod testing;
+
 pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
 pub type StreamResult<T> = Pin<Box<dyn Stream<Item = Result<T, ForgeError>> + Send>>;
 
@@ -14,16 +25,256 @@ pub type StreamResult<T> = Pin<Box<dyn Stream<Item = Result<T, ForgeError>> + Se
 pub struct ChatRequest {
     pub model: String,
     pub messages: Vec<Message>,
+    /// System prompt. Sent ahead of any `Role::System` messages, which are
+    /// still honored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
     pub temperature: Option<f32>,
+    /// Nucleus sampling cutoff, in `0.0..=1.0`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Ignored by providers without penalty support (Anthropic).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    /// Ignored by providers without penalty support (Anthropic).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
     pub max_tokens: Option<u32>,
+    /// Completions to generate; see `ChatResponse::choices`. Only OpenAI
+    /// supports more than one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    /// Requests deterministic sampling where the provider supports it
+    /// (OpenAI, Gemini); other adapters drop it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    /// Sequences that end generation when produced. Omitted from provider
+    /// requests when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
     pub tools: Vec<ToolDefinition>,
+    /// Whether and which tool the model must call. Omitted from provider
+    /// requests when `None`, leaving the provider default (usually `Auto`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    /// Requested output format. Omitted from provider requests when `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    /// Output modalities, e.g. `[Text, Audio]` for OpenAI audio models.
+    /// Empty leaves the provider default (text). Only OpenAI sends it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modalities: Vec<Modality>,
+    /// Spoken output settings; required when `modalities` includes
+    /// `Modality::Audio`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<AudioConfig>,
+    /// Additional HTTP headers sent with this request, e.g. gateway keys or
+    /// trace ids. Adapters reject invalid names or values with
+    /// `ForgeError::Validation`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra_headers: HashMap<String, String>,
     pub metadata: Value,
 }
 
+/// Tool use policy requested through `ChatRequest::tool_choice`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "name", rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides whether to call a tool.
+    Auto,
+    /// The model must not call tools.
+    None,
+    /// The model must call at least one tool.
+    Required,
+    /// The model must call the named tool.
+    Function(String),
+}
+
+/// Output modality requested through `ChatRequest::modalities`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Modality {
+    Text,
+    Audio,
+}
+
+/// Voice and encoding of spoken output, sent as OpenAI's `audio` parameter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioConfig {
+    /// Provider voice name, e.g. `alloy`.
+    pub voice: String,
+    /// Audio encoding, e.g. `wav`, `mp3` or `pcm16`.
+    pub format: String,
+}
+
+/// Output format requested through `ChatRequest::response_format`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    /// Any syntactically valid JSON object.
+    JsonObject,
+    /// JSON matching `schema`. With `strict`, providers that support it
+    /// constrain decoding to the schema instead of treating it as a hint.
+    JsonSchema {
+        name: String,
+        schema: Value,
+        strict: bool,
+    },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: Role,
-    pub content: String,
+    pub content: MessageContent,
+    /// Tool calls requested by an assistant turn.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// For `Role::Tool` messages, the id of the call this result answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Marks the conversation up to and including this message as a prompt
+    /// cache breakpoint. Honoured by providers with explicit caching
+    /// (Anthropic `cache_control`) and ignored by the rest.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cache: bool,
+}
+
+impl Message {
+    pub fn new(role: Role, content: impl Into<MessageContent>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            cache: false,
+        }
+    }
+
+    pub fn text(role: Role, text: impl Into<String>) -> Self {
+        Self::new(role, MessageContent::Text(text.into()))
+    }
+
+    /// Sets `cache`, e.g. on a long `Role::System` message reused across calls.
+    pub fn cached(mut self) -> Self {
+        self.cache = true;
+        self
+    }
+}
+
+/// Message body. Plain text serializes as a JSON string, so existing
+/// transcripts keep round-tripping; multimodal bodies serialize as a part list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl MessageContent {
+    /// Concatenated text of the message, ignoring non-text parts.
+    pub fn text(&self) -> String {
+        match self {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    ContentPart::Image { .. } => None,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            MessageContent::Text(text) => text.is_empty(),
+            MessageContent::Parts(parts) => parts.is_empty(),
+        }
+    }
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        MessageContent::Text(String::new())
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        MessageContent::Text(text)
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        MessageContent::Text(text.to_string())
+    }
+}
+
+impl From<Vec<ContentPart>> for MessageContent {
+    fn from(parts: Vec<ContentPart>) -> Self {
+        MessageContent::Parts(parts)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text {
+        text: String,
+    },
+    /// An image given either as an `http(s)` URL or as raw base64 data.
+    Image {
+        url_or_base64: String,
+        mime_type: String,
+    },
+}
+
+impl ContentPart {
+    /// Returns the URL of an image part when it is not inline base64 data.
+    pub fn image_url(&self) -> Option<&str> {
+        match self {
+            ContentPart::Image { url_or_base64, .. }
+                if url_or_base64.starts_with("http://")
+                    || url_or_base64.starts_with("https://") =>
+            {
+                Some(url_or_base64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads an image file into a base64 `Image` part. The MIME type comes
+    /// from the extension: `png`, `jpg`/`jpeg`, `gif` or `webp`. Other
+    /// extensions fail with `ForgeError::Validation`; unreadable files with
+    /// `ForgeError::Internal`.
+    pub fn image_from_path(path: impl AsRef<Path>) -> Result<Self, ForgeError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        let mime_type = match extension.as_deref() {
+            Some("png") => "image/png",
+            Some("jpg" | "jpeg") => "image/jpeg",
+            Some("gif") => "image/gif",
+            Some("webp") => "image/webp",
+            _ => {
+                return Err(ForgeError::Validation(format!(
+                    "unsupported image type: {}",
+                    path.display()
+                )))
+            }
+        };
+        let bytes = std::fs::read(path).map_err(|e| {
+            ForgeError::Internal(format!("failed to read image {}: {e}", path.display()))
+        })?;
+        Ok(ContentPart::Image {
+            url_or_base64: BASE64_STANDARD.encode(bytes),
+            mime_type: mime_type.to_string(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,15 +291,135 @@ pub struct ToolDefinition {
     pub name: String,
     pub description: Option<String>,
     pub input_schema: Value,
+    /// Provider-native declaration sent verbatim as one entry of the `tools`
+    /// array instead of a function schema, e.g. Anthropic server tools such as
+    /// `{"type": "web_search_20250305", "name": "web_search"}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ToolDefinition {
+    /// Wraps a provider-native tool declaration, see `ToolDefinition::raw`.
+    /// `name` is taken from the declaration's `name` field when present.
+    pub fn server_tool(declaration: Value) -> Self {
+        Self {
+            name: declaration
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            description: None,
+            input_schema: Value::Null,
+            raw: Some(declaration),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ChatResponse {
     pub id: String,
     pub model: String,
     pub output_text: String,
+    /// Reasoning ("thinking") text the provider returned apart from the answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
     pub tool_calls: Vec<ToolCall>,
     pub usage: Option<Usage>,
+    /// Why generation stopped. Set even when `output_text` is empty, so an
+    /// intentionally empty answer can be told apart from a failed parse.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
+    /// Wall-clock timing, populated when the adapter is asked to collect it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<ResponseTiming>,
+    /// Sources the answer was grounded on, for providers that report them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
+    /// Backend configuration the response was generated with (OpenAI
+    /// `system_fingerprint`); a change can explain different outputs for the
+    /// same seed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_fingerprint: Option<String>,
+    /// Spoken output of audio-capable models (OpenAI `message.audio`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<AudioOutput>,
+    /// Every completion, in order, when the provider returned more than one
+    /// (`ChatRequest::n`). `output_text`, `tool_calls` and `finish_reason`
+    /// mirror the first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<Choice>,
+}
+
+impl ChatResponse {
+    /// The response `scorer` rates highest; the earliest wins ties. An empty
+    /// `responses` yields `ChatResponse::default()`.
+    pub fn best_of(
+        responses: Vec<ChatResponse>,
+        scorer: impl Fn(&ChatResponse) -> f64,
+    ) -> ChatResponse {
+        let mut best: Option<(f64, ChatResponse)> = None;
+        for response in responses {
+            let score = scorer(&response);
+            match &best {
+                Some((top, _)) if score <= *top => {}
+                _ => best = Some((score, response)),
+            }
+        }
+        best.map(|(_, response)| response).unwrap_or_default()
+    }
+}
+
+/// One of several completions returned for a request with `n > 1`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Choice {
+    pub output_text: String,
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
+}
+
+/// A source backing part of the output. `start_index`/`end_index` delimit the
+/// supported span of `output_text` as the provider reports it; both are
+/// `None` when the source is not tied to a span.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Citation {
+    pub uri: String,
+    pub title: Option<String>,
+    pub start_index: Option<usize>,
+    pub end_index: Option<usize>,
+}
+
+/// Audio generated by the model. `data_base64` is encoded in the format the
+/// request asked for; `id` lets later turns refer back to the audio until
+/// `expires_at` (Unix seconds).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioOutput {
+    pub id: String,
+    pub data_base64: String,
+    pub transcript: String,
+    pub expires_at: Option<u64>,
+}
+
+/// Why the provider stopped generating, normalized across providers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    /// Natural end of turn or a stop sequence.
+    Stop,
+    /// Output hit the token limit.
+    Length,
+    ToolCalls,
+    ContentFilter,
+    /// A provider reason with no normalized equivalent, kept verbatim.
+    Other(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseTiming {
+    /// Time from sending the request until the response was fully parsed.
+    pub total: Duration,
+    /// Time until the response headers arrived.
+    pub first_byte: Option<Duration>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,20 +429,80 @@ pub struct ToolCall {
     pub arguments: Value,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Usage {
-    pub input_tokens: u32,
-    pub output_tokens: u32,
-    pub total_tokens: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    /// Input tokens written to the provider's prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u64>,
+    /// Input tokens served from the provider's prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input_tokens: Option<u64>,
+    /// Output tokens spent on hidden reasoning, where the provider reports them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens: Option<u64>,
 }
 
+impl Usage {
+    /// Adds `other` into `self`, saturating. Optional counts stay `None` only
+    /// while neither side reports them.
+    pub fn accumulate(&mut self, other: &Usage) {
+        fn add(total: &mut Option<u64>, value: Option<u64>) {
+            if let Some(value) = value {
+                *total = Some(total.unwrap_or(0).saturating_add(value));
+            }
+        }
+        self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
+        self.total_tokens = self.total_tokens.saturating_add(other.total_tokens);
+        add(
+            &mut self.cache_creation_input_tokens,
+            other.cache_creation_input_tokens,
+        );
+        add(&mut self.cached_input_tokens, other.cached_input_tokens);
+        add(&mut self.reasoning_tokens, other.reasoning_tokens);
+    }
+}
+
+/// One event from `ChatAdapter::chat_stream`.
+///
+/// New variants may be added in minor releases, so matches should end with a
+/// `_` arm that skips events they do not handle.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum StreamEvent {
-    TextDelta { delta: String },
-    ToolCallDelta { call_id: String, delta: Value },
-    Usage { usage: Usage },
+    /// Emitted once, ahead of any deltas, when the provider reports the
+    /// response id and the model answering.
+    ResponseStart {
+        id: String,
+        model: String,
+    },
+    TextDelta {
+        delta: String,
+    },
+    /// Reasoning ("thinking") text, kept apart from the answer's `TextDelta`s.
+    ReasoningDelta {
+        delta: String,
+    },
+    ToolCallDelta {
+        call_id: String,
+        delta: Value,
+    },
+    Usage {
+        usage: Usage,
+    },
+    /// Emitted once, before `Done`, when the provider reports a stop reason.
+    FinishReason {
+        reason: FinishReason,
+    },
     Done,
+    /// An event type this release does not recognise, e.g. read from a
+    /// replay file recorded by a newer one.
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,6 +521,110 @@ pub struct AdapterInfo {
     pub capabilities: CapabilityMatrix,
 }
 
+/// Error reported by a provider, with the machine-readable parts of its error
+/// body kept alongside the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderError {
+    pub message: String,
+    /// Provider error code, e.g. OpenAI's `context_length_exceeded` or Gemini's
+    /// `INVALID_ARGUMENT` status.
+    pub code: Option<String>,
+    /// Provider error type, e.g. `invalid_request_error`.
+    pub kind: Option<String>,
+    /// HTTP status of the response, or 0 when the error did not come from an
+    /// HTTP error response.
+    pub http_status: u16,
+}
+
+/// Characters of a raw error body adapters keep as the `ProviderError`
+/// message when the body is not the provider's JSON error shape.
+pub const DEFAULT_ERROR_BODY_LIMIT: usize = 2048;
+
+impl ProviderError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            code: None,
+            kind: None,
+            http_status: 0,
+        }
+    }
+}
+
+/// `extra_headers` that carry credentials and must never be logged or
+/// recorded in plaintext.
+pub const CREDENTIAL_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "api-key",
+    "x-api-key",
+    "x-goog-api-key",
+];
+
+/// Whether `name` is one of `CREDENTIAL_HEADERS`, ignoring case.
+pub fn is_credential_header(name: &str) -> bool {
+    CREDENTIAL_HEADERS
+        .iter()
+        .any(|header| header.eq_ignore_ascii_case(name))
+}
+
+/// Parses a provider base URL from configuration, accepting only `http` and
+/// `https` URLs.
+pub fn parse_base_url(raw: &str) -> Result<Url, ForgeError> {
+    let url =
+        Url::parse(raw).map_err(|e| ForgeError::Validation(format!("invalid base URL: {e}")))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ForgeError::Validation(format!(
+            "invalid base URL: unsupported scheme {:?}",
+            url.scheme()
+        )));
+    }
+    Ok(url)
+}
+
+/// Cuts `text` to at most `max_chars` characters, ending it with `…` when
+/// anything was removed. Used for raw error bodies such as HTML error pages
+/// and for traced content.
+pub fn truncate_chars(mut text: String, max_chars: usize) -> String {
+    if let Some((cut, _)) = text.char_indices().nth(max_chars) {
+        text.truncate(cut);
+        text.push('…');
+    }
+    text
+}
+
+impl From<String> for ProviderError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<&str> for ProviderError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+impl std::fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)?;
+        let mut details = Vec::new();
+        if self.http_status != 0 {
+            details.push(format!("status {}", self.http_status));
+        }
+        if let Some(code) = &self.code {
+            details.push(format!("code {code}"));
+        }
+        if let Some(kind) = &self.kind {
+            details.push(format!("type {kind}"));
+        }
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ForgeError {
     #[error("validation error: {0}")]
@@ -97,15 +632,123 @@ pub enum ForgeError {
     #[error("authentication error")]
     Authentication,
     #[error("rate limited")]
-    RateLimited,
+    RateLimited {
+        /// Delay requested by the provider via `Retry-After`, when present.
+        retry_after: Option<Duration>,
+    },
     #[error("provider error: {0}")]
-    Provider(String),
+    Provider(ProviderError),
     #[error("transport error: {0}")]
     Transport(String),
     #[error("internal error: {0}")]
     Internal(String),
 }
 
+/// Exponential backoff settings for adapter-level retries of rate-limit and
+/// transport failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryConfig {
+    pub max_retries: usize,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Delay before retry number `retry` (zero-based): `base_delay * 2^retry`,
+    /// capped at `max_delay`. With jitter the delay is drawn from the upper half
+    /// of that window.
+    pub fn backoff(&self, retry: usize) -> Duration {
+        let factor = 1u32.checked_shl(retry.min(31) as u32).unwrap_or(u32::MAX);
+        let delay = self
+            .base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        if !self.jitter {
+            return delay;
+        }
+        use std::hash::{BuildHasher, Hasher};
+        let random = std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish();
+        let half = delay / 2;
+        half + half.mul_f64((random % 1_000) as f64 / 1_000.0)
+    }
+
+    /// Whether `error` is worth retrying.
+    pub fn should_retry(error: &ForgeError) -> bool {
+        matches!(
+            error,
+            ForgeError::RateLimited { .. } | ForgeError::Transport(_)
+        )
+    }
+}
+
+/// Connection pool and keep-alive tuning for an adapter's HTTP client, applied
+/// with the adapters' `with_http_config`. Without it the HTTP client's own
+/// defaults apply. The `Default` values suit high-throughput use:
+///
+/// - `pool_max_idle_per_host`: 32 idle connections kept per host
+/// - `pool_idle_timeout`: idle connections closed after 90 seconds
+/// - `tcp_keepalive`: TCP keep-alive probes every 60 seconds
+/// - `http2_prior_knowledge`: off; only enable it for endpoints known to
+///   speak HTTP/2 without negotiation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpConfig {
+    pub pool_max_idle_per_host: usize,
+    /// `None` keeps idle connections open indefinitely.
+    pub pool_idle_timeout: Option<Duration>,
+    /// `None` disables TCP keep-alive.
+    pub tcp_keepalive: Option<Duration>,
+    pub http2_prior_knowledge: bool,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            http2_prior_knowledge: false,
+        }
+    }
+}
+
+/// Controls whether adapter tracing spans carry message content. Only takes
+/// effect in adapters built with their `tracing` feature.
+#[derive(Debug, Clone, Copy)]
+pub struct TraceContentOptions {
+    /// Attach the prompt and the `chat` completion as `prompt` and
+    /// `completion` events on the adapter span.
+    pub record_content: bool,
+    /// Recorded content is cut to this many characters.
+    pub max_chars: usize,
+    /// Applied to content before truncation, e.g. to mask personal data.
+    pub redact: Option<fn(&str) -> String>,
+}
+
+impl Default for TraceContentOptions {
+    fn default() -> Self {
+        Self {
+            record_content: false,
+            max_chars: 1024,
+            redact: None,
+        }
+    }
+}
+
 #[async_trait]
 pub trait ChatAdapter: Send + Sync {
     fn info(&self) -> AdapterInfo;
@@ -118,14 +761,103 @@ pub trait ChatAdapter: Send + Sync {
     ) -> Result<StreamResult<StreamEvent>, ForgeError>;
 }
 
+/// Stop sequences accepted by `validate_request`; the common provider cap.
+/// Adapters also check their own provider's limit when called directly.
+pub const MAX_STOP_SEQUENCES: usize = 4;
+
+/// Checks `request` and fails with the first problem found; see
+/// `validate_request_all` for the full list.
 pub fn validate_request(request: &ChatRequest) -> Result<(), ForgeError> {
+    validate_request_all(request)
+        .map_err(|problems| ForgeError::Validation(problems.into_iter().next().unwrap_or_default()))
+}
+
+/// Checks `request` and reports every problem found, in the order the
+/// fields are declared on `ChatRequest`: model, messages (including unknown
+/// `tool_call_id`s), sampling parameters, `n`, `stop`, tools, `tool_choice`,
+/// then modalities.
+pub fn validate_request_all(request: &ChatRequest) -> Result<(), Vec<String>> {
+    let mut problems = Vec::new();
     if request.model.trim().is_empty() {
-        return Err(ForgeError::Validation("model cannot be empty".to_string()));
+        problems.push("model cannot be empty".to_string());
     }
     if request.messages.is_empty() {
-        return Err(ForgeError::Validation(
-            "messages cannot be empty".to_string(),
+        problems.push("messages cannot be empty".to_string());
+    }
+    let mut call_ids = std::collections::HashSet::new();
+    for (index, message) in request.messages.iter().enumerate() {
+        if matches!(message.role, Role::Assistant) {
+            call_ids.extend(message.tool_calls.iter().map(|call| call.id.as_str()));
+        }
+        if let Some(id) = &message.tool_call_id {
+            if matches!(message.role, Role::Tool) && !call_ids.contains(id.as_str()) {
+                problems.push(format!(
+                    "message {index} answers unknown tool_call_id {id:?}; no earlier assistant message made that call"
+                ));
+            }
+        }
+    }
+    if let Some(temperature) = request.temperature {
+        if !(0.0..=2.0).contains(&temperature) {
+            problems.push(format!(
+                "temperature must be between 0.0 and 2.0, got {temperature}"
+            ));
+        }
+    }
+    if let Some(top_p) = request.top_p {
+        if !(0.0..=1.0).contains(&top_p) {
+            problems.push(format!("top_p must be between 0.0 and 1.0, got {top_p}"));
+        }
+    }
+    for (name, penalty) in [
+        ("frequency_penalty", request.frequency_penalty),
+        ("presence_penalty", request.presence_penalty),
+    ] {
+        if let Some(penalty) = penalty {
+            if !(-2.0..=2.0).contains(&penalty) {
+                problems.push(format!(
+                    "{name} must be between -2.0 and 2.0, got {penalty}"
+                ));
+            }
+        }
+    }
+    if request.n == Some(0) {
+        problems.push("n must be at least 1".to_string());
+    }
+    if request.stop.len() > MAX_STOP_SEQUENCES {
+        problems.push(format!(
+            "at most {MAX_STOP_SEQUENCES} stop sequences are supported, got {}",
+            request.stop.len()
         ));
     }
-    Ok(())
+    let mut seen = std::collections::HashSet::new();
+    for (index, tool) in request.tools.iter().enumerate() {
+        if tool.raw.is_some() {
+            continue;
+        }
+        if tool.name.trim().is_empty() {
+            problems.push(format!("tool at index {index} has an empty name"));
+        } else if !seen.insert(tool.name.as_str()) {
+            problems.push(format!("duplicate tool name: {}", tool.name));
+        }
+        if tool.input_schema.get("type").and_then(Value::as_str) != Some("object") {
+            problems.push(format!(
+                "tool {:?} input_schema must be a JSON object with \"type\": \"object\"",
+                tool.name
+            ));
+        }
+    }
+    if let Some(ToolChoice::Function(name)) = &request.tool_choice {
+        if !request.tools.iter().any(|tool| tool.name == *name) {
+            problems.push(format!("tool_choice names unknown tool {name:?}"));
+        }
+    }
+    if request.modalities.contains(&Modality::Audio) && request.audio.is_none() {
+        problems.push("audio config is required when the audio modality is requested".to_string());
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
 }
@@ -0,0 +1,84 @@
+//! OpenAI chat-completions message format, shared by the OpenAI adapter and
+//! `ToolLoopResult::to_openai_messages` so the two cannot drift apart.
+
+use crate::{ContentPart, Message, MessageContent, Role};
+use serde_json::{json, Map, Value};
+
+/// Serializes `message` as an OpenAI chat-completions message.
+pub fn message_to_openai(message: &Message) -> Value {
+    let mut out = Map::new();
+    out.insert(
+        "role".to_string(),
+        Value::String(role_to_openai(&message.role).to_string()),
+    );
+    if message.tool_calls.is_empty() {
+        out.insert("content".to_string(), content_to_openai(&message.content));
+    } else {
+        // A pure tool-call turn carries `content: null` rather than an empty string.
+        let content = if message.content.is_empty() {
+            Value::Null
+        } else {
+            content_to_openai(&message.content)
+        };
+        out.insert("content".to_string(), content);
+        out.insert(
+            "tool_calls".to_string(),
+            message
+                .tool_calls
+                .iter()
+                .map(|call| {
+                    json!({
+                        "id": call.id,
+                        "type": "function",
+                        "function": {
+                            "name": call.name,
+                            "arguments": call.arguments.to_string(),
+                        }
+                    })
+                })
+                .collect(),
+        );
+    }
+    if let Some(tool_call_id) = &message.tool_call_id {
+        out.insert(
+            "tool_call_id".to_string(),
+            Value::String(tool_call_id.clone()),
+        );
+    }
+    Value::Object(out)
+}
+
+fn role_to_openai(role: &Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool => "tool",
+    }
+}
+
+fn content_to_openai(content: &MessageContent) -> Value {
+    let parts = match content {
+        MessageContent::Text(text) => return Value::String(text.clone()),
+        MessageContent::Parts(parts) => parts,
+    };
+    parts
+        .iter()
+        .map(|part| match (part, part.image_url()) {
+            (ContentPart::Text { text }, _) => json!({ "type": "text", "text": text }),
+            (ContentPart::Image { .. }, Some(url)) => {
+                json!({ "type": "image_url", "image_url": { "url": url } })
+            }
+            (
+                ContentPart::Image {
+                    url_or_base64,
+                    mime_type,
+                },
+                None,
+            ) => json!({
+                "type": "image_url",
+                "image_url": { "url": format!("data:{mime_type};base64,{url_or_base64}") }
+            }),
+        })
+        .collect()
+}
@@ -0,0 +1,129 @@
+//! `tracing` instrumentation shared by the provider adapters, compiled to
+//! no-ops unless the `tracing` feature is enabled.
+
+#[cfg(feature = "tracing")]
+use crate::truncate_chars;
+use crate::{ChatRequest, ForgeError, TraceContentOptions, Usage};
+use serde_json::Value;
+use std::future::Future;
+use std::time::Duration;
+
+/// Runs `call` inside a `forgeai.adapter` span and records its total latency.
+pub async fn instrument<T>(
+    operation: &'static str,
+    provider: &str,
+    model: &str,
+    call: impl Future<Output = Result<T, ForgeError>>,
+) -> Result<T, ForgeError> {
+    #[cfg(feature = "tracing")]
+    {
+        use tracing::Instrument;
+
+        let span = tracing::info_span!(
+            "forgeai.adapter",
+            operation,
+            provider,
+            model,
+            request_bytes = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+        );
+        let started = std::time::Instant::now();
+        let result = call.instrument(span.clone()).await;
+        span.record("latency_ms", started.elapsed().as_millis() as u64);
+        if let Err(error) = &result {
+            span.in_scope(|| tracing::warn!(%error, "provider call failed"));
+        }
+        result
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (operation, provider, model);
+        call.await
+    }
+}
+
+/// Records the encoded size of `body` on the current adapter span.
+pub fn request_body(body: &Value) {
+    #[cfg(feature = "tracing")]
+    {
+        let bytes = serde_json::to_vec(body).map_or(0, |encoded| encoded.len());
+        tracing::Span::current().record("request_bytes", bytes as u64);
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = body;
+}
+
+/// Logs the HTTP status a provider answered with.
+pub fn http_status(status: u16) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(status, "provider responded");
+    #[cfg(not(feature = "tracing"))]
+    let _ = status;
+}
+
+/// Logs that attempt `attempt` is retried after `delay`.
+pub fn retry(attempt: usize, delay: Duration) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        attempt,
+        delay_ms = delay.as_millis() as u64,
+        "retrying provider call"
+    );
+    #[cfg(not(feature = "tracing"))]
+    let _ = (attempt, delay);
+}
+
+/// Logs the token usage of a response.
+pub fn usage(usage: &Usage) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        input_tokens = usage.input_tokens,
+        output_tokens = usage.output_tokens,
+        total_tokens = usage.total_tokens,
+        "token usage"
+    );
+    #[cfg(not(feature = "tracing"))]
+    let _ = usage;
+}
+
+/// Records the prompt as a `prompt` event on the current span, if enabled.
+pub fn prompt(options: &TraceContentOptions, request: &ChatRequest) {
+    #[cfg(feature = "tracing")]
+    if options.record_content {
+        let text = request
+            .system
+            .iter()
+            .map(|system| format!("system: {system}"))
+            .chain(request.messages.iter().map(|message| {
+                format!(
+                    "{}: {}",
+                    format!("{:?}", message.role).to_lowercase(),
+                    message.content.text()
+                )
+            }))
+            .collect::<Vec<_>>()
+            .join("\n");
+        tracing::info!(content = %prepare_content(options, &text), "prompt");
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (options, request);
+}
+
+/// Records `text` as a `completion` event on the current span, if enabled.
+pub fn completion(options: &TraceContentOptions, text: &str) {
+    #[cfg(feature = "tracing")]
+    if options.record_content {
+        tracing::info!(content = %prepare_content(options, text), "completion");
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (options, text);
+}
+
+#[cfg(feature = "tracing")]
+fn prepare_content(options: &TraceContentOptions, text: &str) -> String {
+    let text = match options.redact {
+        Some(redact) => redact(text),
+        None => text.to_string(),
+    };
+    truncate_chars(text, options.max_chars)
+}
@@ -0,0 +1,132 @@
+//! In-memory `ChatAdapter` for tests, enabled by the `testing` feature.
+
+use crate::{
+    AdapterInfo, CapabilityMatrix, ChatAdapter, ChatRequest, ChatResponse, ForgeError, StreamEvent,
+    StreamResult,
+};
+use async_trait::async_trait;
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+/// `ChatAdapter` that serves canned responses in the order they were pushed
+/// and fails with `ForgeError::Internal` once a queue is exhausted.
+///
+/// ```
+/// use forgeai_core::testing::MockAdapter;
+/// use forgeai_core::ChatResponse;
+/// use forgeai::Client;
+/// use std::sync::Arc;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let adapter = MockAdapter::new().push_response(ChatResponse {
+///     output_text: "hi".to_string(),
+///     ..Default::default()
+/// });
+/// let client = Client::new(Arc::new(adapter));
+///
+/// assert_eq!(client.complete("mock-model", "Say hi").await.unwrap(), "hi");
+/// assert!(client.complete("mock-model", "Again").await.is_err());
+/// # }
+/// ```
+pub struct MockAdapter {
+    info: AdapterInfo,
+    responses: Mutex<VecDeque<ChatResponse>>,
+    streams: Mutex<VecDeque<Vec<StreamEvent>>>,
+    requests: Mutex<Vec<ChatRequest>>,
+}
+
+impl Default for MockAdapter {
+    fn default() -> Self {
+        Self {
+            info: AdapterInfo {
+                name: "mock".to_string(),
+                base_url: None,
+                capabilities: CapabilityMatrix {
+                    streaming: true,
+                    tools: true,
+                    structured_output: true,
+                    multimodal_input: true,
+                    citations: true,
+                },
+            },
+            responses: Mutex::new(VecDeque::new()),
+            streams: Mutex::new(VecDeque::new()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl MockAdapter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a response for the next `chat` call.
+    pub fn push_response(self, response: ChatResponse) -> Self {
+        lock(&self.responses).push_back(response);
+        self
+    }
+
+    /// Queues the events yielded by the next `chat_stream` call.
+    pub fn push_stream(self, events: Vec<StreamEvent>) -> Self {
+        lock(&self.streams).push_back(events);
+        self
+    }
+
+    /// Replaces the default `AdapterInfo` (named `mock`, every capability on).
+    pub fn with_info(mut self, info: AdapterInfo) -> Self {
+        self.info = info;
+        self
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<ChatRequest> {
+        lock(&self.requests).clone()
+    }
+}
+
+#[async_trait]
+impl ChatAdapter for MockAdapter {
+    fn info(&self) -> AdapterInfo {
+        self.info.clone()
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        lock(&self.requests).push(request);
+        lock(&self.responses)
+            .pop_front()
+            .ok_or_else(|| ForgeError::Internal("no mock chat response remaining".to_string()))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+        lock(&self.requests).push(request);
+        let events = lock(&self.streams)
+            .pop_front()
+            .ok_or_else(|| ForgeError::Internal("no mock stream response remaining".to_string()))?;
+        Ok(Box::pin(Events(events.into())))
+    }
+}
+
+struct Events(VecDeque<StreamEvent>);
+
+impl Stream for Events {
+    type Item = Result<StreamEvent, ForgeError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.0.pop_front().map(Ok))
+    }
+}
+
+/// A panicking test must not poison the mock for the assertions after it.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
@@ -0,0 +1,105 @@
+//! Procedural macros for forgeai-rs.
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Error, Expr, ExprLit, FnArg, ItemFn, Lit, Meta};
+
+/// Turns an async function into a tool for `Client::chat_with_tools`.
+///
+/// The function takes one argument struct deriving `JsonSchema` and
+/// `Deserialize`, returns `Result<O, ToolError>` with `O: Serialize`, and
+/// must have a doc comment, which becomes the tool description. Alongside
+/// it the macro generates `register_<name>(&mut ToolRegistry)`, registering
+/// the function under its own name with a schema derived from the argument
+/// struct. Generated code refers to the `forgeai` crate.
+///
+/// ```rust,ignore
+/// /// Current temperature in a city, in degrees Celsius.
+/// #[forge_tool]
+/// async fn weather(args: WeatherArgs) -> Result<Value, ToolError> {
+///     fetch_weather(&args.city).await
+/// }
+///
+/// let mut tools = ToolRegistry::new();
+/// register_weather(&mut tools);
+/// ```
+#[proc_macro_attribute]
+pub fn forge_tool(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return Error::new(
+            proc_macro2::Span::call_site(),
+            "#[forge_tool] takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let function = parse_macro_input!(item as ItemFn);
+    match expand(&function) {
+        Ok(register) => quote!(#function #register).into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(function: &ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let signature = &function.sig;
+    if signature.asyncness.is_none() {
+        return Err(Error::new_spanned(
+            signature.fn_token,
+            "#[forge_tool] expects an async fn",
+        ));
+    }
+    let mut inputs = signature.inputs.iter();
+    let args = match (inputs.next(), inputs.next()) {
+        (Some(FnArg::Typed(args)), None) => &args.ty,
+        _ => {
+            return Err(Error::new_spanned(
+                &signature.inputs,
+                "#[forge_tool] expects exactly one argument struct",
+            ))
+        }
+    };
+    let description = doc_comment(function).ok_or_else(|| {
+        Error::new_spanned(
+            &signature.ident,
+            "#[forge_tool] needs a doc comment to describe the tool",
+        )
+    })?;
+
+    let ident = &signature.ident;
+    let name = ident.to_string();
+    let register = format_ident!("register_{}", ident);
+    let visibility = &function.vis;
+    let doc = format!("Registers [`{name}`] as the `{name}` tool in `registry`.");
+    Ok(quote! {
+        #[doc = #doc]
+        #visibility fn #register(
+            registry: &mut ::forgeai::forgeai_tools::ToolRegistry,
+        ) -> &mut ::forgeai::forgeai_tools::ToolRegistry {
+            registry.register_tool(
+                ::forgeai::forgeai_schema::tool_definition::<#args>(#name, #description),
+                #ident,
+            )
+        }
+    })
+}
+
+/// The function's doc comment with each line trimmed, or `None` when it has
+/// none.
+fn doc_comment(function: &ItemFn) -> Option<String> {
+    let lines: Vec<String> = function
+        .attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(line),
+                    ..
+                }) => Some(line.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
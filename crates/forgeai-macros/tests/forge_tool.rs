@@ -0,0 +1,116 @@
+use forgeai::forgeai_schema::schemars::JsonSchema;
+use forgeai::forgeai_tools::{ToolError, ToolRegistry};
+use forgeai::{Client, ToolLoopOptions};
+use forgeai_core::testing::MockAdapter;
+use forgeai_core::{ChatRequest, ChatResponse, Message, Role, ToolCall};
+use forgeai_macros::forge_tool;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Deserialize, JsonSchema)]
+#[schemars(crate = "forgeai::forgeai_schema::schemars")]
+struct WeatherArgs {
+    /// City to report on.
+    city: String,
+}
+
+/// Current temperature in a city,
+/// in degrees Celsius.
+#[forge_tool]
+async fn weather(args: WeatherArgs) -> Result<Value, ToolError> {
+    tokio::task::yield_now().await;
+    Ok(json!({ "city": args.city, "celsius": 21 }))
+}
+
+fn request(tools: &ToolRegistry) -> ChatRequest {
+    ChatRequest {
+        model: "mock-model".to_string(),
+        messages: vec![Message::new(Role::User, "how warm is Paris?")],
+        system: None,
+        temperature: None,
+        top_p: None,
+        frequency_penalty: None,
+        presence_penalty: None,
+        max_tokens: None,
+        n: None,
+        seed: None,
+        stop: vec![],
+        tools: tools.definitions(),
+        tool_choice: None,
+        response_format: None,
+        modalities: vec![],
+        audio: None,
+        extra_headers: HashMap::new(),
+        metadata: json!({}),
+    }
+}
+
+#[test]
+fn generates_definition_from_doc_comment_and_schema() {
+    let mut tools = ToolRegistry::new();
+    register_weather(&mut tools);
+
+    let definitions = tools.definitions();
+    assert_eq!(definitions.len(), 1);
+    assert_eq!(definitions[0].name, "weather");
+    assert_eq!(
+        definitions[0].description.as_deref(),
+        Some("Current temperature in a city,\nin degrees Celsius.")
+    );
+    let schema = &definitions[0].input_schema;
+    assert_eq!(schema["properties"]["city"]["type"], "string");
+    assert_eq!(schema["required"], json!(["city"]));
+}
+
+#[tokio::test]
+async fn macro_tool_runs_in_the_tool_loop() {
+    let mut tools = ToolRegistry::new();
+    register_weather(&mut tools);
+    let adapter = MockAdapter::new()
+        .push_response(ChatResponse {
+            tool_calls: vec![ToolCall {
+                id: "call-1".to_string(),
+                name: "weather".to_string(),
+                arguments: json!({ "city": "Paris" }),
+            }],
+            ..Default::default()
+        })
+        .push_response(ChatResponse {
+            output_text: "It is 21°C in Paris.".to_string(),
+            ..Default::default()
+        });
+    let client = Client::new(Arc::new(adapter));
+
+    let result = client
+        .chat_with_tools(request(&tools), &tools, ToolLoopOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(result.final_response.output_text, "It is 21°C in Paris.");
+    assert_eq!(result.sent_tools[0].name, "weather");
+    assert_eq!(result.tool_invocations.len(), 1);
+    assert_eq!(
+        result.tool_invocations[0].output,
+        json!({ "city": "Paris", "celsius": 21 })
+    );
+}
+
+#[tokio::test]
+async fn mismatched_arguments_fail_the_call() {
+    let mut tools = ToolRegistry::new();
+    register_weather(&mut tools);
+
+    let err = forgeai::forgeai_tools::AsyncToolExecutor::call_async(
+        &tools,
+        "weather",
+        json!({ "town": "Paris" }),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(
+        matches!(err, ToolError::Execution(ref m) if m.contains("invalid arguments for weather"))
+    );
+}
@@ -1,3 +1,339 @@
+use async_trait::async_trait;
+use forgeai_core::{
+    is_credential_header, AdapterInfo, ChatAdapter, ChatRequest, ChatResponse, ForgeError,
+    StreamEvent, StreamResult,
+};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
 pub fn init_tracing() {
     let _ = tracing::subscriber::set_global_default(tracing::subscriber::NoSubscriber::default());
 }
+
+const REDACTED: &str = "<redacted>";
+
+type LogSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// `ChatAdapter` that logs every request, response, stream event and error of
+/// the wrapped adapter as one JSON line each, then passes them through
+/// unchanged.
+///
+/// Credential headers in `extra_headers` are always redacted; message,
+/// output and tool-call contents only when `redact_content` is set.
+pub struct LoggingAdapter {
+    inner: Arc<dyn ChatAdapter>,
+    sink: LogSink,
+    redact_content: bool,
+}
+
+impl LoggingAdapter {
+    /// Logs to `sink`.
+    pub fn new(inner: Arc<dyn ChatAdapter>, sink: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self {
+            inner,
+            sink: Arc::new(sink),
+            redact_content: false,
+        }
+    }
+
+    /// Logs through `tracing` at `INFO` level with the `forgeai` target.
+    pub fn with_tracing(inner: Arc<dyn ChatAdapter>) -> Self {
+        Self::new(inner, |line| tracing::info!(target: "forgeai", "{line}"))
+    }
+
+    /// Replaces message bodies, output text, reasoning, audio and tool-call
+    /// arguments with `"<redacted>"` in log entries, including those of every
+    /// returned choice.
+    pub fn redact_content(mut self, redact: bool) -> Self {
+        self.redact_content = redact;
+        self
+    }
+
+    fn log(&self, kind: &str, provider: &str, payload: Value) {
+        let entry = json!({ "kind": kind, "provider": provider, "payload": payload });
+        (self.sink)(&entry.to_string());
+    }
+
+    fn request_entry(&self, request: &ChatRequest) -> Value {
+        let mut value = serde_json::to_value(request).unwrap_or(Value::Null);
+        if let Some(headers) = value
+            .get_mut("extra_headers")
+            .and_then(Value::as_object_mut)
+        {
+            for (name, header) in headers.iter_mut() {
+                if is_credential_header(name) {
+                    *header = json!(REDACTED);
+                }
+            }
+        }
+        if self.redact_content {
+            if let Some(messages) = value.get_mut("messages").and_then(Value::as_array_mut) {
+                for message in messages {
+                    message["content"] = json!(REDACTED);
+                    redact_tool_calls(message);
+                }
+            }
+            if value.get("system").is_some_and(|system| !system.is_null()) {
+                value["system"] = json!(REDACTED);
+            }
+        }
+        value
+    }
+
+    fn response_entry(&self, response: &ChatResponse) -> Value {
+        let mut value = serde_json::to_value(response).unwrap_or(Value::Null);
+        if self.redact_content {
+            value["output_text"] = json!(REDACTED);
+            redact_tool_calls(&mut value);
+            if value.get("reasoning").is_some() {
+                value["reasoning"] = json!(REDACTED);
+            }
+            if let Some(audio) = value.get_mut("audio").filter(|audio| audio.is_object()) {
+                audio["transcript"] = json!(REDACTED);
+                audio["data_base64"] = json!(REDACTED);
+            }
+            if let Some(choices) = value.get_mut("choices").and_then(Value::as_array_mut) {
+                for choice in choices {
+                    choice["output_text"] = json!(REDACTED);
+                    redact_tool_calls(choice);
+                }
+            }
+        }
+        value
+    }
+
+    fn event_entry(&self, event: &StreamEvent) -> Value {
+        let mut value = serde_json::to_value(event).unwrap_or(Value::Null);
+        if self.redact_content {
+            if let Some(delta) = value.get_mut("delta") {
+                *delta = json!(REDACTED);
+            }
+        }
+        value
+    }
+}
+
+fn redact_tool_calls(value: &mut Value) {
+    if let Some(calls) = value.get_mut("tool_calls").and_then(Value::as_array_mut) {
+        for call in calls {
+            call["arguments"] = json!(REDACTED);
+        }
+    }
+}
+
+#[async_trait]
+impl ChatAdapter for LoggingAdapter {
+    fn info(&self) -> AdapterInfo {
+        self.inner.info()
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        let provider = self.inner.info().name;
+        self.log("request", &provider, self.request_entry(&request));
+        match self.inner.chat(request).await {
+            Ok(response) => {
+                self.log("response", &provider, self.response_entry(&response));
+                Ok(response)
+            }
+            Err(err) => {
+                self.log("error", &provider, json!(err.to_string()));
+                Err(err)
+            }
+        }
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+        let provider = self.inner.info().name;
+        self.log("request", &provider, self.request_entry(&request));
+        let stream = match self.inner.chat_stream(request).await {
+            Ok(stream) => stream,
+            Err(err) => {
+                self.log("error", &provider, json!(err.to_string()));
+                return Err(err);
+            }
+        };
+        let logger = Self {
+            inner: Arc::clone(&self.inner),
+            sink: Arc::clone(&self.sink),
+            redact_content: self.redact_content,
+        };
+        Ok(Box::pin(stream.map(move |item| {
+            match &item {
+                Ok(event) => logger.log("stream_event", &provider, logger.event_entry(event)),
+                Err(err) => logger.log("error", &provider, json!(err.to_string())),
+            }
+            item
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forgeai_core::{AudioOutput, CapabilityMatrix, Choice, Message, Role, ToolCall};
+    use futures_util::stream;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    struct EchoAdapter;
+
+    #[async_trait]
+    impl ChatAdapter for EchoAdapter {
+        fn info(&self) -> AdapterInfo {
+            AdapterInfo {
+                name: "echo".to_string(),
+                base_url: None,
+                capabilities: CapabilityMatrix {
+                    streaming: true,
+                    tools: false,
+                    structured_output: false,
+                    multimodal_input: false,
+                    citations: false,
+                },
+            }
+        }
+
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+            let output_text = request.messages[0].content.text();
+            let tool_calls = vec![ToolCall {
+                id: "call-1".to_string(),
+                name: "lookup".to_string(),
+                arguments: json!({"q": "secret"}),
+            }];
+            Ok(ChatResponse {
+                id: "resp-1".to_string(),
+                model: request.model,
+                output_text: output_text.clone(),
+                reasoning: Some("secret".to_string()),
+                tool_calls: tool_calls.clone(),
+                audio: Some(AudioOutput {
+                    id: "audio-1".to_string(),
+                    data_base64: "secret".to_string(),
+                    transcript: "secret".to_string(),
+                    expires_at: None,
+                }),
+                choices: vec![Choice {
+                    output_text,
+                    tool_calls,
+                    finish_reason: None,
+                }],
+                ..Default::default()
+            })
+        }
+
+        async fn chat_stream(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+            Ok(Box::pin(stream::iter(vec![
+                Ok(StreamEvent::TextDelta {
+                    delta: "secret".to_string(),
+                }),
+                Ok(StreamEvent::Done),
+            ])))
+        }
+    }
+
+    fn sample_request() -> ChatRequest {
+        ChatRequest {
+            model: "echo-1".to_string(),
+            messages: vec![Message::new(Role::User, "secret")],
+            system: Some("be brief".to_string()),
+            temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            max_tokens: None,
+            n: None,
+            seed: None,
+            stop: vec![],
+            tools: vec![],
+            tool_choice: None,
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::from([
+                ("Authorization".to_string(), "Bearer sk-test".to_string()),
+                ("X-Trace".to_string(), "abc".to_string()),
+            ]),
+            metadata: json!({}),
+        }
+    }
+
+    fn recording_adapter(redact: bool) -> (LoggingAdapter, Arc<Mutex<Vec<Value>>>) {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&lines);
+        let adapter = LoggingAdapter::new(Arc::new(EchoAdapter), move |line| {
+            sink.lock()
+                .unwrap()
+                .push(serde_json::from_str(line).unwrap());
+        })
+        .redact_content(redact);
+        (adapter, lines)
+    }
+
+    #[tokio::test]
+    async fn sink_receives_request_and_response_entries() {
+        let (adapter, lines) = recording_adapter(false);
+        let response = adapter.chat(sample_request()).await.unwrap();
+        assert_eq!(response.output_text, "secret");
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["kind"], "request");
+        assert_eq!(lines[0]["provider"], "echo");
+        assert_eq!(lines[0]["payload"]["model"], "echo-1");
+        assert_eq!(
+            lines[0]["payload"]["extra_headers"]["Authorization"],
+            "<redacted>"
+        );
+        assert_eq!(lines[0]["payload"]["extra_headers"]["X-Trace"], "abc");
+        assert_eq!(lines[1]["kind"], "response");
+        assert_eq!(lines[1]["payload"]["output_text"], "secret");
+    }
+
+    #[tokio::test]
+    async fn redact_content_hides_messages_and_deltas() {
+        let (adapter, lines) = recording_adapter(true);
+        adapter.chat(sample_request()).await.unwrap();
+        let events: Vec<_> = adapter
+            .chat_stream(sample_request())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert!(matches!(&events[0], Ok(StreamEvent::TextDelta { delta }) if delta == "secret"));
+
+        let lines = lines.lock().unwrap();
+        let kinds: Vec<_> = lines.iter().map(|line| line["kind"].clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "request",
+                "response",
+                "request",
+                "stream_event",
+                "stream_event"
+            ]
+        );
+        assert_eq!(lines[0]["payload"]["messages"][0]["content"], "<redacted>");
+        assert_eq!(lines[0]["payload"]["system"], "<redacted>");
+        let response = &lines[1]["payload"];
+        assert_eq!(response["output_text"], "<redacted>");
+        assert_eq!(response["tool_calls"][0]["arguments"], "<redacted>");
+        assert_eq!(response["reasoning"], "<redacted>");
+        assert_eq!(response["audio"]["transcript"], "<redacted>");
+        assert_eq!(response["audio"]["data_base64"], "<redacted>");
+        assert_eq!(response["choices"][0]["output_text"], "<redacted>");
+        assert_eq!(
+            response["choices"][0]["tool_calls"][0]["arguments"],
+            "<redacted>"
+        );
+        assert_eq!(lines[3]["payload"]["delta"], "<redacted>");
+        assert!(!lines.iter().any(|line| line.to_string().contains("secret")));
+    }
+}
@@ -1,7 +1,433 @@
+//! Record/replay wrapper for deterministic tests against recorded provider traffic.
+
+use async_trait::async_trait;
+use forgeai_core::{
+    is_credential_header, AdapterInfo, CapabilityMatrix, ChatAdapter, ChatRequest, ChatResponse,
+    ForgeError, StreamEvent, StreamResult,
+};
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 
+/// One recorded exchange, stored as a single JSONL line.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReplayEntry {
-    pub request: String,
-    pub response: String,
+    /// Canonical hash of the request, see `request_key`.
+    pub key: String,
+    /// The recorded request, with credential `extra_headers` redacted.
+    pub request: ChatRequest,
+    /// Set for `chat` exchanges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<ChatResponse>,
+    /// Set for `chat_stream` exchanges, in the order they were received.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<StreamEvent>>,
+}
+
+enum Mode {
+    Record {
+        inner: Arc<dyn ChatAdapter>,
+        file: Arc<Mutex<File>>,
+    },
+    Replay {
+        responses: HashMap<String, ChatResponse>,
+        streams: HashMap<String, Vec<StreamEvent>>,
+    },
+}
+
+/// `ChatAdapter` that either records the traffic of a wrapped adapter to a
+/// JSONL file or serves responses back from such a file.
+pub struct ReplayAdapter {
+    mode: Mode,
+}
+
+impl ReplayAdapter {
+    /// Forwards every call to `inner` and appends successful exchanges to `path`.
+    pub fn record(inner: Arc<dyn ChatAdapter>, path: impl AsRef<Path>) -> Result<Self, ForgeError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())
+            .map_err(|e| ForgeError::Internal(format!("failed to open replay file: {e}")))?;
+        Ok(Self {
+            mode: Mode::Record {
+                inner,
+                file: Arc::new(Mutex::new(file)),
+            },
+        })
+    }
+
+    /// Serves responses recorded in `path`. When a request was recorded more
+    /// than once, the latest recording wins.
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, ForgeError> {
+        let file = File::open(path.as_ref())
+            .map_err(|e| ForgeError::Internal(format!("failed to open replay file: {e}")))?;
+        let mut responses = HashMap::new();
+        let mut streams = HashMap::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line =
+                line.map_err(|e| ForgeError::Internal(format!("failed to read replay file: {e}")))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: ReplayEntry = serde_json::from_str(&line).map_err(|e| {
+                ForgeError::Internal(format!("invalid replay entry on line {}: {e}", index + 1))
+            })?;
+            if let Some(response) = entry.response {
+                responses.insert(entry.key.clone(), response);
+            }
+            if let Some(events) = entry.events {
+                streams.insert(entry.key, events);
+            }
+        }
+        Ok(Self {
+            mode: Mode::Replay { responses, streams },
+        })
+    }
+}
+
+#[async_trait]
+impl ChatAdapter for ReplayAdapter {
+    fn info(&self) -> AdapterInfo {
+        match &self.mode {
+            Mode::Record { inner, .. } => inner.info(),
+            Mode::Replay { .. } => AdapterInfo {
+                name: "replay".to_string(),
+                base_url: None,
+                capabilities: CapabilityMatrix {
+                    streaming: true,
+                    tools: true,
+                    structured_output: true,
+                    multimodal_input: true,
+                    citations: true,
+                },
+            },
+        }
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        let key = request_key(&request);
+        match &self.mode {
+            Mode::Record { inner, file } => {
+                let response = inner.chat(request.clone()).await?;
+                append_entry(
+                    file,
+                    &ReplayEntry {
+                        key,
+                        request: redact_credentials(request),
+                        response: Some(response.clone()),
+                        events: None,
+                    },
+                )?;
+                Ok(response)
+            }
+            Mode::Replay { responses, .. } => responses.get(&key).cloned().ok_or_else(no_recording),
+        }
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+        let key = request_key(&request);
+        match &self.mode {
+            Mode::Record { inner, file } => {
+                let stream = inner.chat_stream(request.clone()).await?;
+                let file = Arc::clone(file);
+                let mut entry = Some(ReplayEntry {
+                    key,
+                    request: redact_credentials(request),
+                    response: None,
+                    events: Some(Vec::new()),
+                });
+                // The exchange is written once `Done` arrives; streams that fail
+                // midway are not recorded.
+                Ok(Box::pin(stream.map(move |item| {
+                    let event = item?;
+                    if let Some(current) = entry.as_mut() {
+                        current
+                            .events
+                            .get_or_insert_with(Vec::new)
+                            .push(event.clone());
+                    }
+                    if matches!(event, StreamEvent::Done) {
+                        if let Some(finished) = entry.take() {
+                            append_entry(&file, &finished)?;
+                        }
+                    }
+                    Ok(event)
+                })))
+            }
+            Mode::Replay { streams, .. } => {
+                let events = streams.get(&key).cloned().ok_or_else(no_recording)?;
+                Ok(Box::pin(futures_util::stream::iter(
+                    events.into_iter().map(Ok),
+                )))
+            }
+        }
+    }
+}
+
+/// Canonical hash of a request's model, system prompt, messages, tools and
+/// the settings that shape the response (`tool_choice`, `response_format`,
+/// `n`). Object keys are sorted first, so field order does not affect the
+/// key; sampling parameters and metadata are ignored.
+pub fn request_key(request: &ChatRequest) -> String {
+    let mut identity = json!({
+        "model": request.model,
+        "messages": request.messages,
+        "tools": request.tools,
+    });
+    // Only keyed when set, so recordings made before the fields existed still match.
+    if let Some(system) = &request.system {
+        identity["system"] = json!(system);
+    }
+    if let Some(tool_choice) = &request.tool_choice {
+        identity["tool_choice"] = json!(tool_choice);
+    }
+    if let Some(response_format) = &request.response_format {
+        identity["response_format"] = json!(response_format);
+    }
+    if let Some(n) = request.n {
+        identity["n"] = json!(n);
+    }
+    let canonical = canonicalize(identity).to_string();
+    // FNV-1a keeps keys stable across Rust releases, unlike `DefaultHasher`.
+    let hash = canonical
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{hash:016x}")
+}
+
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, canonicalize(value)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        other => other,
+    }
+}
+
+/// Masks credential headers so recordings can be committed alongside tests.
+fn redact_credentials(mut request: ChatRequest) -> ChatRequest {
+    for (name, value) in request.extra_headers.iter_mut() {
+        if is_credential_header(name) {
+            *value = "<redacted>".to_string();
+        }
+    }
+    request
+}
+
+fn append_entry(file: &Mutex<File>, entry: &ReplayEntry) -> Result<(), ForgeError> {
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| ForgeError::Internal(format!("failed to encode replay entry: {e}")))?;
+    line.push('\n');
+    let mut file = file
+        .lock()
+        .map_err(|_| ForgeError::Internal("replay file lock poisoned".to_string()))?;
+    file.write_all(line.as_bytes())
+        .map_err(|e| ForgeError::Internal(format!("failed to write replay entry: {e}")))
+}
+
+fn no_recording() -> ForgeError {
+    ForgeError::Internal("no recorded response".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forgeai_core::{Message, ResponseFormat, Role, ToolChoice, ToolDefinition};
+    use futures_util::stream;
+
+    struct EchoAdapter;
+
+    #[async_trait]
+    impl ChatAdapter for EchoAdapter {
+        fn info(&self) -> AdapterInfo {
+            AdapterInfo {
+                name: "echo".to_string(),
+                base_url: None,
+                capabilities: CapabilityMatrix {
+                    streaming: true,
+                    tools: true,
+                    structured_output: false,
+                    multimodal_input: false,
+                    citations: false,
+                },
+            }
+        }
+
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+            Ok(ChatResponse {
+                id: "resp-1".to_string(),
+                model: request.model,
+                output_text: request.messages[0].content.text(),
+                ..Default::default()
+            })
+        }
+
+        async fn chat_stream(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+            Ok(Box::pin(stream::iter(vec![
+                Ok(StreamEvent::TextDelta {
+                    delta: "Hel".to_string(),
+                }),
+                Ok(StreamEvent::TextDelta {
+                    delta: "lo".to_string(),
+                }),
+                Ok(StreamEvent::Done),
+            ])))
+        }
+    }
+
+    fn sample_request(schema: Value) -> ChatRequest {
+        ChatRequest {
+            model: "echo-1".to_string(),
+            messages: vec![Message::new(Role::User, "hello")],
+            system: None,
+            temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            max_tokens: None,
+            n: None,
+            seed: None,
+            stop: vec![],
+            tools: vec![ToolDefinition {
+                name: "lookup".to_string(),
+                description: None,
+                input_schema: schema,
+                raw: None,
+            }],
+            tool_choice: None,
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::new(),
+            metadata: json!({}),
+        }
+    }
+
+    fn replay_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "forgeai-replay-{}-{name}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_chat_and_stream() {
+        let path = replay_path("roundtrip");
+        let recorder = ReplayAdapter::record(Arc::new(EchoAdapter), &path).unwrap();
+        let schema = json!({"type": "object", "properties": {"q": {"type": "string"}}});
+        recorder.chat(sample_request(schema.clone())).await.unwrap();
+        let recorded: Vec<_> = recorder
+            .chat_stream(sample_request(schema))
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(recorded.len(), 3);
+
+        let replayer = ReplayAdapter::replay(&path).unwrap();
+        // Same request with object keys in a different order.
+        let reordered = json!({"properties": {"q": {"type": "string"}}, "type": "object"});
+        let response = replayer
+            .chat(sample_request(reordered.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.output_text, "hello");
+
+        let events: Vec<_> = replayer
+            .chat_stream(sample_request(reordered))
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert!(matches!(&events[0], StreamEvent::TextDelta { delta } if delta == "Hel"));
+        assert!(matches!(&events[1], StreamEvent::TextDelta { delta } if delta == "lo"));
+        assert!(matches!(events[2], StreamEvent::Done));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn recordings_redact_credential_headers() {
+        let path = replay_path("credentials");
+        let recorder = ReplayAdapter::record(Arc::new(EchoAdapter), &path).unwrap();
+        let mut request = sample_request(json!({"type": "object"}));
+        request.extra_headers = HashMap::from([
+            ("Authorization".to_string(), "Bearer sk-test".to_string()),
+            ("X-Trace".to_string(), "abc".to_string()),
+        ]);
+        recorder.chat(request.clone()).await.unwrap();
+
+        let recording = std::fs::read_to_string(&path).unwrap();
+        assert!(!recording.contains("sk-test"));
+        let entry: ReplayEntry = serde_json::from_str(recording.trim()).unwrap();
+        assert_eq!(entry.request.extra_headers["Authorization"], "<redacted>");
+        assert_eq!(entry.request.extra_headers["X-Trace"], "abc");
+
+        // The key ignores headers, so the original request still replays.
+        let replayer = ReplayAdapter::replay(&path).unwrap();
+        assert_eq!(replayer.chat(request).await.unwrap().output_text, "hello");
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn response_shaping_settings_change_the_key() {
+        let base = sample_request(json!({"type": "object"}));
+        let key = request_key(&base);
+
+        let mut with_choice = base.clone();
+        with_choice.tool_choice = Some(ToolChoice::Required);
+        let mut with_format = base.clone();
+        with_format.response_format = Some(ResponseFormat::JsonObject);
+        let mut with_n = base;
+        with_n.n = Some(2);
+
+        let keys = [
+            request_key(&with_choice),
+            request_key(&with_format),
+            request_key(&with_n),
+        ];
+        assert!(keys.iter().all(|other| *other != key));
+        assert_ne!(keys[0], keys[1]);
+    }
+
+    #[tokio::test]
+    async fn unmatched_request_returns_internal_error() {
+        let path = replay_path("unmatched");
+        std::fs::write(&path, "").unwrap();
+        let replayer = ReplayAdapter::replay(&path).unwrap();
+
+        let err = replayer
+            .chat(sample_request(json!({"type": "object"})))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ForgeError::Internal(ref m) if m == "no recorded response"));
+
+        let _ = std::fs::remove_file(&path);
+    }
 }
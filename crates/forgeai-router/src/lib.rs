@@ -2,7 +2,16 @@ use async_trait::async_trait;
 use forgeai_core::{
     AdapterInfo, ChatAdapter, ChatRequest, ChatResponse, ForgeError, StreamEvent, StreamResult,
 };
-use std::sync::Arc;
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// `ChatRequest.metadata` key naming the adapter (by `AdapterInfo::name`) that must
+/// serve the request, e.g. `{"provider": "anthropic"}`.
+pub const PROVIDER_HINT_KEY: &str = "provider";
 
 pub fn pick_first_healthy(adapters: &[AdapterInfo]) -> Option<&AdapterInfo> {
     adapters.first()
@@ -43,9 +52,80 @@ impl FailoverRouter {
         Ok(Self { adapters, policy })
     }
 
-    fn adapters_to_try(&self) -> impl Iterator<Item = &Arc<dyn ChatAdapter>> {
-        self.adapters.iter().take(self.policy.max_adapters_to_try)
+    /// Adapters to try for `request`, in order. A provider hint in the request
+    /// metadata overrides the configured ordering and pins the named adapter.
+    fn adapters_to_try(
+        &self,
+        request: &ChatRequest,
+    ) -> Result<Vec<&Arc<dyn ChatAdapter>>, ForgeError> {
+        if let Some(index) = pinned_adapter(&self.adapters, request)? {
+            return Ok(vec![&self.adapters[index]]);
+        }
+        Ok(self
+            .adapters
+            .iter()
+            .take(self.policy.max_adapters_to_try)
+            .collect())
+    }
+}
+
+/// Index of the adapter named by the request's provider hint, if any.
+fn pinned_adapter(
+    adapters: &[Arc<dyn ChatAdapter>],
+    request: &ChatRequest,
+) -> Result<Option<usize>, ForgeError> {
+    let Some(provider) = request
+        .metadata
+        .get(PROVIDER_HINT_KEY)
+        .and_then(|v| v.as_str())
+    else {
+        return Ok(None);
+    };
+    adapters
+        .iter()
+        .position(|adapter| adapter.info().name == provider)
+        .map(Some)
+        .ok_or_else(|| {
+            ForgeError::Validation(format!("no adapter named '{provider}' for provider hint"))
+        })
+}
+
+async fn chat_in_order(
+    adapters: Vec<&Arc<dyn ChatAdapter>>,
+    request: ChatRequest,
+) -> Result<ChatResponse, ForgeError> {
+    let mut last_error: Option<ForgeError> = None;
+    for adapter in adapters {
+        match adapter.chat(request.clone()).await {
+            Ok(response) => return Ok(response),
+            Err(error) if should_failover(&error) => {
+                last_error = Some(error);
+            }
+            Err(error) => return Err(error),
+        }
     }
+    Err(last_error.unwrap_or_else(|| {
+        ForgeError::Internal("failover router exhausted adapters without error".to_string())
+    }))
+}
+
+async fn chat_stream_in_order(
+    adapters: Vec<&Arc<dyn ChatAdapter>>,
+    request: ChatRequest,
+) -> Result<StreamResult<StreamEvent>, ForgeError> {
+    let mut last_error: Option<ForgeError> = None;
+    for adapter in adapters {
+        match adapter.chat_stream(request.clone()).await {
+            Ok(stream) => return Ok(stream),
+            Err(error) if should_failover(&error) => {
+                last_error = Some(error);
+            }
+            Err(error) => return Err(error),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        ForgeError::Internal("failover router exhausted adapters without error".to_string())
+    }))
 }
 
 #[async_trait]
@@ -59,19 +139,270 @@ impl ChatAdapter for FailoverRouter {
         }
     }
 
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        chat_in_order(self.adapters_to_try(&request)?, request).await
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+        chat_stream_in_order(self.adapters_to_try(&request)?, request).await
+    }
+}
+
+/// How `LoadBalancedRouter` picks the first adapter for each request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Strategy {
+    /// Cycle through adapters in order.
+    RoundRobin,
+    /// Pick randomly, using one weight per adapter as relative probabilities.
+    Weighted(Vec<u32>),
+}
+
+/// Spreads requests across adapters according to a `Strategy`. When the picked
+/// adapter fails with a retryable error, the remaining adapters are tried in
+/// order after it. Provider hints pin a request as with `FailoverRouter`.
+pub struct LoadBalancedRouter {
+    adapters: Vec<Arc<dyn ChatAdapter>>,
+    strategy: Strategy,
+    next: AtomicUsize,
+}
+
+impl LoadBalancedRouter {
+    pub fn new(
+        adapters: Vec<Arc<dyn ChatAdapter>>,
+        strategy: Strategy,
+    ) -> Result<Self, ForgeError> {
+        if adapters.is_empty() {
+            return Err(ForgeError::Validation(
+                "load-balanced router requires at least one adapter".to_string(),
+            ));
+        }
+        if let Strategy::Weighted(weights) = &strategy {
+            if weights.len() != adapters.len() {
+                return Err(ForgeError::Validation(format!(
+                    "expected {} weights, got {}",
+                    adapters.len(),
+                    weights.len()
+                )));
+            }
+            if weights.iter().all(|weight| *weight == 0) {
+                return Err(ForgeError::Validation(
+                    "at least one weight must be positive".to_string(),
+                ));
+            }
+        }
+        Ok(Self {
+            adapters,
+            strategy,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    fn pick(&self) -> usize {
+        match &self.strategy {
+            Strategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % self.adapters.len(),
+            Strategy::Weighted(weights) => {
+                let total: u64 = weights.iter().map(|weight| u64::from(*weight)).sum();
+                let mut roll = RandomState::new().build_hasher().finish() % total;
+                weights
+                    .iter()
+                    .position(|weight| {
+                        let weight = u64::from(*weight);
+                        if roll < weight {
+                            return true;
+                        }
+                        roll -= weight;
+                        false
+                    })
+                    .unwrap_or(0)
+            }
+        }
+    }
+
+    fn adapters_to_try(
+        &self,
+        request: &ChatRequest,
+    ) -> Result<Vec<&Arc<dyn ChatAdapter>>, ForgeError> {
+        if let Some(index) = pinned_adapter(&self.adapters, request)? {
+            return Ok(vec![&self.adapters[index]]);
+        }
+        let start = self.pick();
+        Ok(self.adapters[start..]
+            .iter()
+            .chain(&self.adapters[..start])
+            .collect())
+    }
+}
+
+#[async_trait]
+impl ChatAdapter for LoadBalancedRouter {
+    fn info(&self) -> AdapterInfo {
+        let first = self.adapters[0].info();
+        AdapterInfo {
+            name: "load-balanced-router".to_string(),
+            base_url: first.base_url,
+            capabilities: first.capabilities,
+        }
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        chat_in_order(self.adapters_to_try(&request)?, request).await
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+        chat_stream_in_order(self.adapters_to_try(&request)?, request).await
+    }
+}
+
+/// When `HealthCheckedRouter` takes an adapter out of rotation.
+#[derive(Debug, Clone, Copy)]
+pub struct HealthPolicy {
+    /// Consecutive retryable failures after which an adapter is marked unhealthy.
+    pub failure_threshold: u32,
+    /// How long an unhealthy adapter is skipped before it is probed again.
+    pub cooldown: Duration,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct AdapterHealth {
+    consecutive_failures: u32,
+    unhealthy_since: Option<Instant>,
+}
+
+/// Failover router that tracks adapter health. An adapter that fails
+/// `failure_threshold` times in a row is skipped for `cooldown`; the next
+/// request after that acts as a probe and a success re-admits it. A failed
+/// probe restarts the cooldown.
+pub struct HealthCheckedRouter {
+    adapters: Vec<Arc<dyn ChatAdapter>>,
+    policy: HealthPolicy,
+    health: Mutex<Vec<AdapterHealth>>,
+}
+
+impl HealthCheckedRouter {
+    pub fn new(adapters: Vec<Arc<dyn ChatAdapter>>) -> Result<Self, ForgeError> {
+        Self::with_policy(adapters, HealthPolicy::default())
+    }
+
+    pub fn with_policy(
+        adapters: Vec<Arc<dyn ChatAdapter>>,
+        policy: HealthPolicy,
+    ) -> Result<Self, ForgeError> {
+        if adapters.is_empty() {
+            return Err(ForgeError::Validation(
+                "health-checked router requires at least one adapter".to_string(),
+            ));
+        }
+        let health = adapters.iter().map(|_| AdapterHealth::default()).collect();
+        Ok(Self {
+            adapters,
+            policy,
+            health: Mutex::new(health),
+        })
+    }
+
+    /// Adapter names paired with whether they are currently healthy. Adapters
+    /// waiting out or past their cooldown stay unhealthy until a probe succeeds.
+    pub fn health_snapshot(&self) -> Vec<(String, bool)> {
+        let health = self.lock_health();
+        self.adapters
+            .iter()
+            .zip(health.iter())
+            .map(|(adapter, state)| (adapter.info().name, state.unhealthy_since.is_none()))
+            .collect()
+    }
+
+    fn lock_health(&self) -> std::sync::MutexGuard<'_, Vec<AdapterHealth>> {
+        // Health bookkeeping cannot be left inconsistent by a panic, so a
+        // poisoned lock is safe to reuse.
+        self.health
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Indices of adapters to try for `request`, skipping adapters in cooldown.
+    fn adapters_to_try(&self, request: &ChatRequest) -> Result<Vec<usize>, ForgeError> {
+        if let Some(index) = pinned_adapter(&self.adapters, request)? {
+            return Ok(vec![index]);
+        }
+        let now = Instant::now();
+        let health = self.lock_health();
+        let available: Vec<usize> = health
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| match state.unhealthy_since {
+                None => true,
+                Some(since) => now.duration_since(since) >= self.policy.cooldown,
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if available.is_empty() {
+            return Err(ForgeError::Transport(
+                "no healthy adapters available".to_string(),
+            ));
+        }
+        Ok(available)
+    }
+
+    fn record_success(&self, index: usize) {
+        let mut health = self.lock_health();
+        health[index] = AdapterHealth::default();
+    }
+
+    fn record_failure(&self, index: usize) {
+        let mut health = self.lock_health();
+        let state = &mut health[index];
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.consecutive_failures >= self.policy.failure_threshold {
+            state.unhealthy_since = Some(Instant::now());
+        }
+    }
+}
+
+#[async_trait]
+impl ChatAdapter for HealthCheckedRouter {
+    fn info(&self) -> AdapterInfo {
+        let first = self.adapters[0].info();
+        AdapterInfo {
+            name: "health-checked-router".to_string(),
+            base_url: first.base_url,
+            capabilities: first.capabilities,
+        }
+    }
+
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
         let mut last_error: Option<ForgeError> = None;
-        for adapter in self.adapters_to_try() {
-            match adapter.chat(request.clone()).await {
-                Ok(response) => return Ok(response),
+        for index in self.adapters_to_try(&request)? {
+            match self.adapters[index].chat(request.clone()).await {
+                Ok(response) => {
+                    self.record_success(index);
+                    return Ok(response);
+                }
                 Err(error) if should_failover(&error) => {
+                    self.record_failure(index);
                     last_error = Some(error);
                 }
                 Err(error) => return Err(error),
             }
         }
         Err(last_error.unwrap_or_else(|| {
-            ForgeError::Internal("failover router exhausted adapters without error".to_string())
+            ForgeError::Internal(
+                "health-checked router exhausted adapters without error".to_string(),
+            )
         }))
     }
 
@@ -80,25 +411,116 @@ impl ChatAdapter for FailoverRouter {
         request: ChatRequest,
     ) -> Result<StreamResult<StreamEvent>, ForgeError> {
         let mut last_error: Option<ForgeError> = None;
-        for adapter in self.adapters_to_try() {
-            match adapter.chat_stream(request.clone()).await {
-                Ok(stream) => return Ok(stream),
+        for index in self.adapters_to_try(&request)? {
+            match self.adapters[index].chat_stream(request.clone()).await {
+                Ok(stream) => {
+                    self.record_success(index);
+                    return Ok(stream);
+                }
                 Err(error) if should_failover(&error) => {
+                    self.record_failure(index);
                     last_error = Some(error);
                 }
                 Err(error) => return Err(error),
             }
         }
         Err(last_error.unwrap_or_else(|| {
-            ForgeError::Internal("failover router exhausted adapters without error".to_string())
+            ForgeError::Internal(
+                "health-checked router exhausted adapters without error".to_string(),
+            )
         }))
     }
 }
 
+/// Sends each request to `primary` and, if it has not answered within
+/// `hedge_delay`, also to `backup`. The first successful answer wins and the
+/// other request is dropped. A retryable failure from either side falls back
+/// to the other, launching the backup early if the primary fails first.
+pub struct HedgingRouter {
+    primary: Arc<dyn ChatAdapter>,
+    backup: Arc<dyn ChatAdapter>,
+    hedge_delay: Duration,
+}
+
+impl HedgingRouter {
+    pub fn new(
+        primary: Arc<dyn ChatAdapter>,
+        backup: Arc<dyn ChatAdapter>,
+        hedge_delay: Duration,
+    ) -> Self {
+        Self {
+            primary,
+            backup,
+            hedge_delay,
+        }
+    }
+}
+
+#[async_trait]
+impl ChatAdapter for HedgingRouter {
+    fn info(&self) -> AdapterInfo {
+        let primary = self.primary.info();
+        AdapterInfo {
+            name: "hedging-router".to_string(),
+            base_url: primary.base_url,
+            capabilities: primary.capabilities,
+        }
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        hedged(
+            self.primary.chat(request.clone()),
+            self.backup.chat(request),
+            self.hedge_delay,
+        )
+        .await
+    }
+
+    async fn chat_stream(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+        hedged(
+            self.primary.chat_stream(request.clone()),
+            self.backup.chat_stream(request),
+            self.hedge_delay,
+        )
+        .await
+    }
+}
+
+/// Races `primary` against `backup`, starting `backup` only after `delay` or
+/// once `primary` fails with a retryable error.
+async fn hedged<T>(
+    primary: impl Future<Output = Result<T, ForgeError>>,
+    backup: impl Future<Output = Result<T, ForgeError>>,
+    delay: Duration,
+) -> Result<T, ForgeError> {
+    tokio::pin!(primary);
+    tokio::pin!(backup);
+    tokio::select! {
+        result = &mut primary => match result {
+            Err(error) if should_failover(&error) => return backup.await,
+            other => return other,
+        },
+        _ = tokio::time::sleep(delay) => {}
+    }
+    tokio::select! {
+        result = &mut primary => match result {
+            Err(error) if should_failover(&error) => backup.await,
+            other => other,
+        },
+        result = &mut backup => match result {
+            Err(error) if should_failover(&error) => primary.await,
+            other => other,
+        },
+    }
+}
+
 fn should_failover(error: &ForgeError) -> bool {
     matches!(
         error,
-        ForgeError::RateLimited | ForgeError::Transport(_) | ForgeError::Provider(_)
+        ForgeError::RateLimited { .. } | ForgeError::Transport(_) | ForgeError::Provider(_)
     )
 }
 
@@ -106,6 +528,8 @@ fn should_failover(error: &ForgeError) -> bool {
 mod tests {
     use super::*;
     use forgeai_core::{CapabilityMatrix, Message, Role};
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicBool;
 
     struct MockAdapter {
         name: String,
@@ -135,7 +559,9 @@ mod tests {
                     Err(ForgeError::Validation(message.clone()))
                 }
                 Err(ForgeError::Authentication) => Err(ForgeError::Authentication),
-                Err(ForgeError::RateLimited) => Err(ForgeError::RateLimited),
+                Err(ForgeError::RateLimited { retry_after }) => Err(ForgeError::RateLimited {
+                    retry_after: *retry_after,
+                }),
                 Err(ForgeError::Provider(message)) => Err(ForgeError::Provider(message.clone())),
                 Err(ForgeError::Transport(message)) => Err(ForgeError::Transport(message.clone())),
                 Err(ForgeError::Internal(message)) => Err(ForgeError::Internal(message.clone())),
@@ -147,7 +573,7 @@ mod tests {
             _request: ChatRequest,
         ) -> Result<StreamResult<StreamEvent>, ForgeError> {
             Err(ForgeError::Provider(
-                "stream tests are out of scope for this unit test".to_string(),
+                "stream tests are out of scope for this unit test".into(),
             ))
         }
     }
@@ -155,13 +581,22 @@ mod tests {
     fn request() -> ChatRequest {
         ChatRequest {
             model: "mock".to_string(),
-            messages: vec![Message {
-                role: Role::User,
-                content: "hello".to_string(),
-            }],
+            messages: vec![Message::new(Role::User, "hello")],
+            system: None,
             temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             max_tokens: None,
+            n: None,
+            seed: None,
+            stop: vec![],
             tools: vec![],
+            tool_choice: None,
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::new(),
             metadata: serde_json::json!({}),
         }
     }
@@ -181,6 +616,7 @@ mod tests {
                     output_text: "ok".to_string(),
                     tool_calls: vec![],
                     usage: None,
+                    ..Default::default()
                 }),
             }),
         ])
@@ -205,6 +641,7 @@ mod tests {
                     output_text: "should not be used".to_string(),
                     tool_calls: vec![],
                     usage: None,
+                    ..Default::default()
                 }),
             }),
         ])
@@ -213,4 +650,317 @@ mod tests {
         let err = router.chat(request()).await.unwrap_err();
         assert!(matches!(err, ForgeError::Authentication));
     }
+
+    #[tokio::test]
+    async fn router_honors_provider_hint_in_metadata() {
+        let router = FailoverRouter::new(vec![
+            Arc::new(MockAdapter {
+                name: "openai".to_string(),
+                result: Ok(ChatResponse {
+                    id: "1".to_string(),
+                    model: "mock".to_string(),
+                    output_text: "from openai".to_string(),
+                    tool_calls: vec![],
+                    usage: None,
+                    ..Default::default()
+                }),
+            }),
+            Arc::new(MockAdapter {
+                name: "anthropic".to_string(),
+                result: Ok(ChatResponse {
+                    id: "2".to_string(),
+                    model: "mock".to_string(),
+                    output_text: "from anthropic".to_string(),
+                    tool_calls: vec![],
+                    usage: None,
+                    ..Default::default()
+                }),
+            }),
+        ])
+        .unwrap();
+
+        let mut hinted = request();
+        hinted.metadata = serde_json::json!({"provider": "anthropic"});
+        let response = router.chat(hinted).await.unwrap();
+        assert_eq!(response.output_text, "from anthropic");
+
+        let response = router.chat(request()).await.unwrap();
+        assert_eq!(response.output_text, "from openai");
+    }
+
+    struct CountingAdapter {
+        name: String,
+        fail: AtomicBool,
+        calls: AtomicUsize,
+    }
+
+    impl CountingAdapter {
+        fn new(name: &str, fail: bool) -> Arc<Self> {
+            Arc::new(Self {
+                name: name.to_string(),
+                fail: AtomicBool::new(fail),
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl ChatAdapter for CountingAdapter {
+        fn info(&self) -> AdapterInfo {
+            AdapterInfo {
+                name: self.name.clone(),
+                base_url: None,
+                capabilities: CapabilityMatrix {
+                    streaming: false,
+                    tools: false,
+                    structured_output: false,
+                    multimodal_input: false,
+                    citations: false,
+                },
+            }
+        }
+
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(ForgeError::Transport("unavailable".to_string()));
+            }
+            Ok(ChatResponse {
+                output_text: self.name.clone(),
+                ..Default::default()
+            })
+        }
+
+        async fn chat_stream(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+            Err(ForgeError::Provider("not streamed".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn round_robin_spreads_requests_evenly() {
+        let adapters = [
+            CountingAdapter::new("a", false),
+            CountingAdapter::new("b", false),
+            CountingAdapter::new("c", false),
+        ];
+        let router = Arc::new(
+            LoadBalancedRouter::new(
+                adapters
+                    .iter()
+                    .map(|a| Arc::clone(a) as Arc<dyn ChatAdapter>)
+                    .collect(),
+                Strategy::RoundRobin,
+            )
+            .unwrap(),
+        );
+
+        let tasks: Vec<_> = (0..30)
+            .map(|_| {
+                let router = Arc::clone(&router);
+                tokio::spawn(async move { router.chat(request()).await.unwrap() })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        for adapter in &adapters {
+            assert_eq!(adapter.calls.load(Ordering::SeqCst), 10);
+        }
+    }
+
+    #[tokio::test]
+    async fn weighted_selection_follows_weights() {
+        let heavy = CountingAdapter::new("heavy", false);
+        let light = CountingAdapter::new("light", false);
+        let router = LoadBalancedRouter::new(
+            vec![heavy.clone(), light.clone()],
+            Strategy::Weighted(vec![3, 1]),
+        )
+        .unwrap();
+
+        for _ in 0..4000 {
+            router.chat(request()).await.unwrap();
+        }
+
+        let share = heavy.calls.load(Ordering::SeqCst) as f64 / 4000.0;
+        assert!((0.70..=0.80).contains(&share), "heavy share was {share}");
+    }
+
+    #[tokio::test]
+    async fn load_balancer_fails_over_from_picked_adapter() {
+        let down = CountingAdapter::new("down", true);
+        let up = CountingAdapter::new("up", false);
+        let router =
+            LoadBalancedRouter::new(vec![down.clone(), up.clone()], Strategy::RoundRobin).unwrap();
+
+        for _ in 0..4 {
+            let response = router.chat(request()).await.unwrap();
+            assert_eq!(response.output_text, "up");
+        }
+        assert_eq!(down.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn health_router_trips_after_threshold_and_skips_adapter() {
+        let flaky = CountingAdapter::new("flaky", true);
+        let backup = CountingAdapter::new("backup", false);
+        let router = HealthCheckedRouter::with_policy(
+            vec![flaky.clone(), backup.clone()],
+            HealthPolicy {
+                failure_threshold: 2,
+                cooldown: Duration::from_secs(60),
+            },
+        )
+        .unwrap();
+
+        for _ in 0..5 {
+            let response = router.chat(request()).await.unwrap();
+            assert_eq!(response.output_text, "backup");
+        }
+
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            router.health_snapshot(),
+            vec![("flaky".to_string(), false), ("backup".to_string(), true)]
+        );
+    }
+
+    #[tokio::test]
+    async fn health_router_readmits_adapter_after_successful_probe() {
+        let flaky = CountingAdapter::new("flaky", true);
+        let backup = CountingAdapter::new("backup", false);
+        let router = HealthCheckedRouter::with_policy(
+            vec![flaky.clone(), backup.clone()],
+            HealthPolicy {
+                failure_threshold: 1,
+                cooldown: Duration::from_millis(20),
+            },
+        )
+        .unwrap();
+
+        router.chat(request()).await.unwrap();
+        assert!(!router.health_snapshot()[0].1);
+
+        flaky.fail.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        let response = router.chat(request()).await.unwrap();
+        assert_eq!(response.output_text, "flaky");
+        assert!(router.health_snapshot().iter().all(|(_, healthy)| *healthy));
+    }
+
+    #[tokio::test]
+    async fn health_router_errors_when_every_adapter_is_cooling_down() {
+        let down = CountingAdapter::new("down", true);
+        let router = HealthCheckedRouter::with_policy(
+            vec![down.clone()],
+            HealthPolicy {
+                failure_threshold: 1,
+                cooldown: Duration::from_secs(60),
+            },
+        )
+        .unwrap();
+
+        assert!(router.chat(request()).await.is_err());
+        let err = router.chat(request()).await.unwrap_err();
+
+        assert!(
+            matches!(err, ForgeError::Transport(ref m) if m == "no healthy adapters available")
+        );
+        assert_eq!(down.calls.load(Ordering::SeqCst), 1);
+    }
+
+    struct DelayedAdapter {
+        name: String,
+        delay: Duration,
+        calls: AtomicUsize,
+    }
+
+    impl DelayedAdapter {
+        fn new(name: &str, delay: Duration) -> Arc<Self> {
+            Arc::new(Self {
+                name: name.to_string(),
+                delay,
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl ChatAdapter for DelayedAdapter {
+        fn info(&self) -> AdapterInfo {
+            AdapterInfo {
+                name: self.name.clone(),
+                base_url: None,
+                capabilities: CapabilityMatrix {
+                    streaming: false,
+                    tools: false,
+                    structured_output: false,
+                    multimodal_input: false,
+                    citations: false,
+                },
+            }
+        }
+
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            Ok(ChatResponse {
+                output_text: self.name.clone(),
+                ..Default::default()
+            })
+        }
+
+        async fn chat_stream(
+            &self,
+            _request: ChatRequest,
+        ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+            Err(ForgeError::Provider("not streamed".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn hedge_wins_when_primary_is_slow() {
+        let primary = DelayedAdapter::new("primary", Duration::from_secs(5));
+        let backup = DelayedAdapter::new("backup", Duration::from_millis(10));
+        let router = HedgingRouter::new(primary.clone(), backup.clone(), Duration::from_millis(20));
+
+        let started = Instant::now();
+        let response = router.chat(request()).await.unwrap();
+
+        assert_eq!(response.output_text, "backup");
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn fast_primary_never_launches_hedge() {
+        let primary = DelayedAdapter::new("primary", Duration::from_millis(1));
+        let backup = DelayedAdapter::new("backup", Duration::from_millis(1));
+        let router =
+            HedgingRouter::new(primary.clone(), backup.clone(), Duration::from_millis(200));
+
+        let response = router.chat(request()).await.unwrap();
+
+        assert_eq!(response.output_text, "primary");
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn failed_primary_launches_hedge_immediately() {
+        let primary = CountingAdapter::new("primary", true);
+        let backup = DelayedAdapter::new("backup", Duration::from_millis(1));
+        let router = HedgingRouter::new(primary, backup.clone(), Duration::from_secs(5));
+
+        let started = Instant::now();
+        let response = router.chat(request()).await.unwrap();
+
+        assert_eq!(response.output_text, "backup");
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
 }
@@ -1,8 +1,147 @@
-use serde::Serialize;
-use serde_json::{json, Value};
+//! JSON Schema generation for tool parameters and structured output.
 
-pub fn type_schema<T: Serialize>() -> Value {
-    // Placeholder until schemars integration is added.
-    let _ = std::marker::PhantomData::<T>;
-    json!({"type": "object"})
+use forgeai_core::{ForgeError, ResponseFormat, ToolDefinition};
+use jsonschema::JSONSchema;
+use schemars::generate::SchemaSettings;
+use schemars::JsonSchema;
+use serde_json::Value;
+
+pub use schemars;
+
+/// JSON Schema for `T` in the shape providers accept for tool parameters:
+/// subschemas are inlined instead of referenced and the `$schema` key is dropped.
+pub fn type_schema<T: JsonSchema>() -> Value {
+    let generator = SchemaSettings::draft07()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator();
+    let mut schema = generator.into_root_schema_for::<T>().to_value();
+    if let Value::Object(map) = &mut schema {
+        map.remove("$schema");
+    }
+    schema
+}
+
+/// Builds a `ToolDefinition` whose `input_schema` is generated from `T`.
+pub fn tool_definition<T: JsonSchema>(
+    name: impl Into<String>,
+    description: impl Into<String>,
+) -> ToolDefinition {
+    ToolDefinition {
+        name: name.into(),
+        description: Some(description.into()),
+        input_schema: type_schema::<T>(),
+        raw: None,
+    }
+}
+
+/// Builds a `ResponseFormat::JsonSchema` requesting output shaped like `T`.
+/// The format is named after `T`, reduced to the characters providers accept
+/// in schema names. `strict` is off because derived schemas rarely meet the
+/// stricter subset some providers require for it.
+pub fn response_format<T: JsonSchema>() -> ResponseFormat {
+    let name: String = T::schema_name()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    ResponseFormat::JsonSchema {
+        name,
+        schema: type_schema::<T>(),
+        strict: false,
+    }
+}
+
+/// Parses `output` as JSON and checks it against `schema`, for providers
+/// whose strict mode does not guarantee conformance. Every violation is
+/// listed in the `Validation` error.
+pub fn validate_output(schema: &Value, output: &str) -> Result<Value, ForgeError> {
+    let compiled = JSONSchema::compile(schema)
+        .map_err(|e| ForgeError::Validation(format!("invalid response schema: {e}")))?;
+    let value: Value = serde_json::from_str(output.trim())
+        .map_err(|e| ForgeError::Validation(format!("structured output is not valid JSON: {e}")))?;
+    if let Err(errors) = compiled.validate(&value) {
+        let problems: Vec<String> = errors
+            .map(|error| {
+                let path = error.instance_path.to_string();
+                if path.is_empty() {
+                    error.to_string()
+                } else {
+                    format!("{path}: {error}")
+                }
+            })
+            .collect();
+        return Err(ForgeError::Validation(format!(
+            "structured output does not match the response schema: {}",
+            problems.join("; ")
+        )));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    enum Unit {
+        Celsius,
+        Fahrenheit,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, JsonSchema)]
+    struct Location {
+        city: String,
+        country: Option<String>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, JsonSchema)]
+    struct WeatherArgs {
+        location: Location,
+        unit: Unit,
+        days: u8,
+    }
+
+    #[test]
+    fn tool_definition_inlines_nested_types() {
+        let tool = tool_definition::<WeatherArgs>("weather", "Get the forecast");
+        let schema = tool.input_schema;
+
+        assert_eq!(tool.name, "weather");
+        assert!(schema.get("$schema").is_none());
+        assert!(schema.get("definitions").is_none());
+        assert!(!schema.to_string().contains("$ref"));
+        assert_eq!(schema["type"], "object");
+        assert_eq!(
+            schema["properties"]["unit"]["enum"],
+            json!(["celsius", "fahrenheit"])
+        );
+        assert_eq!(
+            schema["properties"]["location"]["properties"]["city"]["type"],
+            "string"
+        );
+        assert_eq!(
+            schema["properties"]["location"]["required"],
+            json!(["city"])
+        );
+    }
+
+    #[test]
+    fn response_format_is_named_after_type() {
+        let ResponseFormat::JsonSchema { name, schema, .. } = response_format::<Location>() else {
+            panic!("expected a JSON schema format");
+        };
+        assert_eq!(name, "Location");
+        assert_eq!(schema["properties"]["city"]["type"], "string");
+    }
 }
@@ -1,3 +1,301 @@
 //! Stream-related helpers.
 
 pub use forgeai_core::StreamEvent;
+
+use forgeai_core::{ForgeError, StreamResult};
+use futures_util::StreamExt;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+/// Unit at which `chunk_text` flushes buffered text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushGranularity {
+    /// Pass provider deltas through unchanged.
+    Token,
+    /// Flush after each whitespace-terminated word.
+    Word,
+    /// Flush after `.`, `!` or `?` followed by whitespace.
+    Sentence,
+    /// Flush after a blank line (`\n\n`).
+    Paragraph,
+}
+
+/// Regroups the `TextDelta` events of `stream` into chunks of the given
+/// granularity. Other events pass through unchanged, after any buffered text
+/// has been flushed, and the remainder is flushed when the stream ends.
+pub fn chunk_text(
+    stream: StreamResult<StreamEvent>,
+    granularity: FlushGranularity,
+) -> StreamResult<StreamEvent> {
+    let state = ChunkState {
+        inner: stream,
+        granularity,
+        buffer: String::new(),
+        pending: VecDeque::new(),
+        finished: false,
+    };
+    Box::pin(futures_util::stream::unfold(
+        state,
+        |mut state| async move {
+            loop {
+                if let Some(item) = state.pending.pop_front() {
+                    return Some((item, state));
+                }
+                if state.finished {
+                    return None;
+                }
+                match state.inner.next().await {
+                    Some(Ok(StreamEvent::TextDelta { delta })) => state.push(&delta),
+                    Some(other) => {
+                        state.flush();
+                        state.pending.push_back(other);
+                    }
+                    None => {
+                        state.flush();
+                        state.finished = true;
+                    }
+                }
+            }
+        },
+    ))
+}
+
+/// Delays `TextDelta` events so that no more than `tokens_per_sec` are
+/// emitted per second, counting each delta as one token. Other events are not
+/// delayed. A rate that is not a positive finite number leaves the stream
+/// unchanged.
+pub fn rate_limit_output(
+    stream: StreamResult<StreamEvent>,
+    tokens_per_sec: f64,
+) -> StreamResult<StreamEvent> {
+    if !(tokens_per_sec.is_finite() && tokens_per_sec > 0.0) {
+        return stream;
+    }
+    let interval = Duration::from_secs_f64(1.0 / tokens_per_sec);
+    let mut next_at: Option<Instant> = None;
+    Box::pin(stream.then(move |item| {
+        let wait_until = match &item {
+            Ok(StreamEvent::TextDelta { .. }) => {
+                let now = Instant::now();
+                let at = next_at.map_or(now, |at| at.max(now));
+                next_at = Some(at + interval);
+                Some(at)
+            }
+            _ => None,
+        };
+        async move {
+            if let Some(at) = wait_until {
+                tokio::time::sleep_until(at).await;
+            }
+            item
+        }
+    }))
+}
+
+/// Pauses and resumes a stream returned by `pausable`. Clones control the
+/// same stream.
+#[derive(Debug, Clone)]
+pub struct PauseHandle {
+    paused: watch::Sender<bool>,
+}
+
+impl PauseHandle {
+    /// Stops pulling from the underlying stream, so an HTTP body is no
+    /// longer read and the provider connection applies backpressure. An item
+    /// already being awaited is still delivered.
+    pub fn pause(&self) {
+        self.paused.send_replace(true);
+    }
+
+    /// Continues from where the stream was paused.
+    pub fn resume(&self) {
+        self.paused.send_replace(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+}
+
+/// Wraps `stream` so it can be paused and resumed through the returned
+/// `PauseHandle`, e.g. while a read-aloud feature catches up. No items are
+/// lost; dropping every handle resumes the stream for good.
+pub fn pausable<T: Send + 'static>(stream: StreamResult<T>) -> (StreamResult<T>, PauseHandle) {
+    let (paused, receiver) = watch::channel(false);
+    let stream =
+        futures_util::stream::unfold((stream, receiver), |(mut inner, mut receiver)| async move {
+            // An error means every handle is gone, so nothing can resume it.
+            let _ = receiver.wait_for(|paused| !*paused).await;
+            let item = inner.next().await?;
+            Some((item, (inner, receiver)))
+        });
+    (Box::pin(stream), PauseHandle { paused })
+}
+
+struct ChunkState {
+    inner: StreamResult<StreamEvent>,
+    granularity: FlushGranularity,
+    buffer: String,
+    pending: VecDeque<Result<StreamEvent, ForgeError>>,
+    finished: bool,
+}
+
+impl ChunkState {
+    fn push(&mut self, delta: &str) {
+        self.buffer.push_str(delta);
+        while let Some(end) = next_boundary(self.granularity, &self.buffer) {
+            let rest = self.buffer.split_off(end);
+            let chunk = std::mem::replace(&mut self.buffer, rest);
+            self.emit(chunk);
+        }
+    }
+
+    fn flush(&mut self) {
+        let chunk = std::mem::take(&mut self.buffer);
+        self.emit(chunk);
+    }
+
+    fn emit(&mut self, delta: String) {
+        if !delta.is_empty() {
+            self.pending.push_back(Ok(StreamEvent::TextDelta { delta }));
+        }
+    }
+}
+
+/// Byte offset just past the first complete unit in `text`, if any.
+fn next_boundary(granularity: FlushGranularity, text: &str) -> Option<usize> {
+    match granularity {
+        FlushGranularity::Token => (!text.is_empty()).then_some(text.len()),
+        FlushGranularity::Word => {
+            let word_start = text.find(|c: char| !c.is_whitespace())?;
+            text[word_start..]
+                .char_indices()
+                .find(|(_, c)| c.is_whitespace())
+                .map(|(offset, c)| word_start + offset + c.len_utf8())
+        }
+        FlushGranularity::Sentence => {
+            let mut chars = text.char_indices().peekable();
+            while let Some((_, c)) = chars.next() {
+                if matches!(c, '.' | '!' | '?') {
+                    if let Some(&(index, next)) = chars.peek() {
+                        if next.is_whitespace() {
+                            return Some(index + next.len_utf8());
+                        }
+                    }
+                }
+            }
+            None
+        }
+        FlushGranularity::Paragraph => text.find("\n\n").map(|index| index + 2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+
+    fn deltas(parts: &[&str]) -> StreamResult<StreamEvent> {
+        let mut events: Vec<Result<StreamEvent, ForgeError>> = parts
+            .iter()
+            .map(|part| {
+                Ok(StreamEvent::TextDelta {
+                    delta: part.to_string(),
+                })
+            })
+            .collect();
+        events.push(Ok(StreamEvent::Done));
+        Box::pin(stream::iter(events))
+    }
+
+    async fn collect_text(stream: StreamResult<StreamEvent>) -> Vec<String> {
+        stream
+            .filter_map(|item| async move {
+                match item.unwrap() {
+                    StreamEvent::TextDelta { delta } => Some(delta),
+                    _ => None,
+                }
+            })
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn word_granularity_flushes_whole_words() {
+        let chunks = collect_text(chunk_text(
+            deltas(&["Hel", "lo wo", "rld, how", " are you"]),
+            FlushGranularity::Word,
+        ))
+        .await;
+
+        assert_eq!(chunks, vec!["Hello ", "world, ", "how ", "are ", "you"]);
+    }
+
+    #[tokio::test]
+    async fn paragraph_granularity_flushes_on_blank_lines() {
+        let chunks = collect_text(chunk_text(
+            deltas(&["First line.\nStill first", ".\n", "\nSecond", " paragraph."]),
+            FlushGranularity::Paragraph,
+        ))
+        .await;
+
+        assert_eq!(
+            chunks,
+            vec!["First line.\nStill first.\n\n", "Second paragraph."]
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limit_spaces_deltas() {
+        let started = Instant::now();
+        let chunks =
+            collect_text(rate_limit_output(deltas(&["a", "b", "c", "d", "e"]), 50.0)).await;
+
+        assert_eq!(chunks, vec!["a", "b", "c", "d", "e"]);
+        // The first delta is immediate, the other four wait 20ms each.
+        assert!(started.elapsed() >= Duration::from_millis(80));
+    }
+
+    #[tokio::test]
+    async fn paused_stream_yields_nothing_until_resumed() {
+        let pulled = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = pulled.clone();
+        let inner: StreamResult<StreamEvent> =
+            Box::pin(deltas(&["a", "b", "c"]).inspect(move |_| {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }));
+        let (mut stream, handle) = pausable(inner);
+
+        assert!(
+            matches!(stream.next().await, Some(Ok(StreamEvent::TextDelta { delta })) if delta == "a")
+        );
+        handle.pause();
+        assert!(handle.is_paused());
+        let waited = tokio::time::timeout(Duration::from_millis(50), stream.next()).await;
+        assert!(waited.is_err());
+        assert_eq!(pulled.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        handle.resume();
+        let rest = collect_text(stream).await;
+        assert_eq!(rest, vec!["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn buffered_text_is_flushed_before_other_events() {
+        let events: Vec<_> = chunk_text(
+            deltas(&["Pi is 3.14 roughly. Next"]),
+            FlushGranularity::Sentence,
+        )
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+        assert!(
+            matches!(&events[0], StreamEvent::TextDelta { delta } if delta == "Pi is 3.14 roughly. ")
+        );
+        assert!(matches!(&events[1], StreamEvent::TextDelta { delta } if delta == "Next"));
+        assert!(matches!(events[2], StreamEvent::Done));
+    }
+}
@@ -1,4 +1,11 @@
+use async_trait::async_trait;
+use forgeai_core::ToolDefinition;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::Value;
+use std::collections::HashMap;
+use std::future::{self, Future};
+use std::pin::Pin;
 
 #[derive(Debug, thiserror::Error)]
 pub enum ToolError {
@@ -11,3 +18,141 @@ pub enum ToolError {
 pub trait ToolExecutor: Send + Sync {
     fn call(&self, name: &str, input: Value) -> Result<Value, ToolError>;
 }
+
+/// Tool executor for tools that perform I/O. Every `ToolExecutor` is also an
+/// `AsyncToolExecutor`, so synchronous tools work wherever async ones are accepted.
+/// The method is named `call_async` so `call` stays unambiguous on types that
+/// implement both traits.
+#[async_trait]
+pub trait AsyncToolExecutor: Send + Sync {
+    async fn call_async(&self, name: &str, input: Value) -> Result<Value, ToolError>;
+}
+
+#[async_trait]
+impl<T: ToolExecutor + ?Sized> AsyncToolExecutor for T {
+    async fn call_async(&self, name: &str, input: Value) -> Result<Value, ToolError> {
+        self.call(name, input)
+    }
+}
+
+type ToolFuture = Pin<Box<dyn Future<Output = Result<Value, ToolError>> + Send>>;
+type ToolFn = Box<dyn Fn(Value) -> ToolFuture + Send + Sync>;
+
+/// `AsyncToolExecutor` that dispatches to tools registered by name.
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: HashMap<String, ToolFn>,
+    definitions: HashMap<String, ToolDefinition>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `tool` under `name`, replacing any tool already registered
+    /// with that name.
+    pub fn register<F>(&mut self, name: impl Into<String>, tool: F) -> &mut Self
+    where
+        F: Fn(Value) -> Result<Value, ToolError> + Send + Sync + 'static,
+    {
+        let name = name.into();
+        self.definitions.remove(&name);
+        self.tools.insert(
+            name,
+            Box::new(move |input| Box::pin(future::ready(tool(input)))),
+        );
+        self
+    }
+
+    /// Registers the async `tool` under `definition.name`, keeping
+    /// `definition` for `definitions`. Arguments are deserialized into `A`,
+    /// failing with `ToolError::Execution` when they do not match, and the
+    /// output is serialized back to JSON. `#[forge_tool]` generates this call.
+    pub fn register_tool<A, O, F, Fut>(&mut self, definition: ToolDefinition, tool: F) -> &mut Self
+    where
+        A: DeserializeOwned,
+        O: Serialize,
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<O, ToolError>> + Send + 'static,
+    {
+        let name = definition.name.clone();
+        let tool_name = name.clone();
+        let tool: ToolFn = Box::new(move |input| {
+            let call = serde_json::from_value::<A>(input).map(&tool).map_err(|e| {
+                ToolError::Execution(format!("invalid arguments for {tool_name}: {e}"))
+            });
+            let tool_name = tool_name.clone();
+            Box::pin(async move {
+                let output = call?.await?;
+                serde_json::to_value(output).map_err(|e| {
+                    ToolError::Execution(format!("failed to serialize {tool_name} output: {e}"))
+                })
+            })
+        });
+        self.tools.insert(name.clone(), tool);
+        self.definitions.insert(name, definition);
+        self
+    }
+
+    /// Names of the registered tools, sorted, for building `ChatRequest.tools`.
+    pub fn tool_definitions(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.tools.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Definitions of the tools added with `register_tool`, sorted by name,
+    /// ready for `ChatRequest.tools`.
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        let mut definitions: Vec<ToolDefinition> = self.definitions.values().cloned().collect();
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        definitions
+    }
+}
+
+#[async_trait]
+impl AsyncToolExecutor for ToolRegistry {
+    async fn call_async(&self, name: &str, input: Value) -> Result<Value, ToolError> {
+        let tool = self
+            .tools
+            .get(name)
+            .ok_or_else(|| ToolError::NotFound(name.to_string()))?;
+        tool(input).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn registry_dispatches_by_name() {
+        let mut registry = ToolRegistry::new();
+        registry
+            .register("echo", |input| Ok(json!({ "echo": input })))
+            .register("add", |input| {
+                let a = input["a"].as_i64().unwrap_or_default();
+                let b = input["b"].as_i64().unwrap_or_default();
+                Ok(json!(a + b))
+            });
+
+        assert_eq!(
+            registry.call_async("echo", json!("hi")).await.unwrap(),
+            json!({"echo": "hi"})
+        );
+        assert_eq!(
+            registry
+                .call_async("add", json!({"a": 2, "b": 3}))
+                .await
+                .unwrap(),
+            json!(5)
+        );
+        assert!(matches!(
+            registry.call_async("missing", json!({})).await,
+            Err(ToolError::NotFound(name)) if name == "missing"
+        ));
+        assert_eq!(registry.tool_definitions(), vec!["add", "echo"]);
+    }
+}
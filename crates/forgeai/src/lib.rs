@@ -1,26 +1,302 @@
 //! High-level forgeai SDK.
 
+mod limits;
+mod profiles;
+mod shutdown;
+#[cfg(feature = "tokenizer")]
+mod tokenizer;
+mod usage;
+
+pub use limits::{estimate_prompt_tokens, ModelLimits, TokenEstimator};
+pub use profiles::{ModelProfile, ModelProfileRegistry};
+use shutdown::Lifecycle;
+#[cfg(feature = "tokenizer")]
+pub use tokenizer::{count_request_tokens, count_tokens};
+pub use usage::{AtomicUsageAggregator, UsageSink};
+
+use forgeai_core::openai::message_to_openai;
 use forgeai_core::{
-    validate_request, ChatAdapter, ChatRequest, ChatResponse, ForgeError, Message, Role,
-    StreamEvent, StreamResult, ToolCall, Usage,
+    validate_request, CapabilityMatrix, ChatAdapter, ChatRequest, ChatResponse, ForgeError,
+    Message, ResponseFormat, Role, StreamEvent, StreamResult, ToolCall, ToolChoice, ToolDefinition,
+    Usage,
 };
-use forgeai_tools::ToolExecutor;
+use forgeai_schema::schemars::JsonSchema;
+use forgeai_tools::{AsyncToolExecutor, ToolError};
+use futures_util::future::join_all;
+use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+pub use tokio_util::sync::CancellationToken;
 
 pub struct Client {
     adapter: Arc<dyn ChatAdapter>,
+    usage_sink: Option<Arc<dyn UsageSink>>,
+    default_temperature: Option<f32>,
+    audit_hook: Option<AuditHook>,
+    model_profiles: ModelProfileRegistry,
+    correlation_header: Option<String>,
+    model_limits: Option<ModelLimits>,
+    lifecycle: Arc<Lifecycle>,
 }
 
+/// Events buffered by `Client::stream_to_channel` before the stream waits
+/// for the receiver.
+const STREAM_CHANNEL_CAPACITY: usize = 64;
+
+/// `ChatRequest.metadata` key holding the id sent in the header configured by
+/// `Client::with_correlation_header`, e.g. `{"correlation_id": "00-4bf9…-01"}`.
+pub const CORRELATION_ID_KEY: &str = "correlation_id";
+
+/// Receives each request exactly as it is handed to the adapter, after all
+/// client-side defaults and injected prompts have been applied.
+pub type AuditHook = Arc<dyn Fn(&ChatRequest) + Send + Sync>;
+
 impl Client {
     pub fn new(adapter: Arc<dyn ChatAdapter>) -> Self {
-        Self { adapter }
+        Self {
+            adapter,
+            usage_sink: None,
+            default_temperature: None,
+            audit_hook: None,
+            model_profiles: ModelProfileRegistry::new(),
+            correlation_header: None,
+            model_limits: None,
+            lifecycle: Lifecycle::new(),
+        }
+    }
+
+    /// Reports token usage of every successful call to `sink`.
+    pub fn with_usage_sink(mut self, sink: Arc<dyn UsageSink>) -> Self {
+        self.usage_sink = Some(sink);
+        self
+    }
+
+    /// Temperature used for requests that leave `temperature` unset.
+    pub fn with_default_temperature(mut self, temperature: f32) -> Self {
+        self.default_temperature = Some(temperature);
+        self
+    }
+
+    /// Fills parameters a request leaves unset from the profile matching its
+    /// model, ahead of `with_default_temperature`.
+    pub fn with_model_profiles(mut self, profiles: ModelProfileRegistry) -> Self {
+        self.model_profiles = profiles;
+        self
+    }
+
+    /// Sends each request's `metadata[CORRELATION_ID_KEY]` string to the
+    /// provider in the `header` header (e.g. `traceparent` or
+    /// `x-correlation-id`), unless `extra_headers` already sets it.
+    pub fn with_correlation_header(mut self, header: impl Into<String>) -> Self {
+        self.correlation_header = Some(header.into());
+        self
+    }
+
+    /// Rejects requests whose estimated prompt exceeds their model's context
+    /// window with `ForgeError::Validation`, before anything is sent.
+    pub fn with_model_limits(mut self, limits: ModelLimits) -> Self {
+        self.model_limits = Some(limits);
+        self
+    }
+
+    /// Calls `hook` with the final request of every adapter call, including
+    /// each model call of a tool loop, for audit logging.
+    pub fn with_audit_hook(mut self, hook: AuditHook) -> Self {
+        self.audit_hook = Some(hook);
+        self
     }
 
     pub async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
         validate_request(&request)?;
-        self.adapter.chat(request).await
+        let _call = self.lifecycle.begin()?;
+        self.adapter_chat(request).await
+    }
+
+    /// Stops accepting new calls and waits up to `grace` for in-flight calls
+    /// and streams to finish. Streams still open after `grace` end with an
+    /// `Internal` error; in-flight `chat` calls are left to complete. Returns
+    /// `true` if everything finished within `grace`.
+    pub async fn shutdown(&self, grace: Duration) -> bool {
+        self.lifecycle.close();
+        let drained = tokio::time::timeout(grace, self.lifecycle.wait_idle())
+            .await
+            .is_ok();
+        if !drained {
+            self.lifecycle.cancel();
+        }
+        drained
+    }
+
+    /// Capabilities of `model`: the override from its `ModelProfile` if one is
+    /// set, otherwise the adapter's static `CapabilityMatrix`.
+    pub fn capabilities(&self, model: &str) -> CapabilityMatrix {
+        self.model_profiles
+            .lookup(model)
+            .and_then(|profile| profile.capabilities.clone())
+            .unwrap_or_else(|| self.adapter.info().capabilities)
+    }
+
+    /// Probes the adapter with minimal requests against `model` to confirm the
+    /// streaming and tool support its static `CapabilityMatrix` claims. Claimed
+    /// capabilities that fail their probe are reported as `false`; the other
+    /// fields are passed through unprobed.
+    pub async fn detect_capabilities(&self, model: &str) -> CapabilityMatrix {
+        let mut capabilities = self.capabilities(model);
+        let probe = ChatRequest {
+            model: model.to_string(),
+            messages: vec![Message::new(Role::User, "ping")],
+            system: None,
+            temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            max_tokens: Some(1),
+            n: None,
+            seed: None,
+            stop: vec![],
+            tools: vec![],
+            tool_choice: None,
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::new(),
+            metadata: json!({}),
+        };
+
+        if capabilities.streaming {
+            use futures_util::StreamExt;
+            capabilities.streaming = match self.adapter.chat_stream(probe.clone()).await {
+                Ok(mut stream) => matches!(stream.next().await, Some(Ok(_))),
+                Err(_) => false,
+            };
+        }
+        if capabilities.tools {
+            let mut probe = probe;
+            probe.tools = vec![ToolDefinition {
+                name: "noop".to_string(),
+                description: Some("Does nothing.".to_string()),
+                input_schema: json!({"type": "object", "properties": {}}),
+                raw: None,
+            }];
+            capabilities.tools = self.adapter.chat(probe).await.is_ok();
+        }
+        capabilities
+    }
+
+    /// Sends `prompt` as a single user message to `model` with default
+    /// parameters and returns the output text.
+    pub async fn complete(
+        &self,
+        model: impl Into<String>,
+        prompt: impl Into<String>,
+    ) -> Result<String, ForgeError> {
+        let request = ChatRequest {
+            model: model.into(),
+            messages: vec![Message::text(Role::User, prompt)],
+            system: None,
+            temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            max_tokens: None,
+            n: None,
+            seed: None,
+            stop: vec![],
+            tools: vec![],
+            tool_choice: None,
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::new(),
+            metadata: json!({}),
+        };
+        Ok(self.chat(request).await?.output_text)
+    }
+
+    /// Extracts a `T` from `text`. The schema derived from `T` is offered as
+    /// the parameters of an `extract` tool the model is forced to call; a
+    /// plain JSON answer in `output_text` is accepted as well.
+    pub async fn extract<T: DeserializeOwned + JsonSchema>(
+        &self,
+        model: impl Into<String>,
+        text: impl Into<String>,
+    ) -> Result<T, ForgeError> {
+        let request = ChatRequest {
+            model: model.into(),
+            messages: vec![Message::text(Role::User, text)],
+            system: Some(EXTRACT_SYSTEM_PROMPT.to_string()),
+            temperature: Some(0.0),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            max_tokens: None,
+            n: None,
+            seed: None,
+            stop: vec![],
+            tools: vec![forgeai_schema::tool_definition::<T>(
+                EXTRACT_TOOL_NAME,
+                "Record the fields extracted from the user's text.",
+            )],
+            tool_choice: Some(ToolChoice::Function(EXTRACT_TOOL_NAME.to_string())),
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::new(),
+            metadata: json!({}),
+        };
+        let response = self.chat(request).await?;
+        let value = match response
+            .tool_calls
+            .into_iter()
+            .find(|call| call.name == EXTRACT_TOOL_NAME)
+        {
+            Some(call) => call.arguments,
+            None => serde_json::from_str(response.output_text.trim()).map_err(|e| {
+                ForgeError::Validation(format!("extraction response is not valid JSON: {e}"))
+            })?,
+        };
+        serde_json::from_value(value).map_err(|e| {
+            ForgeError::Validation(format!("extraction response does not match schema: {e}"))
+        })
+    }
+
+    /// Sends `request` with a JSON schema `response_format` derived from `T`
+    /// and deserializes `output_text` into `T`. A response that does not parse
+    /// fails with `ForgeError::Provider` carrying the raw text.
+    pub async fn chat_typed<T: DeserializeOwned + JsonSchema>(
+        &self,
+        mut request: ChatRequest,
+    ) -> Result<T, ForgeError> {
+        request.response_format = Some(forgeai_schema::response_format::<T>());
+        let response = self.chat(request).await?;
+        serde_json::from_str(response.output_text.trim()).map_err(|e| {
+            ForgeError::Provider(
+                format!(
+                    "structured response did not match the requested type: {e}; raw output: {}",
+                    response.output_text
+                )
+                .into(),
+            )
+        })
+    }
+
+    /// Runs `chat` and returns its output text, or `default` if the call fails.
+    /// The error is logged at `warn` level rather than returned.
+    pub async fn chat_or(&self, request: ChatRequest, default: String) -> String {
+        match self.chat(request).await {
+            Ok(response) => response.output_text,
+            Err(error) => {
+                tracing::warn!(%error, "chat failed, returning default");
+                default
+            }
+        }
     }
 
     pub async fn chat_stream(
@@ -28,36 +304,320 @@ impl Client {
         request: ChatRequest,
     ) -> Result<StreamResult<StreamEvent>, ForgeError> {
         validate_request(&request)?;
-        self.adapter.chat_stream(request).await
+        let call = self.lifecycle.begin()?;
+        let stream = self.open_stream(request).await?;
+        Ok(self.lifecycle.track(stream, call))
+    }
+
+    /// `chat_stream` reduced to the text of each `TextDelta`, ending at
+    /// `Done`. Tool-call, usage and other events are dropped; errors are
+    /// passed through.
+    pub async fn stream_text(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StreamResult<String>, ForgeError> {
+        use futures_util::future::ready;
+        use futures_util::StreamExt;
+        let events = self.chat_stream(request).await?;
+        Ok(Box::pin(
+            events
+                .take_while(|item| ready(!matches!(item, Ok(StreamEvent::Done))))
+                .filter_map(|item| {
+                    ready(match item {
+                        Ok(StreamEvent::TextDelta { delta }) => Some(Ok(delta)),
+                        Ok(_) => None,
+                        Err(err) => Some(Err(err)),
+                    })
+                }),
+        ))
+    }
+
+    /// `chat_stream` without validation or shutdown tracking, for calls made
+    /// on behalf of an already tracked call.
+    async fn open_stream(
+        &self,
+        mut request: ChatRequest,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+        self.finalize_request(&mut request)?;
+        let model = request.model.clone();
+        let stream = self.adapter.chat_stream(request).await?;
+        let Some(sink) = self.usage_sink.clone() else {
+            return Ok(stream);
+        };
+
+        use futures_util::StreamExt;
+        Ok(Box::pin(stream.inspect(move |item| {
+            if let Ok(StreamEvent::Usage { usage }) = item {
+                sink.record(&model, usage);
+            }
+        })))
+    }
+
+    /// Answers `request` through `chat_stream`, collecting text, tool calls,
+    /// usage and finish reason into one `ChatResponse`, for backends that only
+    /// implement streaming. `id` is taken from the stream when the provider
+    /// reports one and is empty otherwise.
+    pub async fn chat_via_stream(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        validate_request(&request)?;
+        let _call = self.lifecycle.begin()?;
+        self.collect_stream(request).await
+    }
+
+    /// `chat_via_stream` that keeps what arrived before a failure: when the
+    /// stream errors midway, the text, tool calls and usage collected so far
+    /// are returned with the error, e.g. for a chat UI to keep showing them.
+    pub async fn chat_via_stream_lossy(
+        &self,
+        request: ChatRequest,
+    ) -> Result<ChatResponse, InterruptedResponse> {
+        let mut collector = ResponseCollector::new(request.model.clone());
+        let result = async {
+            validate_request(&request)?;
+            let _call = self.lifecycle.begin()?;
+            let mut stream = self.open_stream(request).await?;
+
+            use futures_util::StreamExt;
+            while let Some(item) = stream.next().await {
+                if !collector.push(item?) {
+                    break;
+                }
+            }
+            Ok(())
+        }
+        .await;
+        match result {
+            Ok(()) => Ok(collector.finish()),
+            Err(error) => Err(InterruptedResponse {
+                partial: collector.finish(),
+                error,
+            }),
+        }
+    }
+
+    /// Streams `request`, writing each text delta to `writer` as it arrives,
+    /// and returns the collected `ChatResponse` once the stream ends.
+    pub async fn stream_to_writer<W: AsyncWrite + Unpin>(
+        &self,
+        request: ChatRequest,
+        mut writer: W,
+    ) -> Result<ChatResponse, ForgeError> {
+        validate_request(&request)?;
+        let _call = self.lifecycle.begin()?;
+        let mut collector = ResponseCollector::new(request.model.clone());
+        let mut stream = self.open_stream(request).await?;
+
+        use futures_util::StreamExt;
+        while let Some(item) = stream.next().await {
+            let event = item?;
+            if let StreamEvent::TextDelta { delta } = &event {
+                writer
+                    .write_all(delta.as_bytes())
+                    .await
+                    .map_err(write_error)?;
+                writer.flush().await.map_err(write_error)?;
+            }
+            if !collector.push(event) {
+                break;
+            }
+        }
+        Ok(collector.finish())
+    }
+
+    /// Streams `request` in a background task that forwards every event to
+    /// the returned receiver, e.g. for a UI. The task resolves to the
+    /// collected `ChatResponse`; errors, including failing to start the
+    /// stream, are returned there and simply close the channel. Events are
+    /// still collected if the receiver is dropped.
+    pub async fn stream_to_channel(
+        &self,
+        request: ChatRequest,
+    ) -> (
+        mpsc::Receiver<StreamEvent>,
+        JoinHandle<Result<ChatResponse, ForgeError>>,
+    ) {
+        let (sender, receiver) = mpsc::channel(STREAM_CHANNEL_CAPACITY);
+        let mut collector = ResponseCollector::new(request.model.clone());
+        let stream = match self.chat_stream(request).await {
+            Ok(stream) => stream,
+            Err(err) => return (receiver, tokio::spawn(async move { Err(err) })),
+        };
+        let task = tokio::spawn(async move {
+            use futures_util::StreamExt;
+            let mut stream = stream;
+            while let Some(item) = stream.next().await {
+                let event = item?;
+                let _ = sender.send(event.clone()).await;
+                if !collector.push(event) {
+                    break;
+                }
+            }
+            Ok(collector.finish())
+        });
+        (receiver, task)
+    }
+
+    /// Runs `chat` until it completes or `cancel` fires. Cancelling drops the
+    /// in-flight HTTP request and returns `ForgeError::Transport("cancelled")`.
+    pub async fn chat_cancellable(
+        &self,
+        request: ChatRequest,
+        cancel: CancellationToken,
+    ) -> Result<ChatResponse, ForgeError> {
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(cancelled()),
+            result = self.chat(request) => result,
+        }
+    }
+
+    /// Like `chat_stream`, but `cancel` aborts the call or, once streaming,
+    /// ends the stream with a final `ForgeError::Transport("cancelled")` and
+    /// drops the underlying HTTP response.
+    pub async fn chat_stream_cancellable(
+        &self,
+        request: ChatRequest,
+        cancel: CancellationToken,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+        let stream = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => return Err(cancelled()),
+            result = self.chat_stream(request) => result?,
+        };
+
+        use futures_util::StreamExt;
+        let interrupted = futures_util::stream::once({
+            let cancel = cancel.clone();
+            async move { cancel.is_cancelled() }
+        })
+        .filter_map(|cancelled_midway| async move { cancelled_midway.then(|| Err(cancelled())) });
+        Ok(Box::pin(
+            stream
+                .take_until(cancel.cancelled_owned())
+                .chain(interrupted),
+        ))
+    }
+
+    /// Streams like `chat_stream`, additionally invoking `callback` with
+    /// `(call_id, fragment)` for each tool-call argument fragment as it arrives.
+    pub async fn chat_stream_with_tool_arg_callback<F>(
+        &self,
+        request: ChatRequest,
+        callback: F,
+    ) -> Result<StreamResult<StreamEvent>, ForgeError>
+    where
+        F: Fn(&str, &str) + Send + Sync + 'static,
+    {
+        let stream = self.chat_stream(request).await?;
+
+        use futures_util::StreamExt;
+        Ok(Box::pin(stream.inspect(move |item| {
+            if let Ok(StreamEvent::ToolCallDelta { call_id, delta }) = item {
+                if let Some(fragment) = tool_arg_fragment(delta) {
+                    callback(call_id, &fragment);
+                }
+            }
+        })))
     }
 
     pub async fn chat_with_tools(
         &self,
         request: ChatRequest,
-        tools: &dyn ToolExecutor,
+        tools: &dyn AsyncToolExecutor,
         options: ToolLoopOptions,
     ) -> Result<ToolLoopResult, ForgeError> {
+        let _call = self.lifecycle.begin()?;
         run_tool_loop(self, request, tools, options, false).await
     }
 
     pub async fn chat_with_tools_streaming(
         &self,
         request: ChatRequest,
-        tools: &dyn ToolExecutor,
+        tools: &dyn AsyncToolExecutor,
         options: ToolLoopOptions,
     ) -> Result<ToolLoopResult, ForgeError> {
+        let _call = self.lifecycle.begin()?;
         run_tool_loop(self, request, tools, options, true).await
     }
 }
 
-#[derive(Debug, Clone)]
+fn cancelled() -> ForgeError {
+    ForgeError::Transport("cancelled".to_string())
+}
+
+const EXTRACT_TOOL_NAME: &str = "extract";
+const EXTRACT_SYSTEM_PROMPT: &str = "Extract the requested fields from the user's text \
+    and call the `extract` tool with them. Use only information stated in the text.";
+
+#[derive(Clone)]
 pub struct ToolLoopOptions {
     pub max_iterations: usize,
+    /// Run all tool calls of a single response concurrently. Tool messages keep
+    /// the order of the calls either way.
+    pub parallel: bool,
+    /// Upper bound on the wall-clock time of the whole loop, including every
+    /// model call and tool execution.
+    pub deadline: Option<Duration>,
+    /// Builds the JSON content of the tool message sent back for each call.
+    /// Defaults to `default_tool_result`.
+    pub tool_result_formatter: fn(&ToolCall, &Value) -> Value,
+    /// Called with each tool invocation as soon as the tool has produced its
+    /// output, e.g. for audit logging.
+    pub on_tool_call: Option<ToolCallHook>,
+}
+
+/// Observer for tool invocations, see `ToolLoopOptions::on_tool_call`.
+pub type ToolCallHook = Arc<dyn Fn(&ToolInvocation) + Send + Sync>;
+
+impl std::fmt::Debug for ToolLoopOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolLoopOptions")
+            .field("max_iterations", &self.max_iterations)
+            .field("parallel", &self.parallel)
+            .field("deadline", &self.deadline)
+            .field("on_tool_call", &self.on_tool_call.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl Default for ToolLoopOptions {
     fn default() -> Self {
-        Self { max_iterations: 8 }
+        Self {
+            max_iterations: 8,
+            parallel: false,
+            deadline: None,
+            tool_result_formatter: default_tool_result,
+            on_tool_call: None,
+        }
+    }
+}
+
+/// Default tool message content: `{"tool_call_id", "name", "output"}`.
+pub fn default_tool_result(call: &ToolCall, output: &Value) -> Value {
+    json!({
+        "tool_call_id": call.id,
+        "name": call.name,
+        "output": output
+    })
+}
+
+/// The delay a provider asked for via `Retry-After`, carried by
+/// `ForgeError::RateLimited`. `None` for every other error, or when the
+/// provider gave no delay.
+pub fn wait_for_retry(error: &ForgeError) -> Option<Duration> {
+    match error {
+        ForgeError::RateLimited { retry_after } => *retry_after,
+        _ => None,
+    }
+}
+
+/// Sleeps for `wait_for_retry(error)`, if any. Returns whether it slept.
+pub async fn sleep_for_retry(error: &ForgeError) -> bool {
+    match wait_for_retry(error) {
+        Some(delay) => {
+            tokio::time::sleep(delay).await;
+            true
+        }
+        None => false,
     }
 }
 
@@ -74,14 +634,172 @@ pub struct ToolLoopResult {
     pub final_response: ChatResponse,
     pub tool_invocations: Vec<ToolInvocation>,
     pub iterations: usize,
+    /// Full conversation, including tool turns and the final assistant answer.
+    pub messages: Vec<Message>,
+    /// Usage summed over every model call of the loop that reported it; `None`
+    /// when none did.
+    pub total_usage: Option<Usage>,
+    /// Tool definitions sent with the first model call, for checking which
+    /// schemas the provider actually saw.
+    pub sent_tools: Vec<ToolDefinition>,
+}
+
+impl ToolLoopResult {
+    /// Renders the conversation as an OpenAI chat-completions `messages` array.
+    pub fn to_openai_messages(&self) -> Value {
+        self.messages.iter().map(message_to_openai).collect()
+    }
+}
+
+/// Extracts the argument fragment carried by a provider tool-call delta.
+fn tool_arg_fragment(delta: &Value) -> Option<String> {
+    let arguments = delta
+        .get("function")
+        .and_then(|f| f.get("arguments"))
+        .or_else(|| delta.get("arguments"))
+        .or_else(|| delta.get("partial_json"))?;
+    match arguments {
+        Value::String(fragment) if fragment.is_empty() => None,
+        Value::String(fragment) => Some(fragment.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+fn write_error(err: std::io::Error) -> ForgeError {
+    ForgeError::Internal(format!("failed to write stream output: {err}"))
+}
+
+/// A streamed tool call whose argument fragments are still arriving.
+struct PartialToolCall {
+    id: String,
+    /// OpenAI's per-response tool-call `index`, used to route continuation
+    /// deltas that carry no id.
+    index: Option<u64>,
+    name: String,
+    arguments: String,
+}
+
+impl PartialToolCall {
+    fn finish(self) -> ToolCall {
+        let arguments = if self.arguments.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&self.arguments).unwrap_or(Value::String(self.arguments))
+        };
+        ToolCall {
+            id: self.id,
+            name: if self.name.is_empty() {
+                "unknown_tool".to_string()
+            } else {
+                self.name
+            },
+            arguments,
+        }
+    }
+}
+
+/// Appends a tool-call delta to the matching partial call. OpenAI only sends
+/// the id once, so deltas without one continue the call with the same
+/// `index`, or else the most recent call. Gemini sends neither an id nor an
+/// index, but each of its deltas is a whole call carrying a name, so such a
+/// delta starts a new call. Calls keep the order in which they first appeared.
+fn accumulate_tool_call(calls: &mut Vec<PartialToolCall>, call_id: String, delta: &Value) {
+    let delta_index = delta.get("index").and_then(Value::as_u64);
+    let name = delta
+        .get("name")
+        .or_else(|| delta.get("function").and_then(|f| f.get("name")))
+        .and_then(Value::as_str)
+        .filter(|name| !name.is_empty());
+    let existing = if !call_id.is_empty() {
+        calls.iter().position(|call| call.id == call_id)
+    } else if let Some(index) = delta_index {
+        calls.iter().position(|call| call.index == Some(index))
+    } else if name.is_some() {
+        None
+    } else {
+        calls.len().checked_sub(1)
+    };
+    let position = match existing {
+        Some(position) => position,
+        None => {
+            calls.push(PartialToolCall {
+                id: call_id,
+                index: delta_index,
+                name: String::new(),
+                arguments: String::new(),
+            });
+            calls.len() - 1
+        }
+    };
+    let call = &mut calls[position];
+
+    if call.name.is_empty() {
+        if let Some(name) = name {
+            call.name = name.to_string();
+        }
+    }
+    if let Some(fragment) = tool_arg_fragment(delta) {
+        call.arguments.push_str(&fragment);
+    }
+}
+
+/// Runs a tool, turning a panic into `ToolError::Execution` so it surfaces as an
+/// error from the loop instead of unwinding through the caller's task.
+async fn call_tool(
+    tools: &dyn AsyncToolExecutor,
+    name: &str,
+    input: Value,
+) -> Result<Value, ToolError> {
+    use futures_util::FutureExt;
+
+    AssertUnwindSafe(tools.call_async(name, input))
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic payload".to_string());
+            Err(ToolError::Execution(format!("tool panicked: {message}")))
+        })
 }
 
 async fn run_tool_loop(
     client: &Client,
-    mut request: ChatRequest,
-    tools: &dyn ToolExecutor,
+    request: ChatRequest,
+    tools: &dyn AsyncToolExecutor,
     options: ToolLoopOptions,
     use_streaming: bool,
+) -> Result<ToolLoopResult, ForgeError> {
+    let completed = AtomicUsize::new(0);
+    let Some(deadline) = options.deadline else {
+        return tool_loop_iterations(client, request, tools, &options, use_streaming, &completed)
+            .await;
+    };
+    let run = tool_loop_iterations(client, request, tools, &options, use_streaming, &completed);
+    match tokio::time::timeout(deadline, run).await {
+        Ok(result) => result,
+        Err(_) => Err(ForgeError::Provider(
+            format!(
+                "tool loop deadline exceeded after {} completed iterations",
+                completed.load(Ordering::SeqCst)
+            )
+            .into(),
+        )),
+    }
+}
+
+/// The tool loop proper; `completed` counts finished iterations so a deadline
+/// error can report progress.
+async fn tool_loop_iterations(
+    client: &Client,
+    mut request: ChatRequest,
+    tools: &dyn AsyncToolExecutor,
+    options: &ToolLoopOptions,
+    use_streaming: bool,
+    completed: &AtomicUsize,
 ) -> Result<ToolLoopResult, ForgeError> {
     validate_request(&request)?;
     if options.max_iterations == 0 {
@@ -91,122 +809,234 @@ async fn run_tool_loop(
     }
 
     let mut invocations = Vec::new();
+    let mut total_usage: Option<Usage> = None;
+    let sent_tools = request.tools.clone();
 
     for iteration in 0..options.max_iterations {
         let response = if use_streaming {
-            client.chat_stream_collect(request.clone()).await?
+            client.collect_stream(request.clone()).await?
         } else {
-            client.adapter.chat(request.clone()).await?
+            client.adapter_chat(request.clone()).await?
         };
+        if let Some(usage) = &response.usage {
+            total_usage
+                .get_or_insert_with(Usage::default)
+                .accumulate(usage);
+        }
 
         if response.tool_calls.is_empty() {
+            request
+                .messages
+                .push(Message::new(Role::Assistant, response.output_text.clone()));
             return Ok(ToolLoopResult {
                 final_response: response,
                 tool_invocations: invocations,
                 iterations: iteration + 1,
+                messages: request.messages,
+                total_usage,
+                sent_tools,
             });
         }
 
-        request.messages.push(Message {
-            role: Role::Assistant,
-            content: response.output_text.clone(),
-        });
+        // Carry the tool calls on the assistant turn so each adapter can render it in
+        // its native shape even when the model produced no text alongside them.
+        let mut assistant = Message::new(Role::Assistant, response.output_text.clone());
+        assistant.tool_calls = response.tool_calls.clone();
+        request.messages.push(assistant);
 
-        for call in response.tool_calls {
-            let output = tools
-                .call(&call.name, call.arguments.clone())
-                .map_err(|e| {
-                    ForgeError::Provider(format!("tool '{}' execution failed: {e}", call.name))
-                })?;
+        let outputs = if options.parallel {
+            join_all(
+                response
+                    .tool_calls
+                    .iter()
+                    .map(|call| call_tool(tools, &call.name, call.arguments.clone())),
+            )
+            .await
+        } else {
+            // Stop at the first failure so later tools are not run needlessly.
+            let mut outputs = Vec::with_capacity(response.tool_calls.len());
+            for call in &response.tool_calls {
+                let output = call_tool(tools, &call.name, call.arguments.clone()).await;
+                let failed = output.is_err();
+                outputs.push(output);
+                if failed {
+                    break;
+                }
+            }
+            outputs
+        };
 
-            invocations.push(ToolInvocation {
+        for (call, output) in response.tool_calls.into_iter().zip(outputs) {
+            let output = output.map_err(|e| {
+                ForgeError::Provider(format!("tool '{}' execution failed: {e}", call.name).into())
+            })?;
+
+            let invocation = ToolInvocation {
                 call_id: call.id.clone(),
                 name: call.name.clone(),
                 input: call.arguments.clone(),
                 output: output.clone(),
-            });
+            };
+            if let Some(on_tool_call) = &options.on_tool_call {
+                on_tool_call(&invocation);
+            }
+            invocations.push(invocation);
 
-            request.messages.push(Message {
-                role: Role::Tool,
-                content: json!({
-                    "tool_call_id": call.id,
-                    "name": call.name,
-                    "output": output
-                })
-                .to_string(),
-            });
+            let mut tool_message = Message::new(
+                Role::Tool,
+                (options.tool_result_formatter)(&call, &output).to_string(),
+            );
+            tool_message.tool_call_id = Some(call.id);
+            request.messages.push(tool_message);
         }
+        completed.store(iteration + 1, Ordering::SeqCst);
     }
 
-    Err(ForgeError::Provider(format!(
-        "tool loop exceeded max iterations ({})",
-        options.max_iterations
-    )))
+    Err(ForgeError::Provider(
+        format!(
+            "tool loop exceeded max iterations ({})",
+            options.max_iterations
+        )
+        .into(),
+    ))
 }
 
 impl Client {
-    async fn chat_stream_collect(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
-        let mut stream = self.chat_stream(request.clone()).await?;
-        let mut text = String::new();
-        let mut usage: Option<Usage> = None;
-        let mut tool_call_deltas: HashMap<String, Value> = HashMap::new();
+    /// Applies model profiles and client defaults, checks the result against
+    /// the model limits, then reports it to the audit hook.
+    fn finalize_request(&self, request: &mut ChatRequest) -> Result<(), ForgeError> {
+        let profile = self.model_profiles.lookup(&request.model);
+        if let Some(profile) = profile {
+            profile.apply(request);
+        }
+        if request.temperature.is_none() && !profile.is_some_and(|p| p.omit_temperature) {
+            request.temperature = self.default_temperature;
+        }
+        if let Some(header) = &self.correlation_header {
+            if let Some(id) = request
+                .metadata
+                .get(CORRELATION_ID_KEY)
+                .and_then(Value::as_str)
+            {
+                request
+                    .extra_headers
+                    .entry(header.clone())
+                    .or_insert_with(|| id.to_string());
+            }
+        }
+        if let Some(limits) = &self.model_limits {
+            limits.check(request)?;
+        }
+        if let Some(hook) = &self.audit_hook {
+            hook(request);
+        }
+        Ok(())
+    }
+
+    async fn adapter_chat(&self, mut request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        self.finalize_request(&mut request)?;
+        let strict_schema = match &request.response_format {
+            Some(ResponseFormat::JsonSchema {
+                schema,
+                strict: true,
+                ..
+            }) => Some(schema.clone()),
+            _ => None,
+        };
+        let model = request.model.clone();
+        let response = self.adapter.chat(request).await?;
+        if let (Some(sink), Some(usage)) = (&self.usage_sink, &response.usage) {
+            sink.record(&model, usage);
+        }
+        // Provider strict modes are not airtight, so the output is checked here too.
+        if let Some(schema) = strict_schema {
+            forgeai_schema::validate_output(&schema, &response.output_text)?;
+        }
+        Ok(response)
+    }
+
+    async fn collect_stream(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        let mut collector = ResponseCollector::new(request.model.clone());
+        let mut stream = self.open_stream(request).await?;
 
         use futures_util::StreamExt;
         while let Some(item) = stream.next().await {
-            match item? {
-                StreamEvent::TextDelta { delta } => text.push_str(&delta),
-                StreamEvent::Usage { usage: u } => usage = Some(u),
-                StreamEvent::ToolCallDelta { call_id, delta } => {
-                    tool_call_deltas.insert(call_id, delta);
+            if !collector.push(item?) {
+                break;
+            }
+        }
+        Ok(collector.finish())
+    }
+}
+
+/// Error of `Client::chat_via_stream_lossy`, with everything collected
+/// before it occurred.
+#[derive(Debug)]
+pub struct InterruptedResponse {
+    pub partial: ChatResponse,
+    pub error: ForgeError,
+}
+
+/// Folds stream events into the `ChatResponse` a `chat` call would return.
+struct ResponseCollector {
+    response: ChatResponse,
+    text: String,
+    reasoning: String,
+    partial_calls: Vec<PartialToolCall>,
+}
+
+impl ResponseCollector {
+    fn new(model: String) -> Self {
+        Self {
+            response: ChatResponse {
+                model,
+                ..Default::default()
+            },
+            text: String::new(),
+            reasoning: String::new(),
+            partial_calls: Vec::new(),
+        }
+    }
+
+    /// Records `event`; returns `false` once the stream is `Done`.
+    fn push(&mut self, event: StreamEvent) -> bool {
+        match event {
+            StreamEvent::ResponseStart { id, model } => {
+                self.response.id = id;
+                if !model.is_empty() {
+                    self.response.model = model;
                 }
-                StreamEvent::Done => break,
             }
+            StreamEvent::TextDelta { delta } => self.text.push_str(&delta),
+            StreamEvent::ReasoningDelta { delta } => self.reasoning.push_str(&delta),
+            StreamEvent::Usage { usage } => self.response.usage = Some(usage),
+            StreamEvent::FinishReason { reason } => self.response.finish_reason = Some(reason),
+            StreamEvent::ToolCallDelta { call_id, delta } => {
+                accumulate_tool_call(&mut self.partial_calls, call_id, &delta);
+            }
+            StreamEvent::Done => return false,
+            _ => {}
         }
+        true
+    }
 
-        let tool_calls = tool_call_deltas
+    fn finish(self) -> ChatResponse {
+        let mut response = self.response;
+        response.output_text = self.text;
+        response.reasoning = (!self.reasoning.is_empty()).then_some(self.reasoning);
+        response.tool_calls = self
+            .partial_calls
             .into_iter()
-            .map(|(call_id, delta)| {
-                // Best-effort normalization across provider stream formats.
-                let name = delta
-                    .get("name")
-                    .and_then(Value::as_str)
-                    .or_else(|| {
-                        delta
-                            .get("function")
-                            .and_then(|f| f.get("name"))
-                            .and_then(Value::as_str)
-                    })
-                    .unwrap_or("unknown_tool")
-                    .to_string();
-                let arguments = delta
-                    .get("arguments")
-                    .cloned()
-                    .or_else(|| {
-                        delta
-                            .get("function")
-                            .and_then(|f| f.get("arguments"))
-                            .cloned()
-                    })
-                    .unwrap_or(Value::Null);
-                ToolCall {
-                    id: call_id,
-                    name,
-                    arguments,
-                }
-            })
+            .map(PartialToolCall::finish)
             .collect();
-
-        Ok(ChatResponse {
-            id: "stream-collected".to_string(),
-            model: request.model,
-            output_text: text,
-            tool_calls,
-            usage,
-        })
+        response
     }
 }
 
 pub use forgeai_core;
+#[cfg(feature = "macros")]
+pub use forgeai_macros::forge_tool;
+pub use forgeai_schema;
 pub use forgeai_tools;
 
 #[cfg(test)]
@@ -214,7 +1044,8 @@ mod tests {
     use super::*;
     use async_stream::try_stream;
     use async_trait::async_trait;
-    use forgeai_core::{AdapterInfo, CapabilityMatrix};
+    use forgeai_core::{AdapterInfo, CapabilityMatrix, ContentPart};
+    use forgeai_tools::ToolExecutor;
     use serde_json::json;
     use std::collections::VecDeque;
     use std::sync::Mutex;
@@ -222,6 +1053,7 @@ mod tests {
     struct MockAdapter {
         chat_responses: Mutex<VecDeque<ChatResponse>>,
         stream_responses: Mutex<VecDeque<Vec<StreamEvent>>>,
+        requests: Mutex<Vec<ChatRequest>>,
     }
 
     impl MockAdapter {
@@ -229,6 +1061,7 @@ mod tests {
             Self {
                 chat_responses: Mutex::new(VecDeque::from(items)),
                 stream_responses: Mutex::new(VecDeque::new()),
+                requests: Mutex::new(Vec::new()),
             }
         }
 
@@ -236,6 +1069,7 @@ mod tests {
             Self {
                 chat_responses: Mutex::new(VecDeque::new()),
                 stream_responses: Mutex::new(VecDeque::from(items)),
+                requests: Mutex::new(Vec::new()),
             }
         }
     }
@@ -256,7 +1090,11 @@ mod tests {
             }
         }
 
-        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+            self.requests
+                .lock()
+                .map_err(|_| ForgeError::Internal("lock poisoned".to_string()))?
+                .push(request);
             self.chat_responses
                 .lock()
                 .map_err(|_| ForgeError::Internal("lock poisoned".to_string()))?
@@ -297,13 +1135,22 @@ mod tests {
     fn base_request() -> ChatRequest {
         ChatRequest {
             model: "mock-model".to_string(),
-            messages: vec![Message {
-                role: Role::User,
-                content: "what time is it?".to_string(),
-            }],
+            messages: vec![Message::new(Role::User, "what time is it?")],
+            system: None,
             temperature: Some(0.1),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             max_tokens: Some(128),
+            n: None,
+            seed: None,
+            stop: vec![],
             tools: vec![],
+            tool_choice: None,
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::new(),
             metadata: json!({}),
         }
     }
@@ -321,6 +1168,7 @@ mod tests {
                     arguments: json!({"timezone":"UTC"}),
                 }],
                 usage: None,
+                ..Default::default()
             },
             ChatResponse {
                 id: "2".to_string(),
@@ -328,6 +1176,7 @@ mod tests {
                 output_text: "Current UTC time is 12:00".to_string(),
                 tool_calls: vec![],
                 usage: None,
+                ..Default::default()
             },
         ]);
 
@@ -347,39 +1196,808 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn chat_with_tools_streaming_collects_events_and_executes_tools() {
-        let adapter = MockAdapter::with_stream_responses(vec![
-            vec![
-                StreamEvent::ToolCallDelta {
-                    call_id: "call-1".to_string(),
-                    delta: json!({"name":"time.now","arguments":{"timezone":"UTC"}}),
-                },
-                StreamEvent::Done,
-            ],
-            vec![
-                StreamEvent::TextDelta {
-                    delta: "Current UTC time is 12:00".to_string(),
-                },
-                StreamEvent::Done,
-            ],
-        ]);
+    async fn tool_loop_records_tools_sent_to_provider() {
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![ChatResponse {
+            output_text: "done".to_string(),
+            ..Default::default()
+        }]));
+        let client = Client::new(adapter.clone());
+        let mut request = base_request();
+        request.tools = vec![
+            ToolDefinition {
+                name: "time.now".to_string(),
+                description: Some("Current time".to_string()),
+                input_schema: json!({"type": "object"}),
+                raw: None,
+            },
+            ToolDefinition::server_tool(
+                json!({"type": "web_search_20250305", "name": "web_search"}),
+            ),
+        ];
 
-        let client = Client::new(Arc::new(adapter));
         let result = client
-            .chat_with_tools_streaming(base_request(), &EchoTools, ToolLoopOptions::default())
+            .chat_with_tools(request, &EchoTools, ToolLoopOptions::default())
             .await
             .unwrap();
 
+        let sent = &adapter.requests.lock().unwrap()[0].tools;
         assert_eq!(
-            result.final_response.output_text,
-            "Current UTC time is 12:00"
+            serde_json::to_value(&result.sent_tools).unwrap(),
+            serde_json::to_value(sent).unwrap()
         );
-        assert_eq!(result.tool_invocations.len(), 1);
-        assert_eq!(result.iterations, 2);
+        assert_eq!(result.sent_tools.len(), 2);
     }
 
     #[tokio::test]
-    async fn chat_with_tools_honors_max_iterations() {
+    async fn tool_loop_sums_usage_across_iterations() {
+        let usage = |input, output| {
+            Some(Usage {
+                input_tokens: input,
+                output_tokens: output,
+                total_tokens: input + output,
+                ..Default::default()
+            })
+        };
+        let tool_call = ToolCall {
+            id: "call-1".to_string(),
+            name: "time.now".to_string(),
+            arguments: json!({}),
+        };
+        let adapter = MockAdapter::with_chat_responses(vec![
+            ChatResponse {
+                tool_calls: vec![tool_call.clone()],
+                usage: usage(10, 4),
+                ..Default::default()
+            },
+            ChatResponse {
+                tool_calls: vec![tool_call],
+                usage: None,
+                ..Default::default()
+            },
+            ChatResponse {
+                output_text: "done".to_string(),
+                usage: usage(20, 6),
+                ..Default::default()
+            },
+        ]);
+
+        let client = Client::new(Arc::new(adapter));
+        let result = client
+            .chat_with_tools(base_request(), &EchoTools, ToolLoopOptions::default())
+            .await
+            .unwrap();
+
+        let total = result.total_usage.unwrap();
+        assert_eq!(total.input_tokens, 30);
+        assert_eq!(total.output_tokens, 10);
+        assert_eq!(total.total_tokens, 40);
+    }
+
+    #[tokio::test]
+    async fn tool_loop_deadline_bounds_total_time() {
+        struct SlowToolCaller;
+
+        #[async_trait]
+        impl ChatAdapter for SlowToolCaller {
+            fn info(&self) -> AdapterInfo {
+                MockAdapter::with_chat_responses(vec![]).info()
+            }
+
+            async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+                tokio::time::sleep(Duration::from_millis(40)).await;
+                Ok(ChatResponse {
+                    tool_calls: vec![ToolCall {
+                        id: "call-1".to_string(),
+                        name: "time.now".to_string(),
+                        arguments: json!({}),
+                    }],
+                    ..Default::default()
+                })
+            }
+
+            async fn chat_stream(
+                &self,
+                _request: ChatRequest,
+            ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+                Err(ForgeError::Internal("not streamed".to_string()))
+            }
+        }
+
+        let client = Client::new(Arc::new(SlowToolCaller));
+        let started = std::time::Instant::now();
+        let err = client
+            .chat_with_tools(
+                base_request(),
+                &EchoTools,
+                ToolLoopOptions {
+                    max_iterations: 100,
+                    deadline: Some(Duration::from_millis(100)),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(
+            matches!(err, ForgeError::Provider(ref e) if e.message.starts_with("tool loop deadline exceeded after"))
+        );
+    }
+
+    #[tokio::test]
+    async fn tool_result_formatter_shapes_tool_messages() {
+        let adapter = MockAdapter::with_chat_responses(vec![
+            ChatResponse {
+                tool_calls: vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "time.now".to_string(),
+                    arguments: json!({"timezone": "UTC"}),
+                }],
+                ..Default::default()
+            },
+            ChatResponse {
+                output_text: "done".to_string(),
+                ..Default::default()
+            },
+        ]);
+
+        let client = Client::new(Arc::new(adapter));
+        let result = client
+            .chat_with_tools(
+                base_request(),
+                &EchoTools,
+                ToolLoopOptions {
+                    tool_result_formatter: |call, output| json!({"id": call.id, "result": output}),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let tool_message = result
+            .messages
+            .iter()
+            .find(|m| matches!(m.role, Role::Tool))
+            .unwrap();
+        let content: Value = serde_json::from_str(&tool_message.content.text()).unwrap();
+        assert_eq!(
+            content,
+            json!({"id": "call-1", "result": {"echo": {"timezone": "UTC"}}})
+        );
+    }
+
+    #[tokio::test]
+    async fn on_tool_call_observes_each_invocation() {
+        let tool_turn = || {
+            vec![
+                StreamEvent::ToolCallDelta {
+                    call_id: "call-1".to_string(),
+                    delta: json!({"name": "time.now", "arguments": {"timezone": "UTC"}}),
+                },
+                StreamEvent::Done,
+            ]
+        };
+        let final_turn = || {
+            vec![
+                StreamEvent::TextDelta {
+                    delta: "done".to_string(),
+                },
+                StreamEvent::Done,
+            ]
+        };
+        let adapter = MockAdapter::with_stream_responses(vec![tool_turn(), final_turn()]);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let options = ToolLoopOptions {
+            on_tool_call: Some(Arc::new(move |invocation: &ToolInvocation| {
+                sink.lock().unwrap().push(invocation.clone());
+            })),
+            ..Default::default()
+        };
+
+        let client = Client::new(Arc::new(adapter));
+        client
+            .chat_with_tools_streaming(base_request(), &EchoTools, options.clone())
+            .await
+            .unwrap();
+
+        let adapter = MockAdapter::with_chat_responses(vec![
+            ChatResponse {
+                tool_calls: vec![ToolCall {
+                    id: "call-2".to_string(),
+                    name: "time.now".to_string(),
+                    arguments: json!({}),
+                }],
+                ..Default::default()
+            },
+            ChatResponse::default(),
+        ]);
+        let client = Client::new(Arc::new(adapter));
+        client
+            .chat_with_tools(base_request(), &EchoTools, options)
+            .await
+            .unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0].call_id, "call-1");
+        assert_eq!(seen[0].output, json!({"echo": {"timezone": "UTC"}}));
+        assert_eq!(seen[1].call_id, "call-2");
+    }
+
+    #[tokio::test]
+    async fn chat_with_tools_awaits_async_tools() {
+        struct ClockTools;
+
+        #[async_trait]
+        impl AsyncToolExecutor for ClockTools {
+            async fn call_async(&self, name: &str, _input: Value) -> Result<Value, ToolError> {
+                tokio::task::yield_now().await;
+                Ok(json!({ "tool": name, "time": "12:00" }))
+            }
+        }
+
+        let adapter = MockAdapter::with_chat_responses(vec![
+            ChatResponse {
+                tool_calls: vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "time.now".to_string(),
+                    arguments: json!({}),
+                }],
+                ..Default::default()
+            },
+            ChatResponse {
+                output_text: "It is 12:00".to_string(),
+                ..Default::default()
+            },
+        ]);
+
+        let client = Client::new(Arc::new(adapter));
+        let result = client
+            .chat_with_tools(base_request(), &ClockTools, ToolLoopOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(result.tool_invocations[0].output["time"], "12:00");
+        assert_eq!(result.final_response.output_text, "It is 12:00");
+    }
+
+    #[tokio::test]
+    async fn parallel_tool_calls_run_concurrently_and_keep_order() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Default)]
+        struct SlowTools {
+            in_flight: AtomicUsize,
+            max_in_flight: AtomicUsize,
+        }
+
+        #[async_trait]
+        impl AsyncToolExecutor for SlowTools {
+            async fn call_async(&self, name: &str, _input: Value) -> Result<Value, ToolError> {
+                let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+                // The first call yields longer, so it finishes last.
+                let yields = if name == "first" { 4 } else { 1 };
+                for _ in 0..yields {
+                    tokio::task::yield_now().await;
+                }
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(json!({ "tool": name }))
+            }
+        }
+
+        let call = |id: &str, name: &str| ToolCall {
+            id: id.to_string(),
+            name: name.to_string(),
+            arguments: json!({}),
+        };
+        let adapter = MockAdapter::with_chat_responses(vec![
+            ChatResponse {
+                tool_calls: vec![call("call-1", "first"), call("call-2", "second")],
+                ..Default::default()
+            },
+            ChatResponse {
+                output_text: "done".to_string(),
+                ..Default::default()
+            },
+        ]);
+
+        let tools = SlowTools::default();
+        let client = Client::new(Arc::new(adapter));
+        let result = client
+            .chat_with_tools(
+                base_request(),
+                &tools,
+                ToolLoopOptions {
+                    parallel: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(tools.max_in_flight.load(Ordering::SeqCst), 2);
+        let tool_ids: Vec<_> = result
+            .messages
+            .iter()
+            .filter_map(|m| m.tool_call_id.as_deref())
+            .collect();
+        assert_eq!(tool_ids, ["call-1", "call-2"]);
+    }
+
+    #[tokio::test]
+    async fn detect_capabilities_reports_failed_tool_probe() {
+        // The stream probe succeeds; the tool probe finds no chat response and fails.
+        let adapter = MockAdapter::with_stream_responses(vec![vec![StreamEvent::Done]]);
+        let client = Client::new(Arc::new(adapter));
+
+        let capabilities = client.detect_capabilities("mock-model").await;
+
+        assert!(capabilities.streaming);
+        assert!(!capabilities.tools);
+        assert!(capabilities.structured_output);
+    }
+
+    #[tokio::test]
+    async fn complete_returns_output_text() {
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![ChatResponse {
+            output_text: "hi".to_string(),
+            ..Default::default()
+        }]));
+        let client = Client::new(adapter.clone());
+
+        let text = client.complete("mock-model", "Say hi").await.unwrap();
+
+        assert_eq!(text, "hi");
+        let requests = adapter.requests.lock().unwrap();
+        assert_eq!(requests[0].model, "mock-model");
+        assert_eq!(requests[0].messages.len(), 1);
+        assert_eq!(requests[0].messages[0].content.text(), "Say hi");
+    }
+
+    #[tokio::test]
+    async fn default_temperature_fills_only_unset_requests() {
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![
+            ChatResponse::default(),
+            ChatResponse::default(),
+        ]));
+        let client = Client::new(adapter.clone()).with_default_temperature(0.7);
+
+        let mut unset = base_request();
+        unset.temperature = None;
+        client.chat(unset).await.unwrap();
+        client.chat(base_request()).await.unwrap();
+
+        let requests = adapter.requests.lock().unwrap();
+        assert_eq!(requests[0].temperature, Some(0.7));
+        assert_eq!(requests[1].temperature, Some(0.1));
+    }
+
+    #[tokio::test]
+    async fn extract_deserializes_tool_arguments() {
+        use forgeai_schema::schemars;
+
+        #[derive(Debug, PartialEq, serde::Deserialize, schemars::JsonSchema)]
+        struct Person {
+            name: String,
+            age: u32,
+        }
+
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![ChatResponse {
+            tool_calls: vec![ToolCall {
+                id: "call-1".to_string(),
+                name: "extract".to_string(),
+                arguments: json!({"name": "Ada", "age": 36}),
+            }],
+            ..Default::default()
+        }]));
+        let client = Client::new(adapter.clone());
+
+        let person: Person = client
+            .extract("mock-model", "Ada turned 36 last week.")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            person,
+            Person {
+                name: "Ada".to_string(),
+                age: 36
+            }
+        );
+        let requests = adapter.requests.lock().unwrap();
+        assert_eq!(requests[0].tools[0].name, "extract");
+        assert_eq!(
+            requests[0].tool_choice,
+            Some(ToolChoice::Function("extract".to_string()))
+        );
+        assert_eq!(
+            requests[0].tools[0].input_schema["required"],
+            json!(["name", "age"])
+        );
+        assert!(requests[0].system.is_some());
+    }
+
+    #[tokio::test]
+    async fn audit_hook_sees_injected_system_prompt() {
+        use forgeai_schema::schemars;
+
+        #[derive(serde::Deserialize, schemars::JsonSchema)]
+        struct City {
+            #[allow(dead_code)]
+            name: String,
+        }
+
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![ChatResponse {
+            output_text: r#"{"name": "Paris"}"#.to_string(),
+            ..Default::default()
+        }]));
+        let audited = Arc::new(Mutex::new(Vec::new()));
+        let sink = audited.clone();
+        let client =
+            Client::new(adapter).with_audit_hook(Arc::new(move |request: &ChatRequest| {
+                sink.lock().unwrap().push(request.clone());
+            }));
+
+        client
+            .extract::<City>("mock-model", "I live in Paris.")
+            .await
+            .unwrap();
+
+        let audited = audited.lock().unwrap();
+        assert_eq!(audited.len(), 1);
+        assert_eq!(audited[0].system.as_deref(), Some(EXTRACT_SYSTEM_PROMPT));
+        assert_eq!(audited[0].messages[0].content.text(), "I live in Paris.");
+    }
+
+    #[tokio::test]
+    async fn chat_or_returns_default_on_error() {
+        let client = Client::new(Arc::new(MockAdapter::with_chat_responses(vec![])));
+
+        let text = client.chat_or(base_request(), "fallback".to_string()).await;
+
+        assert_eq!(text, "fallback");
+    }
+
+    #[tokio::test]
+    async fn chat_rejects_out_of_range_top_p() {
+        let client = Client::new(Arc::new(MockAdapter::with_chat_responses(vec![])));
+        let mut request = base_request();
+        request.top_p = Some(1.5);
+
+        let err = client.chat(request).await.unwrap_err();
+
+        assert!(matches!(err, ForgeError::Validation(ref m) if m.contains("top_p")));
+    }
+
+    #[tokio::test]
+    async fn chat_rejects_too_many_stop_sequences() {
+        let client = Client::new(Arc::new(MockAdapter::with_chat_responses(vec![])));
+        let mut request = base_request();
+        request.stop = (0..5).map(|i| format!("stop-{i}")).collect();
+
+        let err = client.chat(request).await.unwrap_err();
+
+        assert!(matches!(err, ForgeError::Validation(ref m) if m.contains("stop sequences")));
+    }
+
+    #[tokio::test]
+    async fn panicking_tool_surfaces_as_error() {
+        struct PanickingTools;
+
+        impl ToolExecutor for PanickingTools {
+            fn call(&self, _name: &str, _input: Value) -> Result<Value, ToolError> {
+                panic!("database unavailable");
+            }
+        }
+
+        let adapter = MockAdapter::with_chat_responses(vec![ChatResponse {
+            tool_calls: vec![ToolCall {
+                id: "call-1".to_string(),
+                name: "db.query".to_string(),
+                arguments: json!({}),
+            }],
+            ..Default::default()
+        }]);
+
+        let client = Client::new(Arc::new(adapter));
+        let err = client
+            .chat_with_tools(base_request(), &PanickingTools, ToolLoopOptions::default())
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, ForgeError::Provider(ref e) if e.message.contains("tool panicked: database unavailable"))
+        );
+    }
+
+    #[tokio::test]
+    async fn chat_with_tools_follow_up_carries_pure_tool_call_turn() {
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![
+            ChatResponse {
+                id: "1".to_string(),
+                model: "mock-model".to_string(),
+                output_text: "".to_string(),
+                tool_calls: vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "time.now".to_string(),
+                    arguments: json!({"timezone":"UTC"}),
+                }],
+                usage: None,
+                ..Default::default()
+            },
+            ChatResponse {
+                id: "2".to_string(),
+                model: "mock-model".to_string(),
+                output_text: "done".to_string(),
+                tool_calls: vec![],
+                usage: None,
+                ..Default::default()
+            },
+        ]));
+
+        let client = Client::new(adapter.clone());
+        client
+            .chat_with_tools(base_request(), &EchoTools, ToolLoopOptions::default())
+            .await
+            .unwrap();
+
+        let requests = adapter.requests.lock().unwrap();
+        let follow_up = &requests[1].messages;
+        assert_eq!(follow_up.len(), 3);
+        assert!(matches!(follow_up[1].role, Role::Assistant));
+        assert!(follow_up[1].content.is_empty());
+        assert_eq!(follow_up[1].tool_calls.len(), 1);
+        assert_eq!(follow_up[1].tool_calls[0].id, "call-1");
+        assert!(matches!(follow_up[2].role, Role::Tool));
+        assert_eq!(follow_up[2].tool_call_id.as_deref(), Some("call-1"));
+    }
+
+    #[tokio::test]
+    async fn tool_loop_result_exports_openai_messages() {
+        let adapter = MockAdapter::with_chat_responses(vec![
+            ChatResponse {
+                id: "1".to_string(),
+                model: "mock-model".to_string(),
+                output_text: "".to_string(),
+                tool_calls: vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "time.now".to_string(),
+                    arguments: json!({"timezone":"UTC"}),
+                }],
+                usage: None,
+                ..Default::default()
+            },
+            ChatResponse {
+                id: "2".to_string(),
+                model: "mock-model".to_string(),
+                output_text: "It is 12:00".to_string(),
+                tool_calls: vec![],
+                usage: None,
+                ..Default::default()
+            },
+        ]);
+
+        let client = Client::new(Arc::new(adapter));
+        let result = client
+            .chat_with_tools(base_request(), &EchoTools, ToolLoopOptions::default())
+            .await
+            .unwrap();
+
+        let messages = result.to_openai_messages();
+        assert_eq!(
+            messages,
+            json!([
+                {"role": "user", "content": "what time is it?"},
+                {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call-1",
+                        "type": "function",
+                        "function": {
+                            "name": "time.now",
+                            "arguments": "{\"timezone\":\"UTC\"}"
+                        }
+                    }]
+                },
+                {
+                    "role": "tool",
+                    "tool_call_id": "call-1",
+                    "content": json!({
+                        "tool_call_id": "call-1",
+                        "name": "time.now",
+                        "output": {"echo": {"timezone": "UTC"}}
+                    })
+                    .to_string()
+                },
+                {"role": "assistant", "content": "It is 12:00"}
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn usage_sink_accumulates_per_model() {
+        let usage = |input, output| {
+            Some(Usage {
+                input_tokens: input,
+                output_tokens: output,
+                total_tokens: input + output,
+                ..Default::default()
+            })
+        };
+        let adapter = MockAdapter::with_chat_responses(vec![
+            ChatResponse {
+                id: "1".to_string(),
+                model: "mock-model".to_string(),
+                output_text: "a".to_string(),
+                tool_calls: vec![],
+                usage: usage(10, 2),
+                ..Default::default()
+            },
+            ChatResponse {
+                id: "2".to_string(),
+                model: "mock-model".to_string(),
+                output_text: "b".to_string(),
+                tool_calls: vec![],
+                usage: usage(5, 3),
+                ..Default::default()
+            },
+        ]);
+        let aggregator = Arc::new(AtomicUsageAggregator::new());
+        let client = Client::new(Arc::new(adapter)).with_usage_sink(aggregator.clone());
+
+        client.chat(base_request()).await.unwrap();
+        client.chat(base_request()).await.unwrap();
+
+        let snapshot = aggregator.snapshot();
+        let totals = &snapshot["mock-model"];
+        assert_eq!(totals.input_tokens, 15);
+        assert_eq!(totals.output_tokens, 5);
+        assert_eq!(totals.total_tokens, 20);
+    }
+
+    #[tokio::test]
+    async fn usage_sink_keys_chat_and_stream_by_request_model() {
+        let usage = Usage {
+            input_tokens: 4,
+            output_tokens: 1,
+            total_tokens: 5,
+            ..Default::default()
+        };
+        // Providers answer with a resolved model name the request did not use.
+        let adapter = MockAdapter::with_chat_responses(vec![ChatResponse {
+            model: "mock-model-2024-08-06".to_string(),
+            usage: Some(usage.clone()),
+            ..Default::default()
+        }]);
+        adapter.stream_responses.lock().unwrap().push_back(vec![
+            StreamEvent::ResponseStart {
+                id: "resp-2".to_string(),
+                model: "mock-model-2024-08-06".to_string(),
+            },
+            StreamEvent::Usage { usage },
+            StreamEvent::Done,
+        ]);
+        let aggregator = Arc::new(AtomicUsageAggregator::new());
+        let client = Client::new(Arc::new(adapter)).with_usage_sink(aggregator.clone());
+
+        client.chat(base_request()).await.unwrap();
+        client.chat_via_stream(base_request()).await.unwrap();
+
+        let snapshot = aggregator.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot["mock-model"].total_tokens, 10);
+    }
+
+    #[tokio::test]
+    async fn chat_with_tools_streaming_collects_events_and_executes_tools() {
+        let adapter = MockAdapter::with_stream_responses(vec![
+            vec![
+                StreamEvent::ToolCallDelta {
+                    call_id: "call-1".to_string(),
+                    delta: json!({"name":"time.now","arguments":{"timezone":"UTC"}}),
+                },
+                StreamEvent::Done,
+            ],
+            vec![
+                StreamEvent::TextDelta {
+                    delta: "Current UTC time is 12:00".to_string(),
+                },
+                StreamEvent::Done,
+            ],
+        ]);
+
+        let client = Client::new(Arc::new(adapter));
+        let result = client
+            .chat_with_tools_streaming(base_request(), &EchoTools, ToolLoopOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.final_response.output_text,
+            "Current UTC time is 12:00"
+        );
+        assert_eq!(result.tool_invocations.len(), 1);
+        assert_eq!(result.iterations, 2);
+    }
+
+    #[tokio::test]
+    async fn streamed_tool_arguments_are_concatenated_before_parsing() {
+        let adapter = MockAdapter::with_stream_responses(vec![
+            vec![
+                StreamEvent::ToolCallDelta {
+                    call_id: "call-1".to_string(),
+                    delta: json!({"function": {"name": "time.now", "arguments": "{\"time"}}),
+                },
+                StreamEvent::ToolCallDelta {
+                    call_id: String::new(),
+                    delta: json!({"function": {"arguments": "zone\":"}}),
+                },
+                StreamEvent::ToolCallDelta {
+                    call_id: "call-1".to_string(),
+                    delta: json!({"function": {"name": "", "arguments": "\"UTC\"}"}}),
+                },
+                StreamEvent::Done,
+            ],
+            vec![
+                StreamEvent::TextDelta {
+                    delta: "done".to_string(),
+                },
+                StreamEvent::Done,
+            ],
+        ]);
+
+        let client = Client::new(Arc::new(adapter));
+        let result = client
+            .chat_with_tools_streaming(base_request(), &EchoTools, ToolLoopOptions::default())
+            .await
+            .unwrap();
+
+        let invocation = &result.tool_invocations[0];
+        assert_eq!(invocation.name, "time.now");
+        assert_eq!(invocation.input, json!({"timezone": "UTC"}));
+    }
+
+    #[tokio::test]
+    async fn tool_arg_callback_receives_fragments_in_order() {
+        let fragment = |arguments: &str| StreamEvent::ToolCallDelta {
+            call_id: "call-1".to_string(),
+            delta: json!({"function": {"arguments": arguments}}),
+        };
+        let adapter = MockAdapter::with_stream_responses(vec![vec![
+            fragment("{\"time"),
+            fragment("zone\":"),
+            fragment("\"UTC\"}"),
+            StreamEvent::Done,
+        ]]);
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        let client = Client::new(Arc::new(adapter));
+        let mut stream = client
+            .chat_stream_with_tool_arg_callback(base_request(), move |call_id, fragment| {
+                sink.lock().unwrap().push(format!("{call_id}:{fragment}"));
+            })
+            .await
+            .unwrap();
+
+        use futures_util::StreamExt;
+        while stream.next().await.is_some() {}
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![
+                "call-1:{\"time".to_string(),
+                "call-1:zone\":".to_string(),
+                "call-1:\"UTC\"}".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn chat_with_tools_honors_max_iterations() {
         let adapter = MockAdapter::with_chat_responses(vec![ChatResponse {
             id: "1".to_string(),
             model: "mock-model".to_string(),
@@ -390,6 +2008,7 @@ mod tests {
                 arguments: json!({}),
             }],
             usage: None,
+            ..Default::default()
         }]);
 
         let client = Client::new(Arc::new(adapter));
@@ -397,11 +2016,768 @@ mod tests {
             .chat_with_tools(
                 base_request(),
                 &EchoTools,
-                ToolLoopOptions { max_iterations: 1 },
+                ToolLoopOptions {
+                    max_iterations: 1,
+                    ..Default::default()
+                },
             )
             .await
             .unwrap_err();
 
         assert!(matches!(err, ForgeError::Provider(_)));
     }
+
+    #[tokio::test]
+    async fn shutdown_waits_for_in_flight_stream_within_grace() {
+        let adapter = MockAdapter::with_stream_responses(vec![vec![
+            StreamEvent::TextDelta {
+                delta: "hi".to_string(),
+            },
+            StreamEvent::Done,
+        ]]);
+        let client = Arc::new(Client::new(Arc::new(adapter)));
+        let mut stream = client.chat_stream(base_request()).await.unwrap();
+
+        let shutdown = tokio::spawn({
+            let client = Arc::clone(&client);
+            async move { client.shutdown(Duration::from_secs(5)).await }
+        });
+        tokio::task::yield_now().await;
+        let err = client.chat(base_request()).await.unwrap_err();
+        assert!(matches!(err, ForgeError::Internal(ref m) if m == "client is shut down"));
+
+        use futures_util::StreamExt;
+        let mut text = String::new();
+        while let Some(event) = stream.next().await {
+            if let StreamEvent::TextDelta { delta } = event.unwrap() {
+                text.push_str(&delta);
+            }
+        }
+        assert_eq!(text, "hi");
+        assert!(shutdown.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn shutdown_cancels_streams_open_after_grace() {
+        let adapter = MockAdapter::with_stream_responses(vec![vec![StreamEvent::TextDelta {
+            delta: "partial".to_string(),
+        }]]);
+        let client = Client::new(Arc::new(adapter));
+        let mut stream = client.chat_stream(base_request()).await.unwrap();
+
+        assert!(!client.shutdown(Duration::from_millis(10)).await);
+
+        use futures_util::StreamExt;
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(matches!(err, ForgeError::Internal(_)));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[derive(Debug, serde::Deserialize, JsonSchema)]
+    #[schemars(crate = "forgeai_schema::schemars")]
+    struct Weather {
+        city: String,
+        celsius: i32,
+    }
+
+    #[tokio::test]
+    async fn chat_typed_parses_output_and_reports_raw_text_on_failure() {
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![
+            ChatResponse {
+                output_text: r#"{"city": "Oslo", "celsius": -3}"#.to_string(),
+                ..Default::default()
+            },
+            ChatResponse {
+                output_text: "It is cold in Oslo.".to_string(),
+                ..Default::default()
+            },
+        ]));
+        let client = Client::new(adapter.clone());
+
+        let weather: Weather = client.chat_typed(base_request()).await.unwrap();
+        assert_eq!(weather.city, "Oslo");
+        assert_eq!(weather.celsius, -3);
+        let sent = adapter.requests.lock().unwrap()[0].response_format.clone();
+        assert!(matches!(
+            sent,
+            Some(forgeai_core::ResponseFormat::JsonSchema { ref name, .. }) if name == "Weather"
+        ));
+
+        let err = client
+            .chat_typed::<Weather>(base_request())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ForgeError::Provider(ref e) if e.message.contains("It is cold in Oslo."))
+        );
+    }
+
+    #[tokio::test]
+    async fn model_profile_fills_max_tokens_for_matching_models() {
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![
+            ChatResponse::default(),
+            ChatResponse::default(),
+            ChatResponse::default(),
+        ]));
+        let mut profiles = ModelProfileRegistry::new();
+        profiles.register(
+            "claude-*",
+            ModelProfile {
+                max_tokens: Some(4096),
+                ..Default::default()
+            },
+        );
+        let client = Client::new(adapter.clone()).with_model_profiles(profiles);
+
+        let mut request = base_request();
+        request.model = "claude-3-5-sonnet".to_string();
+        request.max_tokens = None;
+        client.chat(request.clone()).await.unwrap();
+        request.max_tokens = Some(128);
+        client.chat(request).await.unwrap();
+        let mut other = base_request();
+        other.max_tokens = None;
+        client.chat(other).await.unwrap();
+
+        let requests = adapter.requests.lock().unwrap();
+        assert_eq!(requests[0].max_tokens, Some(4096));
+        assert_eq!(requests[1].max_tokens, Some(128));
+        assert_eq!(requests[2].max_tokens, None);
+    }
+
+    #[tokio::test]
+    async fn cancelling_mid_stream_ends_the_stream() {
+        struct EndlessAdapter;
+
+        #[async_trait]
+        impl ChatAdapter for EndlessAdapter {
+            fn info(&self) -> AdapterInfo {
+                MockAdapter::with_chat_responses(vec![]).info()
+            }
+
+            async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+                std::future::pending().await
+            }
+
+            async fn chat_stream(
+                &self,
+                _request: ChatRequest,
+            ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+                let stream = try_stream! {
+                    loop {
+                        yield StreamEvent::TextDelta { delta: "tick".to_string() };
+                        tokio::time::sleep(Duration::from_millis(5)).await;
+                    }
+                };
+                Ok(Box::pin(stream))
+            }
+        }
+
+        use futures_util::StreamExt;
+        let client = Client::new(Arc::new(EndlessAdapter));
+        let cancel = CancellationToken::new();
+        let mut stream = client
+            .chat_stream_cancellable(base_request(), cancel.clone())
+            .await
+            .unwrap();
+        assert!(stream.next().await.unwrap().is_ok());
+
+        cancel.cancel();
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(matches!(err, ForgeError::Transport(ref m) if m == "cancelled"));
+        assert!(stream.next().await.is_none());
+
+        let err = client
+            .chat_cancellable(base_request(), cancel)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ForgeError::Transport(ref m) if m == "cancelled"));
+    }
+
+    #[test]
+    fn validate_request_all_reports_every_problem() {
+        let tool = ToolDefinition {
+            name: "lookup".to_string(),
+            description: None,
+            input_schema: json!({"type": "object"}),
+            raw: None,
+        };
+        let mut request = base_request();
+        request.model = " ".to_string();
+        let mut dangling = Message::new(Role::Tool, "12:00");
+        dangling.tool_call_id = Some("call-9".to_string());
+        request.messages.push(dangling);
+        request.top_p = Some(1.5);
+        request.n = Some(0);
+        request.tools = vec![tool.clone(), tool];
+        request.tool_choice = Some(ToolChoice::Function("search".to_string()));
+        request.modalities = vec![forgeai_core::Modality::Audio];
+
+        let problems = forgeai_core::validate_request_all(&request).unwrap_err();
+        assert_eq!(
+            problems,
+            vec![
+                "model cannot be empty".to_string(),
+                "message 1 answers unknown tool_call_id \"call-9\"; no earlier assistant message made that call".to_string(),
+                "top_p must be between 0.0 and 1.0, got 1.5".to_string(),
+                "n must be at least 1".to_string(),
+                "duplicate tool name: lookup".to_string(),
+                "tool_choice names unknown tool \"search\"".to_string(),
+                "audio config is required when the audio modality is requested".to_string(),
+            ]
+        );
+        assert!(
+            matches!(validate_request(&request), Err(ForgeError::Validation(ref m)) if m == "model cannot be empty")
+        );
+    }
+
+    fn request_with_tool(name: &str, input_schema: serde_json::Value) -> ChatRequest {
+        let mut request = base_request();
+        request.tools = vec![ToolDefinition {
+            name: name.to_string(),
+            description: None,
+            input_schema,
+            raw: None,
+        }];
+        request
+    }
+
+    #[test]
+    fn validate_request_rejects_dangling_tool_call_id() {
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "lookup".to_string(),
+            arguments: json!({}),
+        };
+        let mut assistant = Message::new(Role::Assistant, "");
+        assistant.tool_calls = vec![call];
+        let result = |id: &str| {
+            let mut message = Message::new(Role::Tool, "42");
+            message.tool_call_id = Some(id.to_string());
+            message
+        };
+
+        let mut request = base_request();
+        request.messages = vec![
+            Message::new(Role::User, "look it up"),
+            assistant,
+            result("call_1"),
+        ];
+        assert!(validate_request(&request).is_ok());
+
+        request.messages.push(result("call_2"));
+        assert!(matches!(
+            validate_request(&request),
+            Err(ForgeError::Validation(ref m)) if m
+                == r#"message 3 answers unknown tool_call_id "call_2"; no earlier assistant message made that call"#
+        ));
+
+        // The call has to come before its result.
+        request.messages.swap(1, 2);
+        request.messages.pop();
+        assert!(validate_request(&request).is_err());
+    }
+
+    #[test]
+    fn validate_request_requires_audio_config_for_audio_modality() {
+        let mut request = base_request();
+        request.modalities = vec![forgeai_core::Modality::Text, forgeai_core::Modality::Audio];
+        assert!(matches!(
+            validate_request(&request),
+            Err(ForgeError::Validation(ref m))
+                if m == "audio config is required when the audio modality is requested"
+        ));
+        request.audio = Some(forgeai_core::AudioConfig {
+            voice: "alloy".to_string(),
+            format: "wav".to_string(),
+        });
+        assert!(validate_request(&request).is_ok());
+    }
+
+    #[test]
+    fn validate_request_rejects_tool_choice_for_unknown_tool() {
+        let mut request = request_with_tool("lookup", json!({"type": "object"}));
+        request.tool_choice = Some(ToolChoice::Function("search".to_string()));
+        assert!(matches!(
+            validate_request(&request),
+            Err(ForgeError::Validation(ref m)) if m == r#"tool_choice names unknown tool "search""#
+        ));
+        request.tool_choice = Some(ToolChoice::Function("lookup".to_string()));
+        assert!(validate_request(&request).is_ok());
+    }
+
+    #[test]
+    fn validate_request_rejects_blank_tool_name() {
+        let request = request_with_tool("  ", json!({"type": "object"}));
+        assert!(matches!(
+            validate_request(&request),
+            Err(ForgeError::Validation(ref m)) if m == "tool at index 0 has an empty name"
+        ));
+    }
+
+    #[test]
+    fn validate_request_rejects_duplicate_tool_names() {
+        let mut request = request_with_tool("lookup", json!({"type": "object"}));
+        request.tools.push(request.tools[0].clone());
+        assert!(matches!(
+            validate_request(&request),
+            Err(ForgeError::Validation(ref m)) if m == "duplicate tool name: lookup"
+        ));
+    }
+
+    #[test]
+    fn validate_request_rejects_non_object_tool_schema() {
+        for schema in [json!("object"), json!({}), json!({"type": "string"})] {
+            let request = request_with_tool("lookup", schema);
+            assert!(matches!(
+                validate_request(&request),
+                Err(ForgeError::Validation(ref m))
+                    if m == r#"tool "lookup" input_schema must be a JSON object with "type": "object""#
+            ));
+        }
+
+        let mut raw = request_with_tool("", json!(null));
+        raw.tools[0].raw = Some(json!({"type": "web_search_preview"}));
+        assert!(validate_request(&raw).is_ok());
+    }
+
+    #[tokio::test]
+    async fn chat_via_stream_collects_deltas_and_response_id() {
+        let adapter = MockAdapter::with_stream_responses(vec![vec![
+            StreamEvent::ResponseStart {
+                id: "chatcmpl-9".to_string(),
+                model: "mock-model-2024".to_string(),
+            },
+            StreamEvent::ReasoningDelta {
+                delta: "Greet the user.".to_string(),
+            },
+            StreamEvent::TextDelta {
+                delta: "Hello, ".to_string(),
+            },
+            StreamEvent::TextDelta {
+                delta: "world".to_string(),
+            },
+            StreamEvent::FinishReason {
+                reason: forgeai_core::FinishReason::Stop,
+            },
+            StreamEvent::Done,
+        ]]);
+        let client = Client::new(Arc::new(adapter));
+
+        let response = client.chat_via_stream(base_request()).await.unwrap();
+
+        assert_eq!(response.id, "chatcmpl-9");
+        assert_eq!(response.model, "mock-model-2024");
+        assert_eq!(response.output_text, "Hello, world");
+        assert_eq!(response.reasoning.as_deref(), Some("Greet the user."));
+        assert_eq!(
+            response.finish_reason,
+            Some(forgeai_core::FinishReason::Stop)
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_json_schema_output_is_validated() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"city": {"type": "string"}, "celsius": {"type": "integer"}},
+            "required": ["city", "celsius"],
+            "additionalProperties": false
+        });
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![
+            ChatResponse {
+                output_text: r#"{"city": "Oslo", "celsius": -3}"#.to_string(),
+                ..Default::default()
+            },
+            ChatResponse {
+                output_text: r#"{"city": "Oslo", "celsius": "cold"}"#.to_string(),
+                ..Default::default()
+            },
+        ]));
+        let client = Client::new(adapter);
+        let mut request = base_request();
+        request.response_format = Some(ResponseFormat::JsonSchema {
+            name: "weather".to_string(),
+            schema,
+            strict: true,
+        });
+
+        client.chat(request.clone()).await.unwrap();
+        let err = client.chat(request).await.unwrap_err();
+        assert!(
+            matches!(&err, ForgeError::Validation(m) if m.contains("/celsius")),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn image_from_path_encodes_file_with_mime_type() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pixel.png");
+        let part = ContentPart::image_from_path(path).unwrap();
+        let ContentPart::Image {
+            url_or_base64,
+            mime_type,
+        } = part
+        else {
+            panic!("expected an image part");
+        };
+        assert_eq!(mime_type, "image/png");
+        assert!(url_or_base64.starts_with("iVBORw0KGgo"));
+
+        let err = ContentPart::image_from_path("missing.png").unwrap_err();
+        assert!(matches!(err, ForgeError::Internal(ref m) if m.contains("missing.png")));
+        let err = ContentPart::image_from_path("notes.txt").unwrap_err();
+        assert!(matches!(err, ForgeError::Validation(ref m) if m.starts_with("unsupported")));
+    }
+
+    #[tokio::test]
+    async fn retry_helpers_honor_retry_after() {
+        let limited = ForgeError::RateLimited {
+            retry_after: Some(Duration::from_millis(20)),
+        };
+        assert_eq!(wait_for_retry(&limited), Some(Duration::from_millis(20)));
+        assert_eq!(
+            wait_for_retry(&ForgeError::RateLimited { retry_after: None }),
+            None
+        );
+        assert_eq!(wait_for_retry(&ForgeError::Authentication), None);
+
+        let started = std::time::Instant::now();
+        assert!(sleep_for_retry(&limited).await);
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert!(!sleep_for_retry(&ForgeError::Transport("reset".to_string())).await);
+    }
+
+    #[tokio::test]
+    async fn collected_tool_calls_keep_stream_order() {
+        let delta = |call_id: &str, value: Value| StreamEvent::ToolCallDelta {
+            call_id: call_id.to_string(),
+            delta: value,
+        };
+        let events = vec![
+            delta(
+                "call_b",
+                json!({"index": 0, "id": "call_b", "function": {"name": "weather", "arguments": ""}}),
+            ),
+            delta(
+                "call_a",
+                json!({"index": 1, "id": "call_a", "function": {"name": "time", "arguments": ""}}),
+            ),
+            delta(
+                "",
+                json!({"index": 0, "function": {"arguments": "{\"city\":"}}),
+            ),
+            delta(
+                "",
+                json!({"index": 1, "function": {"arguments": "{\"tz\":\"UTC\"}"}}),
+            ),
+            delta(
+                "",
+                json!({"index": 0, "function": {"arguments": "\"Oslo\"}"}}),
+            ),
+            StreamEvent::Done,
+        ];
+
+        for _ in 0..10 {
+            let adapter = MockAdapter::with_stream_responses(vec![events.clone()]);
+            let client = Client::new(Arc::new(adapter));
+            let response = client.chat_via_stream(base_request()).await.unwrap();
+
+            let calls: Vec<_> = response
+                .tool_calls
+                .iter()
+                .map(|call| (call.id.as_str(), call.name.as_str(), call.arguments.clone()))
+                .collect();
+            assert_eq!(
+                calls,
+                vec![
+                    ("call_b", "weather", json!({"city": "Oslo"})),
+                    ("call_a", "time", json!({"tz": "UTC"})),
+                ]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn collected_gemini_tool_calls_stay_separate() {
+        // Gemini streams whole calls with neither an id nor an index.
+        let call = |name: &str, arguments: Value| StreamEvent::ToolCallDelta {
+            call_id: String::new(),
+            delta: json!({"name": name, "arguments": arguments}),
+        };
+        let adapter = MockAdapter::with_stream_responses(vec![vec![
+            call("weather", json!({"city": "Oslo"})),
+            call("time", json!({"tz": "UTC"})),
+            StreamEvent::Done,
+        ]]);
+        let client = Client::new(Arc::new(adapter));
+        let response = client.chat_via_stream(base_request()).await.unwrap();
+
+        let calls: Vec<_> = response
+            .tool_calls
+            .iter()
+            .map(|call| (call.name.as_str(), call.arguments.clone()))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("weather", json!({"city": "Oslo"})),
+                ("time", json!({"tz": "UTC"})),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn correlation_id_is_forwarded_as_header() {
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![
+            ChatResponse::default(),
+            ChatResponse::default(),
+        ]));
+        let client = Client::new(adapter.clone()).with_correlation_header("traceparent");
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let mut request = base_request();
+        request.metadata = json!({ CORRELATION_ID_KEY: traceparent });
+
+        client.chat(request.clone()).await.unwrap();
+        request
+            .extra_headers
+            .insert("traceparent".to_string(), "explicit".to_string());
+        client.chat(request).await.unwrap();
+
+        let requests = adapter.requests.lock().unwrap();
+        assert_eq!(requests[0].extra_headers["traceparent"], traceparent);
+        assert_eq!(requests[1].extra_headers["traceparent"], "explicit");
+    }
+
+    #[tokio::test]
+    async fn model_limits_reject_over_budget_prompt() {
+        let adapter = Arc::new(MockAdapter::with_chat_responses(vec![
+            ChatResponse::default(),
+            ChatResponse::default(),
+        ]));
+        let client = Client::new(adapter.clone())
+            .with_model_limits(ModelLimits::new().with_context_window("mock-model", 4));
+        let mut request = base_request();
+        request.messages = vec![Message::new(Role::User, "x".repeat(17))];
+
+        let err = client.chat(request.clone()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ForgeError::Validation(ref m) if m == "estimated prompt tokens exceed model context (5 > 4)"
+        ));
+        assert!(adapter.requests.lock().unwrap().is_empty());
+
+        request.messages = vec![Message::new(Role::User, "x".repeat(16))];
+        client.chat(request.clone()).await.unwrap();
+
+        let client = Client::new(adapter.clone()).with_model_limits(
+            ModelLimits::new()
+                .with_context_window("mock-model", 4)
+                .with_estimator(Arc::new(|request| request.messages.len())),
+        );
+        request.messages = vec![Message::new(Role::User, "x".repeat(100))];
+        client.chat(request).await.unwrap();
+    }
+
+    #[cfg(feature = "tokenizer")]
+    #[test]
+    fn count_tokens_includes_message_overhead() {
+        assert_eq!(
+            count_tokens("gpt-4", &[Message::new(Role::User, "hello world")]),
+            9
+        );
+        let messages = [
+            Message::new(Role::System, "You are a helpful assistant."),
+            Message::new(Role::User, "tiktoken is great!"),
+        ];
+        assert_eq!(count_tokens("gpt-4", &messages), 23);
+        assert_eq!(count_tokens("gpt-4o", &messages), 23);
+        assert_eq!(count_tokens("claude-3-5-sonnet", &messages), 23);
+
+        let mut request = base_request();
+        request.model = "gpt-4".to_string();
+        request.system = Some("You are a helpful assistant.".to_string());
+        request.messages = vec![Message::new(Role::User, "tiktoken is great!")];
+        assert_eq!(count_request_tokens(&request), 23);
+    }
+
+    #[test]
+    fn best_of_picks_highest_scoring_response() {
+        let response = |id: &str, text: &str| ChatResponse {
+            id: id.to_string(),
+            output_text: text.to_string(),
+            ..Default::default()
+        };
+        let candidates = vec![
+            response("a", "short"),
+            response("b", "the longest answer"),
+            response("c", "mid length"),
+            response("d", "equally longest!!"),
+        ];
+
+        let best = ChatResponse::best_of(candidates.clone(), |r| r.output_text.len() as f64);
+        assert_eq!(best.id, "b");
+        let best = ChatResponse::best_of(candidates, |_| 1.0);
+        assert_eq!(best.id, "a");
+        assert_eq!(ChatResponse::best_of(vec![], |_| 0.0).id, "");
+    }
+
+    fn hello_world_stream() -> Vec<StreamEvent> {
+        vec![
+            StreamEvent::ResponseStart {
+                id: "chatcmpl-7".to_string(),
+                model: "mock-model".to_string(),
+            },
+            StreamEvent::TextDelta {
+                delta: "Hello, ".to_string(),
+            },
+            StreamEvent::TextDelta {
+                delta: "world".to_string(),
+            },
+            StreamEvent::Done,
+        ]
+    }
+
+    #[tokio::test]
+    async fn stream_text_yields_only_text_pieces() {
+        let mut events = hello_world_stream();
+        events.insert(
+            2,
+            StreamEvent::ToolCallDelta {
+                call_id: "call_1".to_string(),
+                delta: json!({"name": "lookup", "arguments": "{}"}),
+            },
+        );
+        events.insert(
+            3,
+            StreamEvent::Usage {
+                usage: Usage {
+                    input_tokens: 3,
+                    output_tokens: 2,
+                    total_tokens: 5,
+                    ..Default::default()
+                },
+            },
+        );
+        events.push(StreamEvent::TextDelta {
+            delta: "after done".to_string(),
+        });
+        let adapter = MockAdapter::with_stream_responses(vec![events]);
+        let client = Client::new(Arc::new(adapter));
+
+        use futures_util::StreamExt;
+        let pieces: Vec<String> = client
+            .stream_text(base_request())
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(pieces, vec!["Hello, ", "world"]);
+    }
+
+    #[tokio::test]
+    async fn chat_via_stream_lossy_keeps_partial_output_on_error() {
+        struct FailingAdapter;
+
+        #[async_trait]
+        impl ChatAdapter for FailingAdapter {
+            fn info(&self) -> AdapterInfo {
+                MockAdapter::with_chat_responses(vec![]).info()
+            }
+
+            async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, ForgeError> {
+                Err(ForgeError::Internal("unused".to_string()))
+            }
+
+            async fn chat_stream(
+                &self,
+                _request: ChatRequest,
+            ) -> Result<StreamResult<StreamEvent>, ForgeError> {
+                Ok(Box::pin(futures_util::stream::iter(vec![
+                    Ok(StreamEvent::TextDelta {
+                        delta: "Hello, ".to_string(),
+                    }),
+                    Ok(StreamEvent::TextDelta {
+                        delta: "wor".to_string(),
+                    }),
+                    Err(ForgeError::Transport("connection reset".to_string())),
+                ])))
+            }
+        }
+
+        let client = Client::new(Arc::new(FailingAdapter));
+        let interrupted = client
+            .chat_via_stream_lossy(base_request())
+            .await
+            .unwrap_err();
+        assert_eq!(interrupted.partial.output_text, "Hello, wor");
+        assert!(
+            matches!(interrupted.error, ForgeError::Transport(ref m) if m == "connection reset")
+        );
+
+        let adapter = MockAdapter::with_stream_responses(vec![hello_world_stream()]);
+        let client = Client::new(Arc::new(adapter));
+        let response = client.chat_via_stream_lossy(base_request()).await.unwrap();
+        assert_eq!(response.output_text, "Hello, world");
+    }
+
+    #[tokio::test]
+    async fn stream_to_writer_writes_deltas_and_returns_response() {
+        let adapter = MockAdapter::with_stream_responses(vec![hello_world_stream()]);
+        let client = Client::new(Arc::new(adapter));
+        let mut buffer = Vec::new();
+
+        let response = client
+            .stream_to_writer(base_request(), &mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"Hello, world");
+        assert_eq!(response.id, "chatcmpl-7");
+        assert_eq!(response.output_text, "Hello, world");
+    }
+
+    #[tokio::test]
+    async fn stream_to_channel_forwards_events_and_errors_via_handle() {
+        let adapter = MockAdapter::with_stream_responses(vec![hello_world_stream()]);
+        let client = Client::new(Arc::new(adapter));
+
+        let (mut events, task) = client.stream_to_channel(base_request()).await;
+        let mut received = Vec::new();
+        while let Some(event) = events.recv().await {
+            received.push(event);
+        }
+        assert_eq!(received.len(), 4);
+        assert!(matches!(received[3], StreamEvent::Done));
+        let response = task.await.unwrap().unwrap();
+        assert_eq!(response.output_text, "Hello, world");
+
+        // No stream left, so opening the next one fails.
+        let (mut events, task) = client.stream_to_channel(base_request()).await;
+        assert!(events.recv().await.is_none());
+        assert!(matches!(task.await.unwrap(), Err(ForgeError::Internal(_))));
+    }
+
+    #[tokio::test]
+    async fn collect_stream_skips_unknown_events() {
+        let unknown: StreamEvent =
+            serde_json::from_value(json!({"type": "citation_delta", "uri": "https://example.com"}))
+                .unwrap();
+        assert!(matches!(unknown, StreamEvent::Unknown));
+        let adapter = MockAdapter::with_stream_responses(vec![vec![
+            StreamEvent::TextDelta {
+                delta: "Hel".to_string(),
+            },
+            unknown,
+            StreamEvent::TextDelta {
+                delta: "lo".to_string(),
+            },
+            StreamEvent::Done,
+        ]]);
+        let client = Client::new(Arc::new(adapter));
+
+        let response = client.chat_via_stream(base_request()).await.unwrap();
+
+        assert_eq!(response.output_text, "Hello");
+    }
 }
@@ -0,0 +1,76 @@
+//! Context-window limits checked before a request is sent.
+
+use forgeai_core::{ChatRequest, ForgeError};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Estimates the prompt tokens of a request, e.g. with a real tokenizer.
+pub type TokenEstimator = Arc<dyn Fn(&ChatRequest) -> usize + Send + Sync>;
+
+/// Context windows keyed by exact model name, and the estimator used to
+/// check requests against them. Models without a limit are not checked.
+#[derive(Clone)]
+pub struct ModelLimits {
+    context_tokens: HashMap<String, usize>,
+    estimator: TokenEstimator,
+}
+
+impl std::fmt::Debug for ModelLimits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelLimits")
+            .field("context_tokens", &self.context_tokens)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ModelLimits {
+    fn default() -> Self {
+        Self {
+            context_tokens: HashMap::new(),
+            estimator: Arc::new(estimate_prompt_tokens),
+        }
+    }
+}
+
+impl ModelLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the context window of `model` to `tokens`.
+    pub fn with_context_window(mut self, model: impl Into<String>, tokens: usize) -> Self {
+        self.context_tokens.insert(model.into(), tokens);
+        self
+    }
+
+    /// Replaces the default `estimate_prompt_tokens` heuristic.
+    pub fn with_estimator(mut self, estimator: TokenEstimator) -> Self {
+        self.estimator = estimator;
+        self
+    }
+
+    pub(crate) fn check(&self, request: &ChatRequest) -> Result<(), ForgeError> {
+        let Some(&limit) = self.context_tokens.get(&request.model) else {
+            return Ok(());
+        };
+        let estimate = (self.estimator)(request);
+        if estimate > limit {
+            return Err(ForgeError::Validation(format!(
+                "estimated prompt tokens exceed model context ({estimate} > {limit})"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Rough prompt size: one token per four characters of system prompt and
+/// message text, rounded up. Images and tool schemas are not counted.
+pub fn estimate_prompt_tokens(request: &ChatRequest) -> usize {
+    let chars = request.system.as_deref().map_or(0, |s| s.chars().count())
+        + request
+            .messages
+            .iter()
+            .map(|message| message.content.text().chars().count())
+            .sum::<usize>();
+    chars.div_ceil(4)
+}
@@ -0,0 +1,70 @@
+//! Per-model request defaults and capability overrides.
+
+use forgeai_core::{CapabilityMatrix, ChatRequest};
+
+/// Defaults for one model or model family. Fields left `None` fall through to
+/// the request, then to the client defaults.
+#[derive(Debug, Clone, Default)]
+pub struct ModelProfile {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
+    /// The model rejects `temperature` (e.g. OpenAI `o1`), so it is cleared
+    /// even when the request sets it.
+    pub omit_temperature: bool,
+    /// Replaces the adapter's `CapabilityMatrix` for matching models.
+    pub capabilities: Option<CapabilityMatrix>,
+}
+
+impl ModelProfile {
+    /// Fills parameters the request leaves unset.
+    pub(crate) fn apply(&self, request: &mut ChatRequest) {
+        if self.omit_temperature {
+            request.temperature = None;
+        } else if request.temperature.is_none() {
+            request.temperature = self.temperature;
+        }
+        if request.top_p.is_none() {
+            request.top_p = self.top_p;
+        }
+        if request.max_tokens.is_none() {
+            request.max_tokens = self.max_tokens;
+        }
+    }
+}
+
+/// `ModelProfile`s keyed by model name. A pattern is either an exact model
+/// name or a prefix ending in `*`, such as `claude-*`; an exact match wins,
+/// then the longest matching prefix.
+#[derive(Debug, Clone, Default)]
+pub struct ModelProfileRegistry {
+    profiles: Vec<(String, ModelProfile)>,
+}
+
+impl ModelProfileRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `profile` for `pattern`, replacing any profile already
+    /// registered for the same pattern.
+    pub fn register(&mut self, pattern: impl Into<String>, profile: ModelProfile) -> &mut Self {
+        let pattern = pattern.into();
+        self.profiles.retain(|(existing, _)| *existing != pattern);
+        self.profiles.push((pattern, profile));
+        self
+    }
+
+    /// The profile that applies to `model`, if any.
+    pub fn lookup(&self, model: &str) -> Option<&ModelProfile> {
+        self.profiles
+            .iter()
+            .filter_map(|(pattern, profile)| match pattern.strip_suffix('*') {
+                Some(prefix) if model.starts_with(prefix) => Some((prefix.len(), profile)),
+                None if pattern == model => Some((usize::MAX, profile)),
+                _ => None,
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, profile)| profile)
+    }
+}
@@ -0,0 +1,100 @@
+//! In-flight call tracking for `Client::shutdown`.
+
+use forgeai_core::{ForgeError, StreamEvent, StreamResult};
+use futures_util::{stream, StreamExt};
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{watch, Notify};
+
+pub(crate) struct Lifecycle {
+    closing: AtomicBool,
+    active: AtomicUsize,
+    idle: Notify,
+    cancel: watch::Sender<bool>,
+}
+
+/// Counts as one in-flight call until dropped.
+pub(crate) struct ActiveCall(Arc<Lifecycle>);
+
+impl Drop for ActiveCall {
+    fn drop(&mut self) {
+        if self.0.active.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl Lifecycle {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            closing: AtomicBool::new(false),
+            active: AtomicUsize::new(0),
+            idle: Notify::new(),
+            cancel: watch::Sender::new(false),
+        })
+    }
+
+    /// Registers a new call, or fails once shutdown has started.
+    pub(crate) fn begin(self: &Arc<Self>) -> Result<ActiveCall, ForgeError> {
+        // Counted before checking `closing` so `wait_idle` cannot miss a call
+        // that raced with shutdown.
+        self.active.fetch_add(1, Ordering::SeqCst);
+        let call = ActiveCall(Arc::clone(self));
+        if self.closing.load(Ordering::SeqCst) {
+            return Err(ForgeError::Internal("client is shut down".to_string()));
+        }
+        Ok(call)
+    }
+
+    pub(crate) fn close(&self) {
+        self.closing.store(true, Ordering::SeqCst);
+    }
+
+    pub(crate) async fn wait_idle(&self) {
+        loop {
+            let mut notified = pin!(self.idle.notified());
+            notified.as_mut().enable();
+            if self.active.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Ends every stream returned by `track` that is still open.
+    pub(crate) fn cancel(&self) {
+        self.cancel.send_replace(true);
+    }
+
+    /// Keeps `call` active until the stream yields `Done` or an error, or is
+    /// dropped. A cancelled stream ends with a final `Internal` error.
+    pub(crate) fn track(
+        &self,
+        events: StreamResult<StreamEvent>,
+        call: ActiveCall,
+    ) -> StreamResult<StreamEvent> {
+        let mut cancel = self.cancel.subscribe();
+        let mut after = cancel.clone();
+        let cancelled = async move {
+            let _ = cancel.wait_for(|cancelled| *cancelled).await;
+        };
+        let mut call = Some(call);
+        let events = events.take_until(cancelled).map(move |item| {
+            if matches!(item, Ok(StreamEvent::Done) | Err(_)) {
+                call.take();
+            }
+            item
+        });
+        let interrupted = stream::once(async move { *after.borrow_and_update() }).filter_map(
+            |cancelled| async move {
+                cancelled.then(|| {
+                    Err(ForgeError::Internal(
+                        "stream cancelled by client shutdown".to_string(),
+                    ))
+                })
+            },
+        );
+        Box::pin(events.chain(interrupted))
+    }
+}
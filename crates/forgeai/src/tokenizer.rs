@@ -0,0 +1,55 @@
+//! Token counting with OpenAI's BPE encodings, for pre-flight budget checks.
+
+use forgeai_core::{ChatRequest, Message, Role};
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::{cl100k_base_singleton, o200k_base_singleton};
+
+/// Tokens OpenAI adds around every message (`<|start|>{role}\n…<|end|>\n`).
+const TOKENS_PER_MESSAGE: usize = 3;
+/// Tokens priming the reply with `<|start|>assistant<|message|>`.
+const REPLY_PRIMING_TOKENS: usize = 3;
+
+/// Prompt tokens `messages` use with `model`, counted the way OpenAI
+/// documents for chat models: per-message overhead plus the encoded role and
+/// text, plus the reply priming. Models that use `o200k_base` are counted
+/// with it; every other model, including non-OpenAI ones, falls back to
+/// `cl100k_base`, which is close but not exact for them. Images and tool
+/// calls are not counted.
+pub fn count_tokens(model: &str, messages: &[Message]) -> usize {
+    let bpe = match get_tokenizer(model) {
+        Some(Tokenizer::O200kBase) => o200k_base_singleton(),
+        _ => cl100k_base_singleton(),
+    };
+    let bpe = bpe.lock();
+    let count = |text: &str| bpe.encode_ordinary(text).len();
+    messages
+        .iter()
+        .map(|message| {
+            TOKENS_PER_MESSAGE + count(role_name(&message.role)) + count(&message.content.text())
+        })
+        .sum::<usize>()
+        + REPLY_PRIMING_TOKENS
+}
+
+/// `count_tokens` for a whole request, with `system` counted as a leading
+/// system message. Usable as a `TokenEstimator` for `ModelLimits`.
+pub fn count_request_tokens(request: &ChatRequest) -> usize {
+    match &request.system {
+        Some(system) => {
+            let mut messages = Vec::with_capacity(request.messages.len() + 1);
+            messages.push(Message::new(Role::System, system.as_str()));
+            messages.extend(request.messages.iter().cloned());
+            count_tokens(&request.model, &messages)
+        }
+        None => count_tokens(&request.model, &request.messages),
+    }
+}
+
+fn role_name(role: &Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool => "tool",
+    }
+}
@@ -0,0 +1,45 @@
+//! Per-model token usage reporting.
+
+use forgeai_core::Usage;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Receives token usage reported by successful calls made through `Client`.
+pub trait UsageSink: Send + Sync {
+    /// `model` is the request's model, not the possibly more specific name
+    /// the provider answers with, so `chat` and streaming calls to the same
+    /// model are keyed alike.
+    fn record(&self, model: &str, usage: &Usage);
+}
+
+/// `UsageSink` that accumulates usage totals per model.
+#[derive(Debug, Default)]
+pub struct AtomicUsageAggregator {
+    totals: Mutex<HashMap<String, Usage>>,
+}
+
+impl AtomicUsageAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the accumulated totals keyed by model name.
+    pub fn snapshot(&self) -> HashMap<String, Usage> {
+        self.totals
+            .lock()
+            .map(|totals| totals.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl UsageSink for AtomicUsageAggregator {
+    fn record(&self, model: &str, usage: &Usage) {
+        let Ok(mut totals) = self.totals.lock() else {
+            return;
+        };
+        totals
+            .entry(model.to_string())
+            .or_default()
+            .accumulate(usage);
+    }
+}
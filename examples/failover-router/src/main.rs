@@ -4,6 +4,7 @@ use forgeai_adapter_anthropic::AnthropicAdapter;
 use forgeai_adapter_openai::OpenAiAdapter;
 use forgeai_router::FailoverRouter;
 use serde_json::json;
+use std::collections::HashMap;
 use std::sync::Arc;
 use url::Url;
 
@@ -26,13 +27,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let response: ChatResponse = client
         .chat(ChatRequest {
             model: "gpt-4o-mini".to_string(),
-            messages: vec![Message {
-                role: Role::User,
-                content: "Give me a one-line Rust tip".to_string(),
-            }],
+            messages: vec![Message::new(Role::User, "Give me a one-line Rust tip")],
+            system: None,
             temperature: Some(0.2),
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             max_tokens: Some(128),
+            n: None,
+            seed: None,
+            stop: vec![],
             tools: vec![],
+            tool_choice: None,
+            response_format: None,
+            modalities: vec![],
+            audio: None,
+            extra_headers: HashMap::new(),
             metadata: json!({}),
         })
         .await?;
@@ -1,7 +1,5 @@
-use forgeai::forgeai_core::{ChatRequest, Message, Role};
 use forgeai::Client;
 use forgeai_adapter_openai::OpenAiAdapter;
-use serde_json::json;
 use std::sync::Arc;
 
 #[tokio::main]
@@ -9,20 +7,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let adapter = OpenAiAdapter::from_env()?;
     let client = Client::new(Arc::new(adapter));
 
-    let response = client
-        .chat(ChatRequest {
-            model: "gpt-4o-mini".to_string(),
-            messages: vec![Message {
-                role: Role::User,
-                content: "Hello from forgeai-rs".to_string(),
-            }],
-            temperature: Some(0.2),
-            max_tokens: Some(128),
-            tools: vec![],
-            metadata: json!({}),
-        })
+    let text = client
+        .complete("gpt-4o-mini", "Hello from forgeai-rs")
         .await?;
 
-    println!("{}", response.output_text);
+    println!("{text}");
     Ok(())
 }
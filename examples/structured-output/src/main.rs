@@ -1,7 +1,8 @@
 use forgeai_schema::type_schema;
-use serde::Serialize;
+use schemars::JsonSchema;
 
-#[derive(Serialize)]
+#[allow(dead_code)]
+#[derive(JsonSchema)]
 struct Answer {
     value: String,
     confidence: f32,
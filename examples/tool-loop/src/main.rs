@@ -3,6 +3,7 @@ use forgeai::forgeai_tools::{ToolError, ToolExecutor};
 use forgeai::{Client, ToolLoopOptions};
 use forgeai_adapter_openai::OpenAiAdapter;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 struct DemoTools;
@@ -32,13 +33,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let request = ChatRequest {
         model: "gpt-4o-mini".to_string(),
-        messages: vec![Message {
-            role: Role::User,
-            content: "What time is it in UTC? Use the time.now tool.".to_string(),
-        }],
+        messages: vec![Message::new(Role::User, "What time is it in UTC? Use the time.now tool.")],
+        system: None,
         temperature: Some(0.1),
+        top_p: None,
+        frequency_penalty: None,
+        presence_penalty: None,
         max_tokens: Some(256),
+        n: None,
+        seed: None,
+        stop: vec![],
         tools: vec![],
+        tool_choice: None,
+        response_format: None,
+        modalities: vec![],
+        audio: None,
+        extra_headers: HashMap::new(),
         metadata: json!({}),
     };
 